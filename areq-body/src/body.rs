@@ -1,6 +1,7 @@
 use {
     bytes::{Buf, Bytes, BytesMut},
     futures_lite::prelude::*,
+    http::HeaderMap,
     std::{
         convert::Infallible,
         future,
@@ -31,6 +32,16 @@ pub trait Body {
 
     /// Returns a size [hint](Hint) for the body.
     fn size_hint(&self) -> Hint;
+
+    /// Asynchronously retrieves trailer fields sent after the body data.
+    ///
+    /// Should be called once the [`chunk`](Body::chunk) returns `None`.
+    /// Returns `None` if the body has no trailers. By default,
+    /// a body has no trailers.
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, Error>> {
+        None
+    }
 }
 
 /// Body size hint.
@@ -60,6 +71,9 @@ pub enum Hint {
     /// `Transfer-Encoding: chunked` header for the request.
     Chunked {
         /// Indicates the end of the body stream.
+        ///
+        /// Only data chunks are considered, so
+        /// [trailers](Body::trailers) may still follow the end.
         end: bool,
     },
 }
@@ -145,6 +159,11 @@ where
     fn size_hint(&self) -> Hint {
         (**self).size_hint()
     }
+
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, Error>> {
+        (**self).trailers().await
+    }
 }
 
 impl Body for () {
@@ -265,6 +284,14 @@ where
             None => Hint::Empty,
         }
     }
+
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, Error>> {
+        match self {
+            Some(body) => body.trailers().await,
+            None => None,
+        }
+    }
 }
 
 impl Body for Infallible {
@@ -465,6 +492,41 @@ where
     }
 }
 
+/// The body with [trailers](Body::trailers) sent after its data.
+///
+/// Created by the [`with_trailers`](BodyExt::with_trailers) method.
+/// The size hint of the inner body is turned into the
+/// [chunked](Hint::Chunked) one, since trailers can only
+/// follow the chunked body.
+pub struct WithTrailers<B> {
+    body: B,
+    trailers: Option<HeaderMap>,
+}
+
+impl<B> Body for WithTrailers<B>
+where
+    B: Body,
+{
+    type Chunk = B::Chunk;
+
+    #[inline]
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, Error>> {
+        self.body.chunk().await
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Hint {
+        Hint::Chunked {
+            end: self.body.size_hint().end(),
+        }
+    }
+
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, Error>> {
+        self.trailers.take().map(Ok)
+    }
+}
+
 /// A trait for converting into a [body](Body).
 pub trait IntoBody: Sized {
     type Chunk: Buf;
@@ -559,6 +621,18 @@ pub trait PollBody {
     ) -> Poll<Option<Result<Self::Chunk, Error>>>;
 
     fn size_hint(&self) -> Hint;
+
+    /// Polls trailer fields sent after the body data.
+    ///
+    /// By default, a body has no trailers.
+    #[inline]
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<HeaderMap, Error>>> {
+        _ = cx;
+        Poll::Ready(None)
+    }
 }
 
 impl<P> Body for Pin<P>
@@ -576,6 +650,11 @@ where
     fn size_hint(&self) -> Hint {
         self.as_ref().size_hint()
     }
+
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, Error>> {
+        future::poll_fn(|cx| self.as_mut().poll_trailers(cx)).await
+    }
 }
 
 /// Alias for a boxed [body](PollBody).
@@ -732,12 +811,27 @@ pub trait BodyExt: IntoBody {
         }
     }
 
+    /// Attaches [trailers](Body::trailers) sent after the body data.
+    #[inline]
+    fn with_trailers(self, trailers: HeaderMap) -> WithTrailers<Self::Body> {
+        WithTrailers {
+            body: self.into_body(),
+            trailers: Some(trailers),
+        }
+    }
+
     #[inline]
     fn into_poll_body(self) -> impl PollBody<Chunk = Self::Chunk> {
-        unfold(self.into_body(), |mut body| async {
-            match body.chunk().await {
-                Some(res) => Step::Next { body, res },
-                None => Step::End(body),
+        unfold(self.into_body(), |mut body, want| async move {
+            match want {
+                Want::Chunk => match body.chunk().await {
+                    Some(res) => Step::Next { body, res },
+                    None => Step::End(body),
+                },
+                Want::Trailers => {
+                    let trailers = body.trailers().await;
+                    Step::Trailers { body, trailers }
+                }
             }
         })
     }
@@ -753,38 +847,120 @@ pub trait BodyExt: IntoBody {
 
 impl<B> BodyExt for B where B: IntoBody {}
 
+#[derive(Clone, Copy)]
+enum Want {
+    Chunk,
+    Trailers,
+}
+
 enum Step<B, C> {
-    Next { body: B, res: Result<C, Error> },
+    Next {
+        body: B,
+        res: Result<C, Error>,
+    },
     End(B),
+    Trailers {
+        body: B,
+        trailers: Option<Result<HeaderMap, Error>>,
+    },
+}
+
+impl<B, C> Step<B, C> {
+    fn is(&self, want: Want) -> bool {
+        match self {
+            Self::Next { .. } | Self::End(_) => matches!(want, Want::Chunk),
+            Self::Trailers { .. } => matches!(want, Want::Trailers),
+        }
+    }
 }
 
 #[inline]
 fn unfold<B, F, U>(body: B, f: F) -> Unfold<B, F, U>
 where
     B: Body,
-    F: FnMut(B) -> U,
+    F: FnMut(B, Want) -> U,
     U: Future<Output = Step<B, B::Chunk>>,
 {
     Unfold {
         body: Some(body),
         f,
         fu: None,
+        ready: None,
     }
 }
 
 pin_project_lite::pin_project! {
-    struct Unfold<B, F, U> {
+    struct Unfold<B, F, U>
+    where
+        B: Body,
+    {
         body: Option<B>,
         f: F,
         #[pin]
         fu: Option<U>,
+        // the completed step of another kind, if its
+        // future was dropped before it was polled to the end
+        ready: Option<Step<(), B::Chunk>>,
+    }
+}
+
+impl<B, F, U> Unfold<B, F, U>
+where
+    B: Body,
+    F: FnMut(B, Want) -> U,
+    U: Future<Output = Step<B, B::Chunk>>,
+{
+    fn poll_step(
+        self: Pin<&mut Self>,
+        want: Want,
+        cx: &mut Context<'_>,
+    ) -> Poll<Step<(), B::Chunk>> {
+        let mut me = self.project();
+        if let Some(step) = me.ready.take_if(|step| step.is(want)) {
+            return Poll::Ready(step);
+        }
+
+        loop {
+            if let Some(body) = me.body.take() {
+                me.fu.set(Some((me.f)(body, want)));
+            }
+
+            let fu = me
+                .fu
+                .as_mut()
+                .as_pin_mut()
+                .expect("future should always be here");
+            let step = match fu.poll(cx) {
+                Poll::Ready(Step::Next { body, res }) => {
+                    *me.body = Some(body);
+                    Step::Next { body: (), res }
+                }
+                Poll::Ready(Step::End(body)) => {
+                    *me.body = Some(body);
+                    Step::End(())
+                }
+                Poll::Ready(Step::Trailers { body, trailers }) => {
+                    *me.body = Some(body);
+                    Step::Trailers { body: (), trailers }
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if step.is(want) {
+                return Poll::Ready(step);
+            }
+
+            // the step of another kind was requested before and
+            // then cancelled, so keep it until it's requested again
+            *me.ready = Some(step);
+        }
     }
 }
 
 impl<B, F, U> PollBody for Unfold<B, F, U>
 where
     B: Body,
-    F: FnMut(B) -> U,
+    F: FnMut(B, Want) -> U,
     U: Future<Output = Step<B, B::Chunk>>,
 {
     type Chunk = B::Chunk;
@@ -794,21 +970,23 @@ where
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Self::Chunk, Error>>> {
-        let mut me = self.project();
-        if let Some(body) = me.body.take() {
-            me.fu.set(Some((me.f)(body)));
+        match self.poll_step(Want::Chunk, cx) {
+            Poll::Ready(Step::Next { res, .. }) => Poll::Ready(Some(res)),
+            Poll::Ready(Step::End(())) => Poll::Ready(None),
+            Poll::Ready(Step::Trailers { .. }) => unreachable!("the step is a chunk"),
+            Poll::Pending => Poll::Pending,
         }
+    }
 
-        let fu = me.fu.as_pin_mut().expect("future should always be here");
-
-        match fu.poll(cx) {
-            Poll::Ready(Step::Next { body, res }) => {
-                *me.body = Some(body);
-                Poll::Ready(Some(res))
-            }
-            Poll::Ready(Step::End(body)) => {
-                *me.body = Some(body);
-                Poll::Ready(None)
+    #[inline]
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<HeaderMap, Error>>> {
+        match self.poll_step(Want::Trailers, cx) {
+            Poll::Ready(Step::Trailers { trailers, .. }) => Poll::Ready(trailers),
+            Poll::Ready(Step::Next { .. } | Step::End(())) => {
+                unreachable!("the step is trailers")
             }
             Poll::Pending => Poll::Pending,
         }
@@ -1093,6 +1271,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn trailers() {
+        use http::HeaderValue;
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        let body = "hi".with_trailers(trailers.clone());
+        assert!(
+            matches!(body.size_hint(), Hint::Chunked { end: false }),
+            "trailers follow the chunked body",
+        );
+
+        let mut boxed_body = body.boxed_local();
+        let chunk = future::block_on(boxed_body.chunk()).transpose().ok();
+        assert_eq!(chunk, Some(Some("hi".as_bytes())));
+        assert!(
+            future::block_on(boxed_body.chunk()).is_none(),
+            "the data ends"
+        );
+        assert!(boxed_body.size_hint().end(), "the data ends");
+
+        let actual = future::block_on(boxed_body.trailers()).transpose().ok();
+        assert_eq!(actual, Some(Some(trailers)));
+        assert!(
+            future::block_on(boxed_body.trailers()).is_none(),
+            "no more trailers"
+        );
+        assert!(
+            future::block_on("hi".trailers()).is_none(),
+            "no trailers by default"
+        );
+    }
+
+    #[test]
+    fn cancelled_chunk() {
+        use http::HeaderValue;
+
+        /// The body that yields once before every chunk.
+        struct Slow<B>(B);
+
+        impl<B> Body for Slow<B>
+        where
+            B: Body,
+        {
+            type Chunk = B::Chunk;
+
+            async fn chunk(&mut self) -> Option<Result<Self::Chunk, Error>> {
+                future::yield_now().await;
+                self.0.chunk().await
+            }
+
+            async fn trailers(&mut self) -> Option<Result<HeaderMap, Error>> {
+                self.0.trailers().await
+            }
+
+            fn size_hint(&self) -> Hint {
+                self.0.size_hint()
+            }
+        }
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        let mut boxed_body = Slow("hi".with_trailers(trailers.clone())).boxed_local();
+        let cancelled = future::block_on(future::poll_once(boxed_body.chunk()));
+        assert!(cancelled.is_none(), "the chunk is pending");
+
+        let actual = future::block_on(boxed_body.trailers()).transpose().ok();
+        assert_eq!(actual, Some(Some(trailers)));
+
+        let chunk = future::block_on(boxed_body.chunk()).transpose().ok();
+        assert_eq!(chunk, Some(Some("hi".as_bytes())), "the chunk is kept");
+        assert!(
+            future::block_on(boxed_body.chunk()).is_none(),
+            "the data ends"
+        );
+    }
+
     #[cfg(feature = "rtn")]
     #[test]
     fn boxed() {
@@ -1,8 +1,8 @@
 use crate::body::{Body, BodyExt, Boxed, IntoBody};
 
 /// Trait alias for a thread-safe [body](Body).
-pub trait SendBody: Body<Chunk: Send, chunk(..): Send> + Send {}
-impl<B> SendBody for B where B: Body<Chunk: Send, chunk(..): Send> + Send {}
+pub trait SendBody: Body<Chunk: Send, chunk(..): Send, trailers(..): Send> + Send {}
+impl<B> SendBody for B where B: Body<Chunk: Send, chunk(..): Send, trailers(..): Send> + Send {}
 
 /// Extension methods for a thread-safe [body](Body).
 pub trait SendBodyExt: IntoBody {
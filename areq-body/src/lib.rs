@@ -18,6 +18,7 @@ pub mod prelude {
 
 pub use crate::body::{
     Body, BodyExt, Boxed, BoxedLocal, Chunked, Deferred, Full, Hint, IntoBody, PollBody, Void,
+    WithTrailers,
 };
 
 #[cfg(feature = "rtn")]
@@ -4,21 +4,47 @@ use {
         error::Error,
         handler::{Handler, Parser, ReadStrategy},
         headers::{self, ContentLen},
+        upgrade::Upgraded,
     },
-    async_channel::{Receiver, Sender},
+    async_channel::{Receiver, SendError, Sender},
     bytes::{Buf, Bytes},
-    futures_lite::prelude::*,
-    http::{HeaderValue, Request, Response, header},
+    futures_lite::{
+        future,
+        io::{ReadHalf, WriteHalf},
+        prelude::*,
+    },
+    http::{HeaderMap, HeaderValue, Method, Request, Response, StatusCode, header},
     std::{
         fmt, io,
         pin::{self, Pin},
+        sync::Arc,
+        time::Duration,
     },
 };
 
+type Informational = Arc<dyn Fn(&Response<()>) + Send + Sync>;
+
+type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+#[derive(Clone)]
+struct ExpectContinue {
+    timeout: Duration,
+    timer: Arc<dyn Fn(Duration) -> Sleep + Send + Sync>,
+}
+
+impl ExpectContinue {
+    fn sleep(&self) -> Sleep {
+        (self.timer)(self.timeout)
+    }
+}
+
 #[derive(Clone)]
 pub struct Config {
     parser: Parser,
     read_strategy: ReadStrategy,
+    pipeline: usize,
+    informational: Option<Informational>,
+    expect: Option<ExpectContinue>,
 }
 
 impl Config {
@@ -34,6 +60,54 @@ impl Config {
         self
     }
 
+    /// Enables pipelining with up to `n` requests in flight.
+    ///
+    /// Requests are written without waiting for responses to previous
+    /// ones, while responses are read in the same order. By default,
+    /// it's `1`, so the next request is written only after the previous
+    /// response is completely read.
+    #[inline]
+    pub fn pipeline(mut self, n: usize) -> Self {
+        self.pipeline = n.max(1);
+        self
+    }
+
+    /// Sets the callback called on each informational `1xx` response.
+    ///
+    /// Informational responses like `103 Early Hints` precede the final
+    /// response and have no body. By default, they're skipped.
+    #[inline]
+    pub fn on_informational<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Response<()>) + Send + Sync + 'static,
+    {
+        self.informational = Some(Arc::new(f));
+        self
+    }
+
+    /// Enables the `Expect: 100-continue` mechanism for requests with a body.
+    ///
+    /// The request head is sent first, then the body is sent only after
+    /// the server responds with `100 Continue` or the timeout elapses.
+    /// If the server responds with a final status instead, the body is
+    /// skipped and the response is returned, so the connection is closed
+    /// after it.
+    ///
+    /// The timer is a function that waits for the given duration. Requests
+    /// with the `Expect: 100-continue` header set explicitly are handled the
+    /// same way. If the mechanism isn't enabled, the body of such requests
+    /// is sent right away, since a server may ignore the expectation.
+    #[inline]
+    pub fn expect_continue<T, U>(mut self, timeout: Duration, timer: T) -> Self
+    where
+        T: Fn(Duration) -> U + Send + Sync + 'static,
+        U: Future<Output = ()> + Send + 'static,
+    {
+        let timer = Arc::new(move |dur| -> Sleep { Box::pin(timer(dur)) });
+        self.expect = Some(ExpectContinue { timeout, timer });
+        self
+    }
+
     #[inline]
     pub fn handshake<I, B>(self, io: I) -> (Requester<B>, impl Future<Output = ()>)
     where
@@ -41,22 +115,60 @@ impl Config {
         B: Body,
     {
         let (send_req, recv_req) = async_channel::bounded(1);
-        let (send_res, recv_res) = async_channel::bounded(1);
-        let reqs = Requester { send_req, recv_res };
+        let (alive, closed) = async_channel::bounded(1);
+        let reqs = Requester { send_req, closed };
         let conn = async move {
             let io = pin::pin!(io);
+            let (read, write) = futures_lite::io::split(io);
             let conn = Connection {
                 recv_req,
-                send_res,
-                io: Handler::new(io, self.read_strategy),
+                read: Handler::new(read, self.read_strategy),
+                write: Handler::new(write, self.read_strategy),
                 parser: self.parser,
+                pipeline: self.pipeline,
+                informational: self.informational,
+                expect: self.expect,
             };
 
             connect(conn).await;
+            drop(alive);
         };
 
         (reqs, conn)
     }
+
+    /// Sends the request and reads the response head, handing back the
+    /// connection.
+    ///
+    /// Unlike [`handshake`](Config::handshake), the connection is used
+    /// for a single request. If the server responds with
+    /// `101 Switching Protocols`, the [upgraded](Upgraded) connection can be
+    /// used for the new protocol. Bytes received after the response head are
+    /// kept in it, so they're read first. Otherwise, the response body isn't
+    /// read and remains in the connection.
+    #[inline]
+    pub async fn upgrade<I, B>(
+        &self,
+        io: I,
+        req: Request<B>,
+    ) -> Result<(Response<()>, Upgraded<I>), Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+        B: Body,
+    {
+        let mut io = Handler::new(io, self.read_strategy);
+        write_request(&mut io, req).await?;
+
+        let res = read_head(
+            &mut io,
+            &self.parser,
+            self.informational.as_ref(),
+            &mut None,
+        )
+        .await?;
+        let (io, read_buf) = io.into_parts();
+        Ok((res, Upgraded::new(io, read_buf)))
+    }
 }
 
 impl Default for Config {
@@ -65,139 +177,562 @@ impl Default for Config {
         Self {
             parser: Parser::new(),
             read_strategy: ReadStrategy::default(),
+            pipeline: 1,
+            informational: None,
+            expect: None,
         }
     }
 }
 
+type Slot = Sender<Result<Response<FetchBody>, Error>>;
+
 struct Connection<'pin, I, B> {
-    recv_req: Receiver<Request<B>>,
-    send_res: Sender<Result<Response<FetchBody>, Error>>,
-    io: Handler<Pin<&'pin mut I>>,
+    recv_req: Receiver<(Request<B>, Slot)>,
+    read: Handler<ReadHalf<Pin<&'pin mut I>>>,
+    write: Handler<WriteHalf<Pin<&'pin mut I>>>,
     parser: Parser,
+    pipeline: usize,
+    informational: Option<Informational>,
+    expect: Option<ExpectContinue>,
+}
+
+/// The written request waiting for its response.
+struct Pending {
+    method: Method,
+    slot: Slot,
+
+    /// Tells the writer whether to send the delayed request body.
+    proceed: Option<Sender<bool>>,
 }
 
-async fn connect<I, B>(mut conn: Connection<'_, I, B>)
+async fn connect<I, B>(conn: Connection<'_, I, B>)
 where
     I: AsyncRead + AsyncWrite,
     B: Body,
 {
-    while let Ok(req) = conn.recv_req.recv().await {
-        let process = async {
-            let (parts, mut body) = req.into_parts();
-            let mut head = Request::from_parts(parts, ());
+    let Connection {
+        recv_req,
+        mut read,
+        mut write,
+        parser,
+        pipeline,
+        informational,
+        expect,
+    } = conn;
 
-            match body.size_hint() {
-                Hint::Empty => conn.io.write_header(&head).await?,
-                Hint::Full { .. } => {
-                    let full = body.take_full().await?;
+    // a permit is taken for each request in flight
+    // and returned when its response is completely read
+    let (take_permit, return_permit) = async_channel::bounded(pipeline);
+    let (send_slot, recv_slot) = async_channel::unbounded();
 
-                    let chunk = full.as_ref().map(Buf::chunk).unwrap_or_default();
-                    let chunk_len = HeaderValue::from(chunk.len());
+    let writer = async {
+        let send_slot = send_slot;
+        while let Ok((mut req, slot)) = recv_req.recv().await {
+            // the request closes the connection,
+            // so no more requests are written after it
+            let close = headers::has_connection_close(req.headers());
+            if close {
+                recv_req.close();
+            }
 
-                    head.headers_mut().insert(header::CONTENT_LENGTH, chunk_len);
-                    headers::remove_chunked_encoding(head.headers_mut());
+            // the method is needed to determine the response framing
+            let method = req.method().clone();
+            if take_permit.send(()).await.is_err() {
+                _ = slot.try_send(Err(Error::Closed));
+                break;
+            }
 
-                    conn.io.write_header(&head).await?;
-                    conn.io.write_body(chunk).await?;
-                }
-                Hint::Chunked { .. } => {
-                    head.headers_mut().remove(header::CONTENT_LENGTH);
-                    headers::insert_chunked_encoding(head.headers_mut());
-
-                    conn.io.write_header(&head).await?;
-                    while let Some(chunk) = body.chunk().await {
-                        conn.io.write_chunk(chunk?.chunk()).await?;
-                        conn.io.flush().await?;
+            let has_body = !matches!(req.body().size_hint(), Hint::Empty);
+            if expect.is_some() && has_body {
+                req.headers_mut().insert(
+                    header::EXPECT,
+                    const { HeaderValue::from_static("100-continue") },
+                );
+            }
+
+            // without the timeout the body is sent right away,
+            // since a server may ignore the expectation
+            let wait = expect
+                .as_ref()
+                .filter(|_| has_body && headers::has_expect_continue(req.headers()));
+
+            if let Some(expect) = wait {
+                let head = async {
+                    let out = write_head(&mut write, req).await?;
+                    write.flush().await?;
+                    Ok::<_, Error>(out)
+                };
+
+                let out = match head.await {
+                    Ok(out) => out,
+                    Err(e) => {
+                        _ = slot.try_send(Err(e));
+                        break;
                     }
+                };
+
+                // the response is read while the body is delayed
+                let (proceed, wait) = async_channel::bounded(1);
+                let pending = Pending {
+                    method,
+                    slot,
+                    proceed: Some(proceed),
+                };
+
+                if let Err(SendError(pending)) = send_slot.send(pending).await {
+                    _ = pending.slot.try_send(Err(Error::Closed));
+                    break;
+                }
+
+                let timeout = async {
+                    expect.sleep().await;
+                    true
+                };
+
+                let send_body = future::or(async { wait.recv().await.unwrap_or(false) }, timeout);
+                let send_body = send_body.await;
+                drop(wait);
+
+                // the final response is received before the body is sent,
+                // so the connection can't be used anymore
+                if !send_body {
+                    break;
+                }
 
-                    conn.io.write_chunk(&[]).await?;
+                if write_body(&mut write, out).await.is_err() {
+                    // the response slot is already passed to the reader,
+                    // so shut down the connection to make the reading fail
+                    _ = write.close().await;
+                    break;
+                }
+            } else {
+                if let Err(e) = write_request(&mut write, req).await {
+                    // the request is partially written, so the connection can't be used anymore
+                    _ = slot.try_send(Err(e));
+                    break;
+                }
+
+                let pending = Pending {
+                    method,
+                    slot,
+                    proceed: None,
+                };
+
+                if let Err(SendError(pending)) = send_slot.send(pending).await {
+                    _ = pending.slot.try_send(Err(Error::Closed));
+                    break;
                 }
             }
 
-            conn.io.flush().await?;
+            if close {
+                break;
+            }
+        }
+
+        // fail requests which are not written
+        recv_req.close();
+        while let Ok((_, slot)) = recv_req.try_recv() {
+            _ = slot.try_send(Err(Error::Closed));
+        }
+    };
+
+    let reader = async {
+        while let Ok(pending) = recv_slot.recv().await {
+            let informational = informational.as_ref();
+            if !read_response(&mut read, &parser, informational, &recv_req, pending).await {
+                break;
+            }
+
+            _ = return_permit.recv().await;
+        }
+
+        // the connection is closed, so fail requests
+        // which are written but not responded
+        recv_req.close();
+        recv_slot.close();
+        return_permit.close();
+        while let Ok(pending) = recv_slot.try_recv() {
+            _ = pending.slot.try_send(Err(Error::Closed));
+        }
+    };
+
+    future::zip(writer, reader).await;
+
+    // all responses are read, so gracefully shut down the connection
+    _ = write.close().await;
+}
+
+/// Reads the response and sends it to the slot.
+///
+/// Returns `false` if the connection can't be used anymore.
+async fn read_response<I, B>(
+    io: &mut Handler<I>,
+    parser: &Parser,
+    informational: Option<&Informational>,
+    recv_req: &Receiver<(Request<B>, Slot)>,
+    pending: Pending,
+) -> bool
+where
+    I: AsyncRead + Unpin,
+{
+    let Pending {
+        method,
+        slot,
+        mut proceed,
+    } = pending;
+
+    let head = async {
+        let res = read_head(io, parser, informational, &mut proceed).await?;
+        let (state, mut reusable) = read_body_state(&method, &res)?;
+
+        // the final response is received while the request body is
+        // delayed, so it's skipped and the connection can't be reused
+        if proceed
+            .take()
+            .is_some_and(|proceed| proceed.try_send(false).is_ok())
+        {
+            reusable = false;
+        }
+
+        Ok((res, state, reusable))
+    };
 
-            let head = conn.io.read_header().await?;
-            let res = conn.parser.parse_header(head)?;
+    let (give, fetch) = async_channel::bounded(16);
+    let (mut state, keep_alive) = match head.await {
+        Ok((res, state, reusable)) => {
+            let keep_alive = reusable && !headers::has_connection_close(res.headers());
 
-            let headers = res.headers();
-            let state = match headers::parse_content_len(headers) {
-                ContentLen::Num(n) => ReadBodyState::Remaining(n),
-                ContentLen::None if headers::has_chunked_encoding(headers) => {
-                    ReadBodyState::Chunked
+            // stop accepting requests before the response is delivered,
+            // so the connection isn't considered reusable after that
+            if !keep_alive {
+                recv_req.close();
+            }
+
+            let res = res.map(|_| FetchBody {
+                fetch,
+                end: false,
+                trailers: None,
+            });
+            _ = slot.try_send(Ok(res));
+            (state, keep_alive)
+        }
+        Err(e) => {
+            _ = slot.try_send(Err(e));
+            return false;
+        }
+    };
+
+    loop {
+        let mut trailers = None;
+        let (frame, end) = match &mut state {
+            ReadBodyState::Remaining(0) => (Ok(Bytes::new()), true),
+            ReadBodyState::Remaining(n) => (io.read_body(n).await, false),
+            ReadBodyState::Chunked => match io.read_chunk().await {
+                // the last chunk is followed by trailers
+                Ok(chunk) if chunk.is_empty() => {
+                    let read = async { parser.parse_trailers(io.read_trailers().await?) };
+                    match read.await {
+                        Ok(map) => {
+                            trailers = Some(map).filter(|map| !map.is_empty());
+                            (Ok(chunk), true)
+                        }
+                        Err(e) => (Err(e), false),
+                    }
                 }
-                _ => return Err(Error::invalid_input()),
-            };
+                chunk => (chunk, false),
+            },
+            ReadBodyState::Eof => {
+                let chunk = io.read_to_eof().await;
+                let end = chunk.as_ref().is_ok_and(Bytes::is_empty);
+                (chunk, end)
+            }
+        };
+
+        let error = frame.is_err();
+
+        // if the body is dropped, the rest of it is still read
+        // to get the connection ready for the next response
+        _ = give
+            .send(Next {
+                frame,
+                end,
+                trailers,
+            })
+            .await;
+        if error {
+            return false;
+        }
+
+        if end {
+            return keep_alive;
+        }
+    }
+}
+
+/// Reads the final response head.
+///
+/// Informational responses are passed to the callback and skipped,
+/// except for `101 Switching Protocols` which is the final one.
+/// On `100 Continue`, the writer is told to send the delayed body.
+async fn read_head<I>(
+    io: &mut Handler<I>,
+    parser: &Parser,
+    informational: Option<&Informational>,
+    proceed: &mut Option<Sender<bool>>,
+) -> Result<Response<()>, Error>
+where
+    I: AsyncRead + Unpin,
+{
+    loop {
+        let head = io.read_header().await?;
+        let res = parser.parse_header(head)?;
+        let status = res.status();
+        if !status.is_informational() || status == StatusCode::SWITCHING_PROTOCOLS {
+            return Ok(res);
+        }
+
+        if status == StatusCode::CONTINUE {
+            if let Some(proceed) = proceed.take() {
+                _ = proceed.try_send(true);
+            }
+        }
+
+        if let Some(f) = informational {
+            f(&res);
+        }
+    }
+}
+
+/// Determines the response body framing as described in RFC 9112 section 6.3.
+///
+/// Also returns whether the connection can be reused after the body.
+fn read_body_state(method: &Method, res: &Response<()>) -> Result<(ReadBodyState, bool), Error> {
+    let status = res.status();
+
+    // the connection is switched to another protocol or becomes a tunnel
+    if status == StatusCode::SWITCHING_PROTOCOLS || method == Method::CONNECT && status.is_success()
+    {
+        return Ok((ReadBodyState::Remaining(0), false));
+    }
+
+    // these responses never have a body regardless of their headers
+    if method == Method::HEAD
+        || status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+    {
+        return Ok((ReadBodyState::Remaining(0), true));
+    }
 
-            Ok((res, state))
+    let headers = res.headers();
+    let content_len = headers::parse_content_len(headers);
+    if headers.contains_key(header::TRANSFER_ENCODING) {
+        // the transfer encoding overrides the content length, but such a message
+        // may be an attempt of request smuggling, so the connection isn't reused
+        let reusable = content_len == ContentLen::None;
+        return if headers::has_chunked_encoding(headers) {
+            Ok((ReadBodyState::Chunked, reusable))
+        } else {
+            Ok((ReadBodyState::Eof, false))
         };
+    }
+
+    match content_len {
+        ContentLen::Num(n) => Ok((ReadBodyState::Remaining(n), true)),
+        ContentLen::None => Ok((ReadBodyState::Eof, false)),
+        ContentLen::Invalid => Err(Error::invalid_input()),
+    }
+}
+
+async fn write_request<I, B>(io: &mut Handler<I>, req: Request<B>) -> Result<(), Error>
+where
+    I: AsyncWrite + Unpin,
+    B: Body,
+{
+    let out = write_head(io, req).await?;
+    write_body(io, out).await
+}
+
+/// The request body to write after the head.
+enum Outgoing<B>
+where
+    B: Body,
+{
+    Empty,
+    Full(Option<B::Chunk>),
+    Sized { body: B, len: usize },
+    Chunked(B),
+}
 
-        let (give, fetch) = async_channel::bounded(16);
-        let mut state = match process.await {
-            Ok((res, state)) => {
-                let res = res.map(|_| FetchBody { fetch, end: false });
-                _ = conn.send_res.send(Ok(res)).await;
-                state
+/// Writes the request head with headers describing the body framing.
+async fn write_head<I, B>(io: &mut Handler<I>, req: Request<B>) -> Result<Outgoing<B>, Error>
+where
+    I: AsyncWrite + Unpin,
+    B: Body,
+{
+    let (parts, body) = req.into_parts();
+    let mut head = Request::from_parts(parts, ());
+
+    let out = match body.size_hint() {
+        Hint::Empty => Outgoing::Empty,
+        Hint::Full { .. } => {
+            let full = body.take_full().await?;
+
+            let chunk = full.as_ref().map(Buf::chunk).unwrap_or_default();
+            let chunk_len = HeaderValue::from(chunk.len());
+
+            head.headers_mut().insert(header::CONTENT_LENGTH, chunk_len);
+            headers::remove_chunked_encoding(head.headers_mut());
+            Outgoing::Full(full)
+        }
+        Hint::Chunked { .. } => match headers::parse_content_len(head.headers()) {
+            // the length of the streamed body is known in advance,
+            // so it's written as is without the chunked encoding
+            // unless the request declares trailers to follow it
+            ContentLen::Num(len) if !head.headers().contains_key(header::TRAILER) => {
+                headers::remove_chunked_encoding(head.headers_mut());
+                Outgoing::Sized { body, len }
             }
-            Err(e) => {
-                _ = conn.send_res.send(Err(e)).await;
-                continue;
+            _ => {
+                head.headers_mut().remove(header::CONTENT_LENGTH);
+                headers::insert_chunked_encoding(head.headers_mut());
+                Outgoing::Chunked(body)
             }
-        };
+        },
+    };
 
-        loop {
-            let (frame, end) = match &mut state {
-                ReadBodyState::Remaining(0) => (Ok(Bytes::new()), true),
-                ReadBodyState::Remaining(n) => (conn.io.read_body(n).await, false),
-                ReadBodyState::Chunked => {
-                    let chunk = conn.io.read_chunk().await;
-                    let end = chunk.as_ref().is_ok_and(Bytes::is_empty);
-                    (chunk, end)
-                }
-            };
+    io.write_header(&head).await?;
+    Ok(out)
+}
 
-            let error = frame.is_err();
-            let next = Next { frame, end };
-            if give.send(next).await.is_err() || error || end {
-                break;
+async fn write_body<I, B>(io: &mut Handler<I>, out: Outgoing<B>) -> Result<(), Error>
+where
+    I: AsyncWrite + Unpin,
+    B: Body,
+{
+    match out {
+        Outgoing::Empty => {}
+        Outgoing::Full(full) => {
+            let chunk = full.as_ref().map(Buf::chunk).unwrap_or_default();
+            io.write_body(chunk).await?;
+        }
+        Outgoing::Sized { mut body, len } => {
+            let mut rest = len;
+            while let Some(chunk) = body.chunk().await {
+                let chunk = chunk?;
+                let chunk = chunk.chunk();
+                rest = rest
+                    .checked_sub(chunk.len())
+                    .ok_or_else(Error::invalid_input)?;
+
+                io.write_body(chunk).await?;
+                io.flush().await?;
+            }
+
+            if rest != 0 {
+                return Err(Error::invalid_input());
+            }
+
+            // trailers can't follow the body without the chunked encoding
+            if body.trailers().await.transpose()?.is_some() {
+                return Err(Error::invalid_input());
+            }
+        }
+        Outgoing::Chunked(mut body) => {
+            while let Some(chunk) = body.chunk().await {
+                io.write_chunk(chunk?.chunk()).await?;
+                io.flush().await?;
+            }
+
+            match body.trailers().await {
+                Some(trailers) => io.write_trailers(&trailers?).await?,
+                None => io.write_chunk(&[]).await?,
             }
         }
     }
+
+    io.flush().await
 }
 
 #[derive(Clone, Copy)]
 enum ReadBodyState {
     Remaining(usize),
     Chunked,
+    Eof,
 }
 
 pub struct Requester<B> {
-    send_req: Sender<Request<B>>,
-    recv_res: Receiver<Result<Response<FetchBody>, Error>>,
+    send_req: Sender<(Request<B>, Slot)>,
+    closed: Receiver<()>,
 }
 
 impl<B> Requester<B> {
+    /// Sends the request and waits for its response.
+    ///
+    /// The requester can be shared or cloned to send requests
+    /// concurrently, each of them receives its own response.
     #[inline]
     pub async fn send(&self, req: Request<B>) -> Result<Response<FetchBody>, Error> {
-        self.send_req.send(req).await.map_err(|_| Error::Closed)?;
-        self.recv_res.recv().await.map_err(|_| Error::Closed)?
+        let (slot, recv_res) = async_channel::bounded(1);
+        self.send_req
+            .send((req, slot))
+            .await
+            .map_err(|_| Error::Closed)?;
+
+        recv_res.recv().await.map_err(|_| Error::Closed)?
+    }
+
+    /// Checks if the connection accepts new requests.
+    ///
+    /// Returns `false` once the connection fails or any side asks to close
+    /// it with the `Connection: close` header, or the response body is
+    /// delimited by the end of the connection. Responses to requests which
+    /// are already sent are still read.
+    #[inline]
+    pub fn is_reusable(&self) -> bool {
+        !self.send_req.is_closed()
+    }
+
+    /// Checks if the connection is completely shut down.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed.is_closed()
+    }
+}
+
+impl<B> Clone for Requester<B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            send_req: self.send_req.clone(),
+            closed: self.closed.clone(),
+        }
     }
 }
 
 struct Next {
     frame: Result<Bytes, Error>,
     end: bool,
+    trailers: Option<HeaderMap>,
 }
 
 pub struct FetchBody {
     fetch: Receiver<Next>,
     end: bool,
+    trailers: Option<HeaderMap>,
 }
 
 impl FetchBody {
     #[inline]
     pub async fn frame(&mut self) -> Result<Bytes, Error> {
-        let Next { frame, end } = self.fetch.recv().await.map_err(|_| Error::Closed)?;
+        let Next {
+            frame,
+            end,
+            trailers,
+        } = self.fetch.recv().await.map_err(|_| Error::Closed)?;
+
         self.end = end;
+        self.trailers = trailers;
         frame
     }
 }
@@ -229,6 +764,13 @@ impl Body for FetchBody {
     fn size_hint(&self) -> Hint {
         Hint::Chunked { end: self.end }
     }
+
+    /// Returns trailers of the chunked response
+    /// once the body is read to the end.
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        self.trailers.take().map(Ok)
+    }
 }
 
 #[cfg(test)]
@@ -377,6 +919,649 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn streamed_with_length() -> Result<(), Error> {
+        use {
+            crate::body::Chunked,
+            futures_lite::{StreamExt, stream},
+        };
+
+        const CHUNKS: [&str; 2] = ["hello, ", "stream"];
+        const REQUEST: [&str; 5] = [
+            "GET / HTTP/1.1\r\n",
+            "content-length: 13\r\n",
+            "\r\n",
+            CHUNKS[0],
+            CHUNKS[1],
+        ];
+
+        const RESPONSE: [&str; 3] = ["HTTP/1.1 200 OK\r\n", "content-length: 0\r\n", "\r\n"];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let body = stream::iter(CHUNKS).map(str::as_bytes).map(Ok);
+            let mut req = Request::new(Chunked(body));
+            req.headers_mut()
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(13));
+
+            reqs.send(req).await?;
+            Ok(())
+        })?;
+
+        assert_eq!(String::from_utf8(write), Ok(REQUEST.concat()));
+        Ok(())
+    }
+
+    fn get(path: &'static str) -> Request<()> {
+        let mut req = Request::new(());
+        *req.uri_mut() = http::Uri::from_static(path);
+        req
+    }
+
+    async fn text(res: Result<Response<FetchBody>, Error>) -> Result<String, Error> {
+        let body = res?.into_body().text().await?;
+        Ok(body)
+    }
+
+    #[test]
+    fn concurrent_requests() -> Result<(), Error> {
+        const RESPONSE: [&str; 2] = [
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\nb",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let a = async { text(reqs.send(get("/a")).await).await };
+            let b = async { text(reqs.clone().send(get("/b")).await).await };
+            let (a, b) = future::zip(a, b).await;
+            assert_eq!(a?, "a");
+            assert_eq!(b?, "b");
+            assert!(reqs.is_reusable(), "the connection is kept alive");
+            Ok(())
+        })?;
+
+        let write = String::from_utf8(write).expect("utf-8");
+        assert_eq!(write, "GET /a HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\n\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn close_delimited() -> Result<(), Error> {
+        const RESPONSE: [&str; 3] = ["HTTP/1.1 200 OK\r\n\r\n", "hello, ", "world"];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        let (_, res) = future::block_on(future::zip(conn, async {
+            let res = reqs.send(get("/")).await;
+            assert!(!reqs.is_reusable(), "the body is delimited by eof");
+            text(res).await
+        }));
+
+        assert_eq!(res?, "hello, world");
+        assert!(reqs.is_closed(), "the connection is shut down");
+        assert!(matches!(
+            future::block_on(reqs.send(get("/"))),
+            Err(Error::Closed),
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn connection_close_response() -> Result<(), Error> {
+        const RESPONSE: [&str; 2] = [
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 1\r\n\r\na",
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\nb",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        future::block_on(future::zip(conn, async {
+            let res = reqs.send(get("/a")).await;
+            assert!(!reqs.is_reusable(), "the server closes the connection");
+            assert_eq!(text(res).await?, "a");
+            assert!(matches!(reqs.send(get("/b")).await, Err(Error::Closed)));
+            Ok::<_, Error>(())
+        }))
+        .1?;
+
+        assert!(reqs.is_closed(), "the connection is shut down");
+        assert_eq!(
+            String::from_utf8(write),
+            Ok("GET /a HTTP/1.1\r\n\r\n".to_owned())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn connection_close_request() -> Result<(), Error> {
+        const RESPONSE: [&str; 2] = [
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\nb",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        future::block_on(future::zip(conn, async {
+            let mut req = get("/a");
+            req.headers_mut()
+                .insert(header::CONNECTION, HeaderValue::from_static("close"));
+
+            let res = reqs.send(req).await;
+            assert!(!reqs.is_reusable(), "the client closes the connection");
+            assert_eq!(text(res).await?, "a");
+            assert!(matches!(reqs.send(get("/b")).await, Err(Error::Closed)));
+            Ok::<_, Error>(())
+        }))
+        .1?;
+
+        assert!(reqs.is_closed(), "the connection is shut down");
+        assert_eq!(
+            String::from_utf8(write),
+            Ok("GET /a HTTP/1.1\r\nconnection: close\r\n\r\n".to_owned()),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn head_response() -> Result<(), Error> {
+        const RESPONSE: [&str; 2] = [
+            "HTTP/1.1 200 OK\r\ncontent-length: 1234\r\n\r\n",
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let mut req = get("/");
+            *req.method_mut() = Method::HEAD;
+
+            // the content length describes the body which isn't sent
+            let res = reqs.send(req).await?;
+            assert_eq!(
+                res.headers().get(header::CONTENT_LENGTH),
+                Some(&HeaderValue::from(1234)),
+            );
+
+            assert_eq!(text(Ok(res)).await?, "");
+            assert_eq!(text(reqs.send(get("/")).await).await?, "a");
+            assert!(reqs.is_reusable(), "the connection is kept alive");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn bodiless_statuses() -> Result<(), Error> {
+        const RESPONSE: [&str; 3] = [
+            "HTTP/1.1 204 No Content\r\n\r\n",
+            "HTTP/1.1 304 Not Modified\r\ncontent-length: 10\r\n\r\n",
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            for status in [StatusCode::NO_CONTENT, StatusCode::NOT_MODIFIED] {
+                let res = reqs.send(get("/")).await?;
+                assert_eq!(res.status(), status);
+                assert_eq!(text(Ok(res)).await?, "", "{status} has no body");
+            }
+
+            assert_eq!(text(reqs.send(get("/")).await).await?, "a");
+            assert!(reqs.is_reusable(), "the connection is kept alive");
+            Ok(())
+        })
+    }
+
+    const INFORMATIONAL: [&str; 3] = [
+        "HTTP/1.1 100 Continue\r\n\r\n",
+        "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\n",
+        "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+    ];
+
+    #[test]
+    fn informational_skipped() -> Result<(), Error> {
+        let read = test::parts(INFORMATIONAL.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let res = reqs.send(get("/")).await?;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(text(Ok(res)).await?, "a");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn informational_callback() -> Result<(), Error> {
+        use std::sync::Mutex;
+
+        let read = test::parts(INFORMATIONAL.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let conf = Config::default().on_informational({
+            let received = received.clone();
+            move |res| {
+                let link = res.headers().get(header::LINK).cloned();
+                received.lock().expect("lock").push((res.status(), link));
+            }
+        });
+
+        let (reqs, conn) = conf.handshake(io);
+        run(conn, async {
+            let res = reqs.send(get("/")).await?;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(text(Ok(res)).await?, "a");
+            Ok(())
+        })?;
+
+        let link = HeaderValue::from_static("</style.css>; rel=preload");
+        assert_eq!(
+            *received.lock().expect("lock"),
+            [
+                (StatusCode::CONTINUE, None),
+                (StatusCode::EARLY_HINTS, Some(link)),
+            ],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailers() -> Result<(), Error> {
+        use crate::body::BodyExt;
+
+        const REQUEST: &str = "\
+            GET / HTTP/1.1\r\n\
+            transfer-encoding: chunked\r\n\
+            \r\n\
+            2\r\nhi\r\n\
+            0\r\n\
+            digest: sha-256=abc\r\n\
+            \r\n";
+
+        const RESPONSE: [&str; 2] = [
+            "HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n",
+            "2\r\nhi\r\n0\r\ndigest: sha-256=abc\r\n\r\n",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let req = Request::new("hi".with_trailers(trailers.clone()));
+            let mut body = reqs.send(req).await?.into_body();
+            assert_eq!(body.frame().await?, "hi");
+            assert!(body.frame().await?.is_empty(), "the data ends");
+            assert!(body.size_hint().end(), "the data ends");
+
+            let actual = body.trailers().await.transpose()?;
+            assert_eq!(actual.as_ref(), Some(&trailers));
+            Ok(())
+        })?;
+
+        let write = String::from_utf8(write).expect("utf-8");
+        assert_eq!(write, REQUEST);
+        Ok(())
+    }
+
+    #[test]
+    fn sized_trailers() -> Result<(), Error> {
+        use crate::body::BodyExt;
+
+        const REQUEST: &str = "\
+            GET / HTTP/1.1\r\n\
+            trailer: digest\r\n\
+            transfer-encoding: chunked\r\n\
+            \r\n\
+            2\r\nhi\r\n\
+            0\r\n\
+            digest: sha-256=abc\r\n\
+            \r\n";
+
+        const RESPONSE: &[&str] = &["HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na"];
+
+        let read = test::parts(RESPONSE.iter().copied().map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        // the declared trailers turn the sized body into the chunked one
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let mut req = Request::new("hi".with_trailers(trailers));
+            let headers = req.headers_mut();
+            headers.insert(header::CONTENT_LENGTH, HeaderValue::from(2));
+            headers.insert(header::TRAILER, HeaderValue::from_static("digest"));
+
+            assert_eq!(text(reqs.send(req).await).await?, "a");
+            Ok(())
+        })?;
+
+        let write = String::from_utf8(write).expect("utf-8");
+        assert_eq!(write, REQUEST);
+        Ok(())
+    }
+
+    #[test]
+    fn sized_undeclared_trailers() {
+        use crate::body::BodyExt;
+
+        let read = test::parts([b"" as &[u8]]);
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        // the trailers can't be sent after the sized body
+        let (reqs, conn) = Config::default().handshake(io);
+        let mut req = Request::new("hi".with_trailers(trailers));
+        req.headers_mut()
+            .insert(header::CONTENT_LENGTH, HeaderValue::from(2));
+
+        let ((), res) = future::block_on(future::zip(conn, reqs.send(req)));
+        assert!(
+            matches!(res, Err(Error::Io(e)) if e.kind() == io::ErrorKind::InvalidInput),
+            "the request is rejected",
+        );
+    }
+
+    const EXPECT_REQUEST: &str = "\
+        GET / HTTP/1.1\r\n\
+        expect: 100-continue\r\n\
+        content-length: 5\r\n\
+        \r\n";
+
+    fn expect<F>(response: &'static [&'static str], timer: F) -> Result<(String, bool), Error>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let read = test::parts(response.iter().copied().map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let timer = std::sync::Mutex::new(Some(timer));
+        let conf = Config::default().expect_continue(Duration::from_secs(1), move |_| {
+            timer
+                .lock()
+                .expect("lock")
+                .take()
+                .expect("the timer is used once")
+        });
+
+        // the requester is dropped after the response,
+        // so the connection completes when the body is written
+        let (reqs, conn) = conf.handshake(io);
+        let (_, res) = future::block_on(future::zip(conn, async move {
+            let res = reqs.send(Request::new("hello")).await;
+            let reusable = reqs.is_reusable();
+            Ok::<_, Error>((text(res).await?, reusable))
+        }));
+
+        let (body, reusable) = res?;
+        assert_eq!(body, "a");
+
+        let write = String::from_utf8(write).expect("utf-8");
+        Ok((write, reusable))
+    }
+
+    #[test]
+    fn expect_continue() -> Result<(), Error> {
+        const RESPONSE: &[&str] = &[
+            "HTTP/1.1 100 Continue\r\n\r\n",
+            "HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+        ];
+
+        let (write, reusable) = expect(RESPONSE, future::pending())?;
+        assert_eq!(write, format!("{EXPECT_REQUEST}hello"));
+        assert!(reusable, "the connection is kept alive");
+        Ok(())
+    }
+
+    #[test]
+    fn expect_rejected() -> Result<(), Error> {
+        const RESPONSE: &[&str] = &["HTTP/1.1 413 Content Too Large\r\ncontent-length: 1\r\n\r\na"];
+
+        // the body is skipped, so the connection can't be reused
+        let (write, reusable) = expect(RESPONSE, future::pending())?;
+        assert_eq!(write, EXPECT_REQUEST);
+        assert!(!reusable, "the connection is closed");
+        Ok(())
+    }
+
+    #[test]
+    fn expect_timeout() -> Result<(), Error> {
+        const RESPONSE: &[&str] = &["HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na"];
+
+        // the server doesn't respond with 100 continue,
+        // so the body is sent after the timeout
+        let (write, reusable) = expect(RESPONSE, future::ready(()))?;
+        assert_eq!(write, format!("{EXPECT_REQUEST}hello"));
+        assert!(reusable, "the connection is kept alive");
+        Ok(())
+    }
+
+    #[test]
+    fn expect_disabled() -> Result<(), Error> {
+        const RESPONSE: &[&str] = &["HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na"];
+
+        let read = test::parts(RESPONSE.iter().copied().map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        // the header is set explicitly, but without the timeout
+        // the body is sent without waiting for 100 continue
+        let (reqs, conn) = Config::default().handshake(io);
+        run(conn, async {
+            let mut req = Request::new("hello");
+            req.headers_mut().insert(
+                header::EXPECT,
+                const { HeaderValue::from_static("100-continue") },
+            );
+
+            assert_eq!(text(reqs.send(req).await).await?, "a");
+            Ok(())
+        })?;
+
+        let write = String::from_utf8(write).expect("utf-8");
+        assert_eq!(write, format!("{EXPECT_REQUEST}hello"));
+        Ok(())
+    }
+
+    /// The connection which responds only after the
+    /// expected number of requests is written.
+    struct Gate {
+        read: &'static [u8],
+        written: Vec<u8>,
+        expected: usize,
+        waker: Option<std::task::Waker>,
+    }
+
+    impl AsyncRead for Gate {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &mut [u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            let requests = self.written.windows(4).filter(|w| w == b"\r\n\r\n").count();
+            if requests < self.expected {
+                self.waker = Some(cx.waker().clone());
+                return std::task::Poll::Pending;
+            }
+
+            Pin::new(&mut self.read).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Gate {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            self.written.extend_from_slice(buf);
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn pipelining() -> Result<(), Error> {
+        let io = Gate {
+            read: b"\
+                HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na\
+                HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n1\r\nb\r\n0\r\n\r\n\
+                HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\nc",
+            written: vec![],
+            expected: 3,
+            waker: None,
+        };
+
+        let (reqs, conn) = Config::default().pipeline(3).handshake(io);
+        run(conn, async {
+            let (a, (b, c)) = future::zip(
+                async { text(reqs.send(get("/a")).await).await },
+                future::zip(async { text(reqs.send(get("/b")).await).await }, async {
+                    text(reqs.send(get("/c")).await).await
+                }),
+            )
+            .await;
+
+            assert_eq!(a?, "a");
+            assert_eq!(b?, "b");
+            assert_eq!(c?, "c");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn pipelining_failure() -> Result<(), Error> {
+        let io = Gate {
+            read: b"HTTP/1.1 200 OK\r\ncontent-length: 1\r\n\r\na",
+            written: vec![],
+            expected: 3,
+            waker: None,
+        };
+
+        let (reqs, conn) = Config::default().pipeline(3).handshake(io);
+        run(conn, async {
+            let (a, (b, c)) = future::zip(
+                async { text(reqs.send(get("/a")).await).await },
+                future::zip(reqs.send(get("/b")), reqs.send(get("/c"))),
+            )
+            .await;
+
+            assert_eq!(a?, "a");
+
+            // the connection is closed before the second response
+            let b = b.expect_err("unexpected eof");
+            assert!(matches!(b, Error::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+            assert!(matches!(c, Err(Error::Closed)));
+
+            // the connection can't be used anymore
+            assert!(matches!(reqs.send(get("/d")).await, Err(Error::Closed)));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn upgrade() -> Result<(), Error> {
+        const REQUEST: [&str; 4] = [
+            "GET / HTTP/1.1\r\n",
+            "connection: upgrade\r\n",
+            "upgrade: echo\r\n",
+            "\r\n",
+        ];
+
+        const RESPONSE: [&str; 5] = [
+            "HTTP/1.1 101 Switching Protocols\r\n",
+            "connection: upgrade\r\n",
+            "upgrade: echo\r\n",
+            "\r\nhello, ",
+            "upgraded",
+        ];
+
+        let read = test::parts(RESPONSE.map(str::as_bytes));
+        let mut write = vec![];
+        let io = test::io(read, &mut write);
+
+        let mut req = Request::new(());
+        req.headers_mut()
+            .insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        req.headers_mut()
+            .insert(header::UPGRADE, HeaderValue::from_static("echo"));
+
+        let (res, mut upgraded) = future::block_on(Config::default().upgrade(io, req))?;
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(upgraded.read_buf(), b"hello, ");
+
+        let mut rest = String::new();
+        future::block_on(upgraded.read_to_string(&mut rest))?;
+        assert_eq!(rest, "hello, upgraded");
+
+        future::block_on(upgraded.write_all(b"echo"))?;
+        drop(upgraded);
+
+        let mut expected = REQUEST.concat();
+        expected.push_str("echo");
+        assert_eq!(String::from_utf8(write), Ok(expected));
+        Ok(())
+    }
+
     #[test]
     fn handshake_is_send() {
         fn assert_send<S>(_: S)
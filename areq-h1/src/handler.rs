@@ -2,7 +2,7 @@ use {
     crate::{bytes::InitBytesMut, error::Error},
     bytes::Bytes,
     futures_lite::prelude::*,
-    http::{Request, Response, Uri, Version},
+    http::{HeaderMap, HeaderName, HeaderValue, Request, Response, Uri, Version},
     httparse::{Header, ParserConfig},
     std::{io::Write, str},
 };
@@ -28,7 +28,26 @@ impl<I> Handler<I> {
         }
     }
 
+    /// Returns the inner io and bytes read but not yet consumed.
+    pub fn into_parts(mut self) -> (I, Bytes) {
+        let len = self.read_buf.len();
+        let read_buf = self.read_buf.split_to(len).freeze();
+        (self.io, read_buf)
+    }
+
     async fn read_to_buf(&mut self) -> Result<(), Error>
+    where
+        I: AsyncRead + Unpin,
+    {
+        if self.read_more().await? == 0 {
+            Err(Error::unexpected_eof())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads more bytes to the buffer and returns their number.
+    async fn read_more(&mut self) -> Result<usize, Error>
     where
         I: AsyncRead + Unpin,
     {
@@ -45,12 +64,7 @@ impl<I> Handler<I> {
         let n = self.io.read(buf).await?;
         self.read_buf.advance(n);
         self.read_strategy.record(n);
-
-        if n == 0 {
-            Err(Error::unexpected_eof())
-        } else {
-            Ok(())
-        }
+        Ok(n)
     }
 
     async fn read_until(&mut self, sep: &[u8]) -> Result<Bytes, Error>
@@ -100,6 +114,21 @@ impl<I> Handler<I> {
         Ok(chunk)
     }
 
+    /// Reads the body delimited by the end of the connection.
+    ///
+    /// Returns an empty chunk when the connection is closed.
+    pub async fn read_to_eof(&mut self) -> Result<Bytes, Error>
+    where
+        I: AsyncRead + Unpin,
+    {
+        if self.read_buf.is_empty() {
+            self.read_more().await?;
+        }
+
+        let len = self.read_buf.len();
+        Ok(self.read_buf.split_to(len).freeze())
+    }
+
     pub async fn read_chunk(&mut self) -> Result<Bytes, Error>
     where
         I: AsyncRead + Unpin,
@@ -113,10 +142,15 @@ impl<I> Handler<I> {
                 .expect("bytes read include suffix");
 
             let len_str = str::from_utf8(len_bytes).map_err(|_| Error::invalid_input())?;
-            let len = usize::from_str_radix(len_str, 16).map_err(|_| Error::invalid_input())?;
-            len + SEP.len()
+            usize::from_str_radix(len_str, 16).map_err(|_| Error::invalid_input())?
         };
 
+        // the last chunk is followed by the trailer section
+        if len == 0 {
+            return Ok(Bytes::new());
+        }
+
+        let len = len + SEP.len();
         while self.read_buf.len() < len {
             self.read_to_buf().await?;
         }
@@ -130,6 +164,24 @@ impl<I> Handler<I> {
         }
     }
 
+    /// Reads the trailer section after the last chunk.
+    pub async fn read_trailers(&mut self) -> Result<Bytes, Error>
+    where
+        I: AsyncRead + Unpin,
+    {
+        const SEP: &[u8; 2] = b"\r\n";
+
+        while self.read_buf.len() < SEP.len() {
+            self.read_to_buf().await?;
+        }
+
+        if self.read_buf.as_mut().starts_with(SEP) {
+            return Ok(self.read_buf.split_to(SEP.len()).freeze());
+        }
+
+        self.read_until(b"\r\n\r\n").await
+    }
+
     pub async fn write_header(&mut self, req: &Request<()>) -> Result<(), Error>
     where
         I: AsyncWrite + Unpin,
@@ -195,6 +247,24 @@ impl<I> Handler<I> {
         Ok(())
     }
 
+    /// Writes the last chunk followed by the trailer section.
+    pub async fn write_trailers(&mut self, trailers: &HeaderMap) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        self.write_buf.clear();
+        self.write_buf.extend_from_slice(b"0\r\n");
+        for (name, value) in trailers {
+            _ = write!(&mut self.write_buf, "{name}: ");
+            self.write_buf.extend_from_slice(value.as_bytes());
+            self.write_buf.extend_from_slice(b"\r\n");
+        }
+
+        self.write_buf.extend_from_slice(b"\r\n");
+        self.io.write(&self.write_buf).await?;
+        Ok(())
+    }
+
     pub async fn flush(&mut self) -> Result<(), Error>
     where
         I: AsyncWrite + Unpin,
@@ -202,6 +272,14 @@ impl<I> Handler<I> {
         self.io.flush().await?;
         Ok(())
     }
+
+    pub async fn close(&mut self) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        self.io.close().await?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -278,11 +356,7 @@ impl Parser {
     }
 
     pub fn parse_header(&self, buf: Bytes) -> Result<Response<()>, Error> {
-        use {
-            http::{HeaderName, HeaderValue, StatusCode},
-            httparse::Status,
-            std::mem::MaybeUninit,
-        };
+        use {http::StatusCode, httparse::Status, std::mem::MaybeUninit};
 
         let mut out = httparse::Response::new(&mut []);
         let uninit_headers = if self.max_headers <= Self::HEADERS_STACK_BUFFER_LEN {
@@ -310,23 +384,33 @@ impl Parser {
         *res.status_mut() =
             StatusCode::from_u16(out.code.unwrap_or_default()).expect("valid status code");
 
-        *res.headers_mut() = {
-            let entry = |header: Header<'_>| {
-                let name =
-                    HeaderName::from_bytes(header.name.as_bytes()).expect("valid header name");
-                let value = HeaderValue::from_maybe_shared(buf.slice_ref(header.value))
-                    .expect("valid header value");
+        *res.headers_mut() = header_map(&buf, out.headers);
+        Ok(res)
+    }
 
-                (name, value)
-            };
+    pub fn parse_trailers(&self, buf: Bytes) -> Result<HeaderMap, Error> {
+        use httparse::Status;
 
-            out.headers.iter().copied().map(entry).collect()
-        };
-
-        Ok(res)
+        let mut headers = vec![httparse::EMPTY_HEADER; self.max_headers];
+        match httparse::parse_headers(&buf, &mut headers)? {
+            Status::Complete((n, headers)) if n == buf.len() => Ok(header_map(&buf, headers)),
+            _ => Err(Error::invalid_input()),
+        }
     }
 }
 
+fn header_map(buf: &Bytes, headers: &[Header<'_>]) -> HeaderMap {
+    let entry = |header: &Header<'_>| {
+        let name = HeaderName::from_bytes(header.name.as_bytes()).expect("valid header name");
+        let value = HeaderValue::from_maybe_shared(buf.slice_ref(header.value))
+            .expect("valid header value");
+
+        (name, value)
+    };
+
+    headers.iter().map(entry).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, futures_lite::future};
@@ -446,6 +530,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn read_to_eof() -> Result<(), Error> {
+        let mut h = Handler::test(RESPONSE);
+        future::block_on(h.read_header())?;
+
+        let body = future::block_on(h.read_to_eof())?;
+        assert_eq!(body, "body".as_bytes());
+
+        let end = future::block_on(h.read_to_eof())?;
+        assert!(end.is_empty(), "the connection is closed");
+        Ok(())
+    }
+
+    #[test]
+    fn read_trailers() -> Result<(), Error> {
+        let parser = Parser::new();
+        for (input, expected) in [
+            ("0\r\n\r\n", vec![]),
+            ("0\r\na: 1\r\nb: 2\r\n\r\n", vec![("a", "1"), ("b", "2")]),
+        ] {
+            let mut h = Handler::test(input.as_bytes());
+            let end = future::block_on(h.read_chunk())?;
+            assert!(end.is_empty(), "the last chunk");
+
+            let trailers = future::block_on(h.read_trailers())?;
+            let trailers = parser.parse_trailers(trailers)?;
+            let actual: Vec<_> = trailers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.to_str().expect("ascii")))
+                .collect();
+
+            assert_eq!(actual, expected);
+            assert!(h.read_buf.is_empty(), "the chunked body is read");
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn read_partial() -> Result<(), Error> {
         use crate::test;
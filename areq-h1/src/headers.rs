@@ -40,6 +40,23 @@ pub(crate) fn has_chunked_encoding(headers: &HeaderMap) -> bool {
     }
 }
 
+/// Checks if the connection should be closed after the message.
+///
+/// The `close` option takes precedence over `keep-alive`.
+pub(crate) fn has_connection_close(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .flat_map(|value| value.as_bytes().split(|&c| c == b','))
+        .any(|part| part.trim_ascii().eq_ignore_ascii_case(b"close"))
+}
+
+pub(crate) fn has_expect_continue(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::EXPECT)
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"100-continue"))
+}
+
 pub(crate) fn remove_chunked_encoding(headers: &mut HeaderMap) {
     let Entry::Occupied(mut en) = headers.entry(header::TRANSFER_ENCODING) else {
         return;
@@ -142,6 +159,25 @@ mod tests {
         assert!(has_chunked_encoding(&h));
     }
 
+    #[test]
+    fn connection_close() {
+        let cases = [
+            ("close", true),
+            ("Close", true),
+            ("keep-alive, close", true),
+            ("keep-alive", false),
+            ("upgrade", false),
+        ];
+
+        for (value, close) in cases {
+            let h = headers(header::CONNECTION, value);
+            assert_eq!(has_connection_close(&h), close, "connection: {value}");
+        }
+
+        let h = headers(HeaderName::from_static("foo"), "close");
+        assert!(!has_connection_close(&h), "keep-alive by default");
+    }
+
     #[test]
     fn remove_none() {
         let mut h = headers(HeaderName::from_static("foo"), "bar");
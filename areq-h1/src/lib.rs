@@ -5,6 +5,7 @@ mod handler;
 mod headers;
 #[cfg(test)]
 mod test;
+mod upgrade;
 
 pub mod body {
     pub use areq_body::*;
@@ -14,4 +15,5 @@ pub use crate::{
     client::{Config, FetchBody, Requester},
     error::Error,
     handler::ReadStrategy,
+    upgrade::Upgraded,
 };
@@ -0,0 +1,106 @@
+use {
+    bytes::{Buf, Bytes},
+    futures_lite::prelude::*,
+    std::{
+        fmt, io,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+};
+
+/// The connection switched to another protocol.
+///
+/// Created by the [`upgrade`](crate::Config::upgrade) method.
+/// Bytes received after the response head are read first,
+/// then reading continues from the inner io.
+pub struct Upgraded<I> {
+    io: I,
+    read_buf: Bytes,
+}
+
+impl<I> Upgraded<I> {
+    pub(crate) fn new(io: I, read_buf: Bytes) -> Self {
+        Self { io, read_buf }
+    }
+
+    /// Returns bytes received after the response head that aren't read yet.
+    #[inline]
+    pub fn read_buf(&self) -> &[u8] {
+        &self.read_buf
+    }
+
+    /// Returns a reference to the inner io.
+    #[inline]
+    pub fn get_ref(&self) -> &I {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the inner io.
+    ///
+    /// Reading from it directly skips the unread
+    /// [buffered bytes](Upgraded::read_buf).
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+
+    /// Returns the inner io and the unread bytes.
+    #[inline]
+    pub fn into_parts(self) -> (I, Bytes) {
+        (self.io, self.read_buf)
+    }
+}
+
+impl<I> fmt::Debug for Upgraded<I> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgraded")
+            .field("read_buf", &self.read_buf)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I> AsyncRead for Upgraded<I>
+where
+    I: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+        if me.read_buf.is_empty() {
+            return Pin::new(&mut me.io).poll_read(cx, buf);
+        }
+
+        let n = usize::min(buf.len(), me.read_buf.len());
+        buf[..n].copy_from_slice(&me.read_buf[..n]);
+        me.read_buf.advance(n);
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<I> AsyncWrite for Upgraded<I>
+where
+    I: AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_close(cx)
+    }
+}
@@ -6,7 +6,10 @@ mod proto;
 
 /// The crate's prelude.
 pub mod prelude {
-    pub use crate::{Json, JsonBodyExt as _};
+    pub use crate::{Json, JsonBodyExt as _, JsonRequestExt as _};
 }
 
-pub use crate::{body::JsonBodyExt, proto::Json};
+pub use crate::{
+    body::JsonBodyExt,
+    proto::{Json, JsonRequestExt},
+};
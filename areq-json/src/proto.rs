@@ -1,6 +1,6 @@
 use {
     areq::{
-        Request,
+        ErrorKind, Request, RequestBuilder,
         body::{Body, IntoRequestBody},
         http::{HeaderValue, header},
     },
@@ -67,3 +67,111 @@ where
             .or_insert(HeaderValue::from_static("application/json"));
     }
 }
+
+/// Extension methods for a [request builder](RequestBuilder) for working with JSON.
+pub trait JsonRequestExt<'client, C, B>
+where
+    C: ?Sized,
+{
+    /// Sets the serialized JSON body.
+    ///
+    /// The `Content-Type` header is set to `application/json`
+    /// if it's missing. A serialization error is returned as
+    /// the [request](ErrorKind::Request) error when the request is sent.
+    fn json<T>(self, t: &T) -> RequestBuilder<'client, C, B, Json<T>>
+    where
+        T: Serialize + ?Sized;
+}
+
+impl<'client, C, B, I> JsonRequestExt<'client, C, B> for RequestBuilder<'client, C, B, I>
+where
+    C: ?Sized,
+{
+    fn json<T>(self, t: &T) -> RequestBuilder<'client, C, B, Json<T>>
+    where
+        T: Serialize + ?Sized,
+    {
+        let json = Json::new(t).map_err(|e| areq::Error::new(ErrorKind::Request, e));
+        self.try_body(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        areq::{
+            Client, ClientExt, Response,
+            body::BodyExt,
+            bytes::Bytes,
+            compress::Compress,
+            decompress::{Coding, Decoded},
+            http::{Method, Uri},
+        },
+        futures_lite::future,
+        std::collections::BTreeMap,
+    };
+
+    #[derive(Default)]
+    struct Record(Vec<Request<String>>);
+
+    impl Client<String> for Record {
+        type Body = Bytes;
+
+        async fn send(
+            &mut self,
+            req: Request<String>,
+        ) -> Result<Response<Self::Body>, areq::Error> {
+            self.0.push(req);
+            Ok(Response::new(areq::http::Response::new(Bytes::new())))
+        }
+    }
+
+    #[test]
+    fn json() -> Result<(), areq::Error> {
+        let mut client = Record::default();
+        let req = client
+            .request(Method::POST, Uri::from_static("/"))
+            .json(&[1, 2, 3])
+            .send();
+
+        future::block_on(req)?;
+
+        let [req] = &client.0[..] else {
+            panic!("one request should be sent");
+        };
+
+        assert_eq!(req.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(req.body(), "[1,2,3]");
+        Ok(())
+    }
+
+    #[test]
+    fn json_error() {
+        // json object keys must be strings
+        let map = BTreeMap::from([((1, 2), 3)]);
+
+        let mut client = Record::default();
+        let req = client
+            .request(Method::POST, Uri::from_static("/"))
+            .json(&map)
+            .send();
+
+        let e = future::block_on(req).expect_err("the value can't be serialized");
+        assert_eq!(e.kind(), ErrorKind::Request);
+        assert!(client.0.is_empty(), "the request shouldn't be sent");
+    }
+
+    #[test]
+    fn compress() -> Result<(), Error> {
+        let json = Json::new(&[1, 2, 3])?;
+        let req = Request::post(Uri::from_static("/"), Compress::new(json, Coding::Gzip));
+        assert_eq!(req.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(req.headers()[header::CONTENT_ENCODING], "gzip");
+
+        let body = areq::http::Request::from(req).into_body();
+        let body = Decoded::new(body, Some(Coding::Gzip))?;
+        assert_eq!(future::block_on(body.text())?, "[1,2,3]");
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use {
+    crate::timer::Timer,
+    areq::resolve::{self, Net, Resolve},
+    async_net::TcpStream,
+    std::{io::Error, net::SocketAddr},
+};
+
+/// The system [resolver](Resolve).
+///
+/// Resolves names with the operating system on a blocking thread pool.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct System;
+
+impl Resolve for System {
+    #[inline]
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, Error> {
+        async_net::resolve((host, port)).await
+    }
+}
+
+/// The smol TCP [network](Net).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tcp;
+
+impl Net for Tcp {
+    type Stream = TcpStream;
+    type Resolver = System;
+    type Timer = Timer;
+
+    #[inline]
+    async fn connect(addr: SocketAddr) -> Result<Self::Stream, Error> {
+        TcpStream::connect(addr).await
+    }
+}
+
+/// The TCP connector.
+///
+/// Resolves the address host and connects to one of the resolved
+/// addresses using the [Happy Eyeballs](resolve::happy_eyeballs)
+/// algorithm. See [`resolve::Connector`] for its methods.
+///
+/// # Example
+///
+/// ```
+/// use {
+///     areq_smol::{http::Uri, http1::Http1, prelude::*, resolve::Static, smol::{Connector, System}},
+///     std::io::Error,
+/// };
+///
+/// async fn get() -> Result<String, Error> {
+///     let uri = Uri::from_static("http://example.com:3001/hello");
+///
+///     // Resolve the domain to the local address
+///     let resolver = Static::new(System).add("example.com", 3001, [([127, 0, 0, 1], 3001).into()]);
+///     let connector = Connector::new().resolver(resolver);
+///
+///     Http1::default()
+///         .connect_with(&uri, &connector)
+///         .await?
+///         .handle(async |client| client.get(uri, ()).await?.text().await)
+///         .await
+/// }
+/// ```
+pub type Connector<R = System> = resolve::Connector<Tcp, R>;
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        areq::{Address, resolve::Static, time::Elapsed},
+        async_net::TcpListener,
+        futures_lite::future,
+        std::{
+            net::{Ipv4Addr, Ipv6Addr},
+            time::Duration,
+        },
+    };
+
+    #[test]
+    fn connect() -> Result<(), Error> {
+        future::block_on(async {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+            let local = listener.local_addr()?;
+
+            // the first address is unreachable, so the next one is used
+            let closed = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+            let unreachable = closed.local_addr()?;
+            drop(closed);
+
+            let resolver = Static::new(System).add(
+                "example.test",
+                80,
+                [
+                    unreachable,
+                    SocketAddr::from((Ipv6Addr::LOCALHOST, unreachable.port())),
+                    local,
+                ],
+            );
+
+            let connector = Connector::new()
+                .resolver(resolver)
+                .delay(Duration::from_millis(10));
+            let accept = async { listener.accept().await.map(|(stream, _)| stream) };
+            let addr = Address::http("example.test");
+            let connect = connector.connect(&addr);
+            let (server, client) = future::try_zip(accept, connect).await?;
+            assert_eq!(server.peer_addr()?, client.local_addr()?);
+            Ok(())
+        })
+    }
+
+    struct Stalled;
+
+    impl Resolve for Stalled {
+        async fn resolve(&self, _: &str, _: u16) -> Result<Vec<SocketAddr>, Error> {
+            future::pending().await
+        }
+    }
+
+    #[test]
+    fn connect_timeout() {
+        let connector = Connector::new()
+            .resolver(Stalled)
+            .connect_timeout(Duration::from_millis(10));
+
+        let addr = Address::http("example.test");
+        let e =
+            future::block_on(connector.connect(&addr)).expect_err("the connect should time out");
+        assert_eq!(e.kind(), std::io::ErrorKind::TimedOut);
+
+        let elapsed = e.get_ref().and_then(|e| e.downcast_ref());
+        assert_eq!(elapsed, Some(&Elapsed::Connect));
+    }
+}
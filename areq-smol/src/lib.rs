@@ -2,11 +2,23 @@
 #![allow(async_fn_in_trait)]
 
 mod connect;
+mod dns;
 mod handle;
+mod spawn;
+mod timer;
 
 /// Smol related traits.
 pub mod smol {
-    pub use crate::{connect::Connect, handle::Handle};
+    pub use crate::{
+        connect::Connect,
+        dns::{Connector, System, Tcp},
+        handle::Handle,
+        spawn::Spawner,
+        timer::Timer,
+    };
+
+    #[cfg(unix)]
+    pub use crate::connect::ConnectUnix;
 }
 
 /// The crate's prelude.
@@ -15,6 +27,9 @@ pub mod prelude {
         crate::smol::{Connect as _, Handle as _},
         areq::prelude::*,
     };
+
+    #[cfg(unix)]
+    pub use crate::smol::ConnectUnix as _;
 }
 
 pub use areq::*;
@@ -0,0 +1,58 @@
+use {areq::pool::Spawn, async_executor::Executor, std::sync::Arc};
+
+/// The [spawner](Spawn) that runs tasks in an [executor](Executor).
+///
+/// It's used to spawn connection tasks of a [pool](areq::pool::Pool).
+/// Spawned tasks are detached, so they run until the connection is closed.
+///
+/// # Example
+///
+/// ```
+/// use {
+///     areq_smol::{http::Uri, http1::Http1, pool::Pool, prelude::*, smol::Spawner},
+///     async_executor::Executor,
+///     std::{io::Error, sync::Arc},
+/// };
+///
+/// async fn get(ex: Arc<Executor<'static>>) -> Result<(), Error> {
+///     let spawner = Spawner::new(ex);
+///     let mut pool = Pool::new(|addr| Http1::default().connect(addr), spawner);
+///
+///     for _ in 0..3 {
+///         let uri = Uri::from_static("http://127.0.0.1:3001/hello");
+///
+///         // The connection is established once and then reused
+///         let text = pool.get(uri, ()).await?.text().await?;
+///         println!("{text}");
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Spawner<'ex>(Arc<Executor<'ex>>);
+
+impl<'ex> Spawner<'ex> {
+    /// Creates a new spawner from the executor.
+    #[inline]
+    pub fn new(ex: Arc<Executor<'ex>>) -> Self {
+        Self(ex)
+    }
+}
+
+impl<'ex> From<Arc<Executor<'ex>>> for Spawner<'ex> {
+    #[inline]
+    fn from(ex: Arc<Executor<'ex>>) -> Self {
+        Self(ex)
+    }
+}
+
+impl Spawn for Spawner<'_> {
+    #[inline]
+    fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.0.spawn(task).detach();
+    }
+}
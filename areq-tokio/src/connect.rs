@@ -1,10 +1,12 @@
 use {
-    crate::io::Io,
-    areq::{Address, Error, HandshakeWith, Session},
+    crate::{dns::Connector, io::Io},
+    areq::{Address, Error, HandshakeWith, Session, resolve::Resolve},
     tokio::net::TcpStream,
-    url::Host,
 };
 
+#[cfg(unix)]
+use {areq::UnixAddress, tokio::net::UnixStream};
+
 /// Extension trait to connect tokio [TCP stream](TcpStream).
 ///
 /// If a connection is successful, the [`connect`](Connect::connect) method
@@ -38,6 +40,15 @@ use {
 pub trait Connect<A, B>: HandshakeWith<Io<TcpStream>, B> {
     /// Connects to the given address.
     async fn connect(self, addr: A) -> Result<(Self::Client, Self::Task), Error>;
+
+    /// Connects to the given address with the [connector](Connector).
+    async fn connect_with<R>(
+        self,
+        addr: A,
+        connector: &Connector<R>,
+    ) -> Result<(Self::Client, Self::Task), Error>
+    where
+        R: Resolve;
 }
 
 impl<H, A, B> Connect<A, B> for H
@@ -47,13 +58,66 @@ where
 {
     #[inline]
     async fn connect(self, addr: A) -> Result<(Self::Client, Self::Task), Error> {
+        self.connect_with(addr, &Connector::new()).await
+    }
+
+    async fn connect_with<R>(
+        self,
+        addr: A,
+        connector: &Connector<R>,
+    ) -> Result<(Self::Client, Self::Task), Error>
+    where
+        R: Resolve,
+    {
         let addr = addr.try_into().map_err(A::Error::into)?;
-        let io = match &addr.host {
-            Host::Domain(d) => TcpStream::connect((d.as_str(), addr.port)).await?,
-            Host::Ipv4(ip4) => TcpStream::connect((*ip4, addr.port)).await?,
-            Host::Ipv6(ip6) => TcpStream::connect((*ip6, addr.port)).await?,
+        let io = connector.connect(&addr).await.map_err(Error::connect)?;
+        let se = Session {
+            addr,
+            io: Io::new(io),
         };
 
+        connector.handshake(self.handshake(se)).await
+    }
+}
+
+/// Extension trait to connect tokio [Unix stream](UnixStream).
+///
+/// Works like [`Connect`], but opens a Unix domain socket at the
+/// path of the [address](UnixAddress). Its logical address is used
+/// for the `Host` header.
+///
+/// # Example
+///
+/// ```
+/// use {
+///     areq_tokio::{UnixAddress, http1::Http1, prelude::*},
+///     std::io::Error,
+/// };
+///
+/// async fn get() -> Result<String, Error> {
+///     let (addr, uri) = UnixAddress::parse_uri("http+unix://%2Frun%2Fapp.sock/status")?;
+///
+///     let (mut client, conn) = Http1::default().connect_unix(addr).await?;
+///     tokio::spawn(conn);
+///     client.get(uri, ()).await?.text().await
+/// }
+/// ```
+#[cfg(unix)]
+pub trait ConnectUnix<A, B>: HandshakeWith<Io<UnixStream>, B> {
+    /// Connects to the given unix socket address.
+    async fn connect_unix(self, addr: A) -> Result<(Self::Client, Self::Task), Error>;
+}
+
+#[cfg(unix)]
+impl<H, A, B> ConnectUnix<A, B> for H
+where
+    A: TryInto<UnixAddress, Error: Into<Error>>,
+    H: HandshakeWith<Io<UnixStream>, B, Task: Send + 'static>,
+{
+    #[inline]
+    async fn connect_unix(self, addr: A) -> Result<(Self::Client, Self::Task), Error> {
+        let UnixAddress { path, addr } = addr.try_into().map_err(A::Error::into)?;
+        let io = UnixStream::connect(path).await.map_err(Error::connect)?;
         let se = Session {
             addr,
             io: Io::new(io),
@@ -62,3 +126,63 @@ where
         self.handshake(se).await
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use {
+        super::*,
+        areq::{ClientExt, body::BodyExt, http1::Http1},
+        futures_lite::future,
+        std::{env, io, process},
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::UnixListener,
+            runtime::Builder,
+        },
+    };
+
+    #[test]
+    fn connect_unix() -> Result<(), Error> {
+        let path = env::temp_dir().join(format!("areq-tokio-{}.sock", process::id()));
+        _ = std::fs::remove_file(&path);
+
+        let rt = Builder::new_current_thread().enable_all().build()?;
+        let (head, text) = rt.block_on(async {
+            let listener = UnixListener::bind(&path)?;
+            let serve = async {
+                let (mut stream, _) = listener.accept().await?;
+                let mut head = vec![];
+                while !head.ends_with(b"\r\n\r\n") {
+                    head.push(stream.read_u8().await?);
+                }
+
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nhello")
+                    .await?;
+
+                Ok::<_, io::Error>(head)
+            };
+
+            let addr = format!(
+                "http+unix://{}",
+                path.display().to_string().replace('/', "%2F")
+            );
+            let (addr, uri) = UnixAddress::parse_uri(&addr)?;
+            let get = async {
+                let (mut client, conn) = Http1::default().connect_unix(addr).await?;
+                let get = async move { client.get(uri, ()).await?.text().await };
+                let (text, ()) = future::zip(get, conn).await;
+                text.map_err(Error::from)
+            };
+
+            future::try_zip(async { serve.await.map_err(Error::from) }, get).await
+        })?;
+
+        _ = std::fs::remove_file(&path);
+        assert_eq!(text, "hello");
+
+        let head = String::from_utf8_lossy(&head);
+        assert!(head.contains("host: localhost\r\n"), "{head}");
+        Ok(())
+    }
+}
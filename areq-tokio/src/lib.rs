@@ -2,16 +2,31 @@
 #![allow(async_fn_in_trait)]
 
 mod connect;
+mod dns;
 mod io;
+mod spawn;
+mod timer;
 
 /// Tokio related types and traits.
 pub mod tokio {
-    pub use crate::{connect::Connect, io::Io};
+    pub use crate::{
+        connect::Connect,
+        dns::{Connector, System, Tcp},
+        io::Io,
+        spawn::Spawner,
+        timer::Timer,
+    };
+
+    #[cfg(unix)]
+    pub use crate::connect::ConnectUnix;
 }
 
 /// The crate's prelude.
 pub mod prelude {
     pub use {crate::tokio::Connect as _, areq::prelude::*};
+
+    #[cfg(unix)]
+    pub use crate::tokio::ConnectUnix as _;
 }
 
 pub use areq::*;
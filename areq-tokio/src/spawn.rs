@@ -0,0 +1,42 @@
+use areq::pool::Spawn;
+
+/// The [spawner](Spawn) that runs tasks on the current tokio runtime.
+///
+/// It's used to spawn connection tasks of a [pool](areq::pool::Pool).
+/// Since the [`tokio::spawn`](https://docs.rs/tokio/latest/tokio/task/fn.spawn.html)
+/// function is used, the pool must be used within the tokio runtime context.
+///
+/// # Example
+///
+/// ```
+/// use {
+///     areq_tokio::{http::Uri, http1::Http1, pool::Pool, prelude::*, tokio::Spawner},
+///     std::io::Error,
+/// };
+///
+/// async fn get() -> Result<(), Error> {
+///     let mut pool = Pool::new(|addr| Http1::default().connect(addr), Spawner);
+///
+///     for _ in 0..3 {
+///         let uri = Uri::from_static("http://127.0.0.1:3001/hello");
+///
+///         // The connection is established once and then reused
+///         let text = pool.get(uri, ()).await?.text().await?;
+///         println!("{text}");
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Spawner;
+
+impl Spawn for Spawner {
+    #[inline]
+    fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(task);
+    }
+}
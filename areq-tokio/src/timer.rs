@@ -0,0 +1,12 @@
+use {areq::time, std::time::Duration};
+
+/// The tokio [timer](time::Timer).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Timer;
+
+impl time::Timer for Timer {
+    #[inline]
+    async fn sleep(&self, dur: Duration) {
+        tokio::time::sleep(dur).await;
+    }
+}
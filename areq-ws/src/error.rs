@@ -0,0 +1,96 @@
+use {
+    http::StatusCode,
+    std::{error, fmt, io},
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Http(areq_h1::Error),
+    Rejected(StatusCode),
+    InvalidHandshake,
+    Protocol(&'static str),
+    TooLargeMessage,
+    InvalidUtf8,
+    Closed,
+}
+
+impl Error {
+    /// Returns the close code to report the error to the server.
+    pub(crate) fn close_code(&self) -> Option<u16> {
+        match self {
+            Self::Protocol(_) => Some(1002),
+            Self::InvalidUtf8 => Some(1007),
+            Self::TooLargeMessage => Some(1009),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn try_into_io(self) -> Result<io::Error, Self> {
+        match self {
+            Self::Io(e) => Ok(e),
+            Self::Http(areq_h1::Error::Io(e)) => Ok(e),
+            e => Err(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(v: io::Error) -> Self {
+        Self::Io(v)
+    }
+}
+
+impl From<areq_h1::Error> for Error {
+    #[inline]
+    fn from(v: areq_h1::Error) -> Self {
+        Self::Http(v)
+    }
+}
+
+impl From<Error> for io::Error {
+    #[inline]
+    fn from(e: Error) -> Self {
+        e.try_into_io().unwrap_or_else(|e| {
+            let kind = match e {
+                Error::Closed => io::ErrorKind::NotConnected,
+                Error::Rejected(_) => io::ErrorKind::ConnectionRefused,
+                _ => io::ErrorKind::InvalidData,
+            };
+
+            Self::new(kind, e)
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {e}"),
+            Self::Http(e) => write!(f, "http error: {e}"),
+            Self::Rejected(status) => write!(f, "the server rejected upgrade with {status}"),
+            Self::InvalidHandshake => write!(f, "invalid handshake response"),
+            Self::Protocol(e) => write!(f, "protocol error: {e}"),
+            Self::TooLargeMessage => write!(f, "too large message"),
+            Self::InvalidUtf8 => write!(f, "invalid utf-8 text"),
+            Self::Closed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Http(e) => Some(e),
+            Self::Rejected(_)
+            | Self::InvalidHandshake
+            | Self::Protocol(_)
+            | Self::TooLargeMessage
+            | Self::InvalidUtf8
+            | Self::Closed => None,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use {crate::error::Error, std::io};
+
+/// The maximum payload length of a control frame.
+pub(crate) const MAX_CONTROL_LEN: usize = 125;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Opcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    fn from_u8(n: u8) -> Option<Self> {
+        match n {
+            0x0 => Some(Self::Continuation),
+            0x1 => Some(Self::Text),
+            0x2 => Some(Self::Binary),
+            0x8 => Some(Self::Close),
+            0x9 => Some(Self::Ping),
+            0xa => Some(Self::Pong),
+            _ => None,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Continuation => 0x0,
+            Self::Text => 0x1,
+            Self::Binary => 0x2,
+            Self::Close => 0x8,
+            Self::Ping => 0x9,
+            Self::Pong => 0xa,
+        }
+    }
+
+    pub fn is_control(self) -> bool {
+        matches!(self, Self::Close | Self::Ping | Self::Pong)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Head {
+    pub fin: bool,
+    pub opcode: Opcode,
+    pub len: u64,
+}
+
+/// Parses the head of a server frame.
+///
+/// Returns the head with its length in bytes or
+/// `None` if the buffer doesn't contain the whole head.
+pub(crate) fn parse_head(buf: &[u8]) -> Result<Option<(Head, usize)>, Error> {
+    let &[b0, b1, ref rest @ ..] = buf else {
+        return Ok(None);
+    };
+
+    if b0 & 0x70 != 0 {
+        return Err(Error::Protocol("reserved bits are set"));
+    }
+
+    let opcode = Opcode::from_u8(b0 & 0x0f).ok_or(Error::Protocol("unknown opcode"))?;
+    if b1 & 0x80 != 0 {
+        return Err(Error::Protocol("the server frame is masked"));
+    }
+
+    let (len, head_len) = match b1 & 0x7f {
+        126 => match rest {
+            &[a, b, ..] => (u64::from(u16::from_be_bytes([a, b])), 4),
+            _ => return Ok(None),
+        },
+        127 => match rest.first_chunk() {
+            Some(&bytes) => (u64::from_be_bytes(bytes), 10),
+            None => return Ok(None),
+        },
+        len => (u64::from(len), 2),
+    };
+
+    let fin = b0 & 0x80 != 0;
+    if opcode.is_control() {
+        if !fin {
+            return Err(Error::Protocol("the control frame is fragmented"));
+        }
+
+        if len > MAX_CONTROL_LEN as u64 {
+            return Err(Error::Protocol("the control frame is too large"));
+        }
+    }
+
+    Ok(Some((Head { fin, opcode, len }, head_len)))
+}
+
+/// Writes the masked client frame to the buffer.
+pub(crate) fn write_frame(
+    buf: &mut Vec<u8>,
+    fin: bool,
+    opcode: Opcode,
+    payload: &[u8],
+) -> Result<(), Error> {
+    let b0 = u8::from(fin) << 7 | opcode.to_u8();
+    buf.push(b0);
+
+    const MASK: u8 = 0x80;
+    let len = payload.len();
+    if let Ok(len @ 0..=125) = u8::try_from(len) {
+        buf.push(MASK | len);
+    } else if let Ok(len) = u16::try_from(len) {
+        buf.push(MASK | 126);
+        buf.extend_from_slice(&len.to_be_bytes());
+    } else {
+        buf.push(MASK | 127);
+        buf.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+
+    let key = mask_key()?;
+    buf.extend_from_slice(&key);
+
+    let start = buf.len();
+    buf.extend_from_slice(payload);
+    apply_mask(&mut buf[start..], key);
+    Ok(())
+}
+
+pub(crate) fn apply_mask(data: &mut [u8], key: [u8; 4]) {
+    for (byte, k) in data.iter_mut().zip(key.iter().cycle()) {
+        *byte ^= k;
+    }
+}
+
+/// Returns a random masking key.
+///
+/// The key must be unpredictable, so it's taken from the system
+/// random number generator.
+fn mask_key() -> Result<[u8; 4], io::Error> {
+    let mut key = [0; 4];
+    getrandom::fill(&mut key)?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> Result<(), Error> {
+        let cases: [(&[u8], _); 5] = [
+            (&[0x81, 0x05], (true, Opcode::Text, 5, 2)),
+            (&[0x02, 0x7e, 0x01, 0x00], (false, Opcode::Binary, 256, 4)),
+            (
+                &[0x80, 0x7f, 0, 0, 0, 0, 0, 1, 0, 0],
+                (true, Opcode::Continuation, 1 << 16, 10),
+            ),
+            (&[0x89, 0x00], (true, Opcode::Ping, 0, 2)),
+            (&[0x8a, 0x7d], (true, Opcode::Pong, 125, 2)),
+        ];
+
+        for (buf, (fin, opcode, len, head_len)) in cases {
+            let head = Head { fin, opcode, len };
+            assert_eq!(parse_head(buf)?, Some((head, head_len)));
+            assert_eq!(parse_head(&buf[..buf.len() - 1])?, None);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let cases: [&[u8]; 5] = [
+            &[0xc1, 0x00],
+            &[0x83, 0x00],
+            &[0x81, 0x80],
+            &[0x09, 0x00],
+            &[0x88, 0x7e, 0x00, 0x7e],
+        ];
+
+        for buf in cases {
+            let e = parse_head(buf).expect_err("invalid frame");
+            assert!(matches!(e, Error::Protocol(_)));
+        }
+    }
+
+    #[test]
+    fn write() -> Result<(), Error> {
+        for len in [0, 125, 126, 0xffff, 0x10000] {
+            let payload = vec![7; len];
+            let mut buf = vec![];
+            write_frame(&mut buf, true, Opcode::Binary, &payload)?;
+
+            let (head_len, len_bytes) = match buf[1] & 0x7f {
+                126 => (4, &buf[2..4]),
+                127 => (10, &buf[2..10]),
+                n => (2, &[n][..]),
+            };
+
+            assert_eq!(buf[0], 0x82);
+            assert_eq!(buf[1] & 0x80, 0x80, "the frame must be masked");
+            assert_eq!(
+                len_bytes.iter().fold(0, |n, &b| n << 8 | usize::from(b)),
+                len
+            );
+
+            let key = buf[head_len..head_len + 4].try_into().expect("mask key");
+            let data = &mut buf[head_len + 4..];
+            apply_mask(data, key);
+            assert_eq!(data, payload);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+use {
+    crate::{error::Error, socket::WebSocket},
+    areq_h1::Upgraded,
+    futures_lite::prelude::*,
+    http::{
+        HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode, Version, header,
+    },
+    std::io,
+};
+
+const KB: usize = 1 << 10;
+
+/// The websocket configuration.
+#[derive(Clone)]
+pub struct Config {
+    h1: areq_h1::Config,
+    pub(crate) frame_size: usize,
+    pub(crate) max_message_size: usize,
+}
+
+impl Config {
+    /// Sets the http/1.1 configuration used for the handshake.
+    #[inline]
+    pub fn h1(mut self, h1: areq_h1::Config) -> Self {
+        self.h1 = h1;
+        self
+    }
+
+    /// Sets the maximum payload size of sent frames.
+    ///
+    /// Larger messages are fragmented. By default, it's 16 KiB.
+    #[inline]
+    pub fn frame_size(mut self, n: usize) -> Self {
+        self.frame_size = n.max(1);
+        self
+    }
+
+    /// Sets the maximum size of received messages.
+    ///
+    /// By default, it's 16 MiB.
+    #[inline]
+    pub fn max_message_size(mut self, n: usize) -> Self {
+        self.max_message_size = n;
+        self
+    }
+
+    /// Performs the opening handshake over the connection.
+    ///
+    /// The request is sent with the `GET` method and headers required
+    /// to upgrade the connection. Other headers like `Host` or
+    /// `Sec-WebSocket-Protocol` must be set by the caller.
+    ///
+    /// # Errors
+    /// Returns [`Error::Rejected`] if the server doesn't switch protocols
+    /// and [`Error::InvalidHandshake`] if the response isn't valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use {
+    ///     areq_ws::{Config, Error, Message},
+    ///     futures_lite::prelude::*,
+    ///     http::{HeaderValue, Request, header},
+    /// };
+    ///
+    /// async fn echo<I>(io: I) -> Result<(), Error>
+    /// where
+    ///     I: AsyncRead + AsyncWrite + Unpin,
+    /// {
+    ///     let mut req = Request::new(());
+    ///     *req.uri_mut() = "/echo".parse().expect("valid uri");
+    ///     req.headers_mut()
+    ///         .insert(header::HOST, HeaderValue::from_static("localhost"));
+    ///
+    ///     let (_, mut ws) = Config::default().handshake(io, req).await?;
+    ///     ws.send(Message::Text("hello".to_owned())).await?;
+    ///     if let Some(message) = ws.recv().await {
+    ///         println!("{:?}", message?);
+    ///     }
+    ///
+    ///     ws.close(None).await
+    /// }
+    /// ```
+    pub async fn handshake<I>(
+        &self,
+        io: I,
+        req: Request<()>,
+    ) -> Result<(Response<()>, WebSocket<Upgraded<I>>), Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut nonce = [0; 16];
+        getrandom::fill(&mut nonce).map_err(io::Error::from)?;
+
+        let key = base64(&nonce);
+        self.handshake_with_key(io, req, &key).await
+    }
+
+    async fn handshake_with_key<I>(
+        &self,
+        io: I,
+        mut req: Request<()>,
+        key: &str,
+    ) -> Result<(Response<()>, WebSocket<Upgraded<I>>), Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        *req.method_mut() = Method::GET;
+        *req.version_mut() = Version::HTTP_11;
+
+        let headers = req.headers_mut();
+        headers.insert(
+            header::CONNECTION,
+            const { HeaderValue::from_static("upgrade") },
+        );
+
+        headers.insert(
+            header::UPGRADE,
+            const { HeaderValue::from_static("websocket") },
+        );
+
+        headers.insert(
+            header::SEC_WEBSOCKET_VERSION,
+            const { HeaderValue::from_static("13") },
+        );
+
+        let key_value = HeaderValue::from_str(key).expect("base64 is a valid header value");
+        headers.insert(header::SEC_WEBSOCKET_KEY, key_value);
+
+        let (res, io) = self.h1.upgrade(io, req).await?;
+        if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(Error::Rejected(res.status()));
+        }
+
+        let headers = res.headers();
+        let valid = has_token(headers, header::UPGRADE, "websocket")
+            && has_token(headers, header::CONNECTION, "upgrade")
+            && headers
+                .get(header::SEC_WEBSOCKET_ACCEPT)
+                .is_some_and(|accept| accept.as_bytes() == accept_key(key).as_bytes());
+
+        if !valid {
+            return Err(Error::InvalidHandshake);
+        }
+
+        Ok((res, WebSocket::new(io, self)))
+    }
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            h1: areq_h1::Config::default(),
+            frame_size: 16 * KB,
+            max_message_size: 16 * KB * KB,
+        }
+    }
+}
+
+/// Checks if the header contains the token in its comma-separated list.
+fn has_token(headers: &HeaderMap, name: HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(token))
+}
+
+/// Computes the `Sec-WebSocket-Accept` value for the key.
+fn accept_key(key: &str) -> String {
+    const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+    let mut sha = sha1_smol::Sha1::new();
+    sha.update(key.as_bytes());
+    sha.update(GUID.as_bytes());
+    base64(&sha.digest().bytes())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let idx = (n >> (18 - 6 * i)) & 0x3f;
+                out.push(char::from(ALPHABET[idx as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{socket::Message, test},
+        futures_lite::future,
+    };
+
+    // the key and the accept value from rfc 6455
+    const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+    const ACCEPT: &str = "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=";
+
+    #[test]
+    fn accept() {
+        assert_eq!(accept_key(KEY), ACCEPT);
+        assert_eq!(base64(b"the sample nonce"), KEY);
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn handshake() -> Result<(), Error> {
+        let mut response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\n\
+            upgrade: websocket\r\n\
+            connection: Upgrade\r\n\
+            sec-websocket-accept: {ACCEPT}\r\n\
+            \r\n",
+        )
+        .into_bytes();
+
+        // the first frame is received with the response head
+        response.extend_from_slice(b"\x81\x02hi");
+
+        let io = test::Mock::new(response);
+        let mut req = Request::new(());
+        *req.uri_mut() = "/chat".parse().expect("valid uri");
+
+        let conf = Config::default();
+        let (res, mut ws) = future::block_on(conf.handshake_with_key(io, req, KEY))?;
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+
+        let message = future::block_on(ws.recv()).expect("message")?;
+        assert_eq!(message, Message::Text("hi".to_owned()));
+
+        let request = String::from_utf8(ws.get_ref().get_ref().output.clone()).expect("utf-8");
+        let expected = format!(
+            "GET /chat HTTP/1.1\r\n\
+            connection: upgrade\r\n\
+            upgrade: websocket\r\n\
+            sec-websocket-version: 13\r\n\
+            sec-websocket-key: {KEY}\r\n\
+            \r\n",
+        );
+
+        assert_eq!(request, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rejected() {
+        let response = b"HTTP/1.1 400 Bad Request\r\ncontent-length: 0\r\n\r\n";
+        let io = test::Mock::new(response.to_vec());
+        let conf = Config::default();
+        let res = future::block_on(conf.handshake_with_key(io, Request::new(()), KEY));
+        assert!(matches!(res, Err(Error::Rejected(StatusCode::BAD_REQUEST))));
+    }
+
+    #[test]
+    fn invalid_accept() {
+        let response = b"\
+            HTTP/1.1 101 Switching Protocols\r\n\
+            upgrade: websocket\r\n\
+            connection: upgrade\r\n\
+            sec-websocket-accept: invalid\r\n\
+            \r\n";
+
+        let io = test::Mock::new(response.to_vec());
+        let conf = Config::default();
+        let res = future::block_on(conf.handshake_with_key(io, Request::new(()), KEY));
+        assert!(matches!(res, Err(Error::InvalidHandshake)));
+    }
+}
@@ -0,0 +1,17 @@
+#![cfg_attr(doc, doc = include_str!("../README.md"))]
+
+mod error;
+mod frame;
+mod handshake;
+mod socket;
+#[cfg(test)]
+mod test;
+
+pub use {
+    crate::{
+        error::Error,
+        handshake::Config,
+        socket::{CloseFrame, Message, WebSocket},
+    },
+    areq_h1::Upgraded,
+};
@@ -0,0 +1,469 @@
+use {
+    crate::{
+        error::Error,
+        frame::{self, MAX_CONTROL_LEN, Opcode},
+        handshake::Config,
+    },
+    bytes::{Buf, Bytes, BytesMut},
+    futures_lite::prelude::*,
+    std::{io, mem},
+};
+
+const READ_LEN: usize = 4 << 10;
+
+/// The websocket message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// The UTF-8 text message.
+    Text(String),
+    /// The binary message.
+    Binary(Bytes),
+    /// The ping with its payload.
+    ///
+    /// A received ping is answered with a pong automatically.
+    Ping(Bytes),
+    /// The pong with its payload.
+    Pong(Bytes),
+    /// The close message with an optional code and reason.
+    Close(Option<CloseFrame>),
+}
+
+/// The code and the reason of closing the connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CloseFrame {
+    /// The status code of the closure.
+    pub code: u16,
+    /// The reason of the closure.
+    pub reason: String,
+}
+
+impl CloseFrame {
+    /// The normal closure code.
+    pub const NORMAL: u16 = 1000;
+
+    /// Creates the close frame with the code and the reason.
+    #[inline]
+    pub fn new<S>(code: u16, reason: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// The websocket connection.
+///
+/// Created by the [`handshake`](Config::handshake) method.
+/// Received pings are answered automatically, fragmented
+/// messages are assembled before they're returned.
+pub struct WebSocket<I> {
+    io: I,
+    frame_size: usize,
+    max_message_size: usize,
+    read_buf: BytesMut,
+    write_buf: Vec<u8>,
+    fragments: Option<(Opcode, BytesMut)>,
+    close_sent: bool,
+    closed: bool,
+}
+
+impl<I> WebSocket<I> {
+    /// Wraps the connection on which the opening handshake is already done.
+    #[inline]
+    pub fn new(io: I, conf: &Config) -> Self {
+        Self {
+            io,
+            frame_size: conf.frame_size,
+            max_message_size: conf.max_message_size,
+            read_buf: BytesMut::new(),
+            write_buf: vec![],
+            fragments: None,
+            close_sent: false,
+            closed: false,
+        }
+    }
+
+    /// Returns a reference to the underlying connection.
+    #[inline]
+    pub fn get_ref(&self) -> &I {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying connection.
+    ///
+    /// Writing to it directly may corrupt the websocket framing.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+
+    /// Returns the underlying connection.
+    ///
+    /// Data that is read but not returned as a message yet is lost.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.io
+    }
+
+    /// Sends the message.
+    ///
+    /// Text and binary messages larger than the
+    /// [frame size](Config::frame_size) are fragmented.
+    /// Sending the [close](Message::Close) message starts
+    /// the closing handshake.
+    ///
+    /// # Errors
+    /// Returns [`Error::Closed`] if the close message has already been sent.
+    pub async fn send(&mut self, message: Message) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        if self.close_sent {
+            return Err(Error::Closed);
+        }
+
+        match message {
+            Message::Text(text) => self.write_message(Opcode::Text, text.as_bytes()).await,
+            Message::Binary(data) => self.write_message(Opcode::Binary, &data).await,
+            Message::Ping(data) => self.write_control(Opcode::Ping, &data).await,
+            Message::Pong(data) => self.write_control(Opcode::Pong, &data).await,
+            Message::Close(frame) => self.write_close(frame.as_ref()).await,
+        }
+    }
+
+    /// Receives the next message.
+    ///
+    /// Returns `None` when the connection is closed. The close message
+    /// of the server is returned before that and answered automatically.
+    ///
+    /// # Errors
+    /// If the server violates the protocol, the connection is closed
+    /// with the corresponding code and the error is returned.
+    pub async fn recv(&mut self) -> Option<Result<Message, Error>>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        if self.closed {
+            return None;
+        }
+
+        let res = self.read_message().await;
+        if let Err(e) = &res {
+            self.closed = true;
+            if let Some(code) = e.close_code() {
+                if !self.close_sent {
+                    _ = self.write_close(Some(&CloseFrame::new(code, ""))).await;
+                }
+            }
+        }
+
+        Some(res)
+    }
+
+    /// Performs the closing handshake.
+    ///
+    /// Sends the close message if it's not sent yet, then skips received
+    /// messages until the server answers and closes the connection.
+    pub async fn close(&mut self, frame: Option<CloseFrame>) -> Result<(), Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        if !self.close_sent {
+            self.write_close(frame.as_ref()).await?;
+        }
+
+        while let Some(res) = self.recv().await {
+            res?;
+        }
+
+        self.io.close().await?;
+        Ok(())
+    }
+
+    async fn read_message(&mut self) -> Result<Message, Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        loop {
+            let (head, payload) = self.read_frame().await?;
+            match head.opcode {
+                Opcode::Ping => {
+                    if !self.close_sent {
+                        self.write_control(Opcode::Pong, &payload).await?;
+                    }
+
+                    return Ok(Message::Ping(payload));
+                }
+                Opcode::Pong => return Ok(Message::Pong(payload)),
+                Opcode::Close => {
+                    let frame = parse_close(payload)?;
+                    self.closed = true;
+                    if !self.close_sent {
+                        // echo the close code as the response
+                        let echo = frame.as_ref().map(|frame| CloseFrame::new(frame.code, ""));
+                        self.write_close(echo.as_ref()).await?;
+                    }
+
+                    return Ok(Message::Close(frame));
+                }
+                Opcode::Text | Opcode::Binary => {
+                    if self.fragments.is_some() {
+                        return Err(Error::Protocol("the fragmented message isn't finished"));
+                    }
+
+                    if head.fin {
+                        return into_message(head.opcode, payload);
+                    }
+
+                    self.fragments = Some((head.opcode, BytesMut::from(payload)));
+                }
+                Opcode::Continuation => {
+                    let Some((opcode, buf)) = &mut self.fragments else {
+                        return Err(Error::Protocol("unexpected continuation frame"));
+                    };
+
+                    if buf.len() + payload.len() > self.max_message_size {
+                        return Err(Error::TooLargeMessage);
+                    }
+
+                    buf.extend_from_slice(&payload);
+                    if head.fin {
+                        let opcode = *opcode;
+                        let buf = mem::take(buf).freeze();
+                        self.fragments = None;
+                        return into_message(opcode, buf);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn read_frame(&mut self) -> Result<(frame::Head, Bytes), Error>
+    where
+        I: AsyncRead + Unpin,
+    {
+        let (head, head_len) = loop {
+            if let Some(head) = frame::parse_head(&self.read_buf)? {
+                break head;
+            }
+
+            self.read_to_buf().await?;
+        };
+
+        let len = usize::try_from(head.len)
+            .ok()
+            .filter(|&len| len <= self.max_message_size)
+            .ok_or(Error::TooLargeMessage)?;
+
+        while self.read_buf.len() < head_len + len {
+            self.read_to_buf().await?;
+        }
+
+        self.read_buf.advance(head_len);
+        let payload = self.read_buf.split_to(len).freeze();
+        Ok((head, payload))
+    }
+
+    async fn read_to_buf(&mut self) -> Result<(), Error>
+    where
+        I: AsyncRead + Unpin,
+    {
+        let mut buf = [0; READ_LEN];
+        let n = self.io.read(&mut buf).await?;
+        if n == 0 {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        self.read_buf.extend_from_slice(&buf[..n]);
+        Ok(())
+    }
+
+    async fn write_message(&mut self, opcode: Opcode, payload: &[u8]) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        self.write_buf.clear();
+
+        let mut frames = payload.chunks(self.frame_size).peekable();
+        if frames.peek().is_none() {
+            frame::write_frame(&mut self.write_buf, true, opcode, &[])?;
+        }
+
+        let mut opcode = opcode;
+        while let Some(data) = frames.next() {
+            let fin = frames.peek().is_none();
+            frame::write_frame(&mut self.write_buf, fin, opcode, data)?;
+            opcode = Opcode::Continuation;
+        }
+
+        self.flush_buf().await
+    }
+
+    async fn write_control(&mut self, opcode: Opcode, payload: &[u8]) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        if payload.len() > MAX_CONTROL_LEN {
+            return Err(Error::Protocol("the control frame is too large"));
+        }
+
+        self.write_buf.clear();
+        frame::write_frame(&mut self.write_buf, true, opcode, payload)?;
+        self.flush_buf().await
+    }
+
+    async fn write_close(&mut self, frame: Option<&CloseFrame>) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        let mut payload = vec![];
+        if let Some(frame) = frame {
+            payload.extend_from_slice(&frame.code.to_be_bytes());
+            payload.extend_from_slice(frame.reason.as_bytes());
+        }
+
+        self.write_control(Opcode::Close, &payload).await?;
+        self.close_sent = true;
+        Ok(())
+    }
+
+    async fn flush_buf(&mut self) -> Result<(), Error>
+    where
+        I: AsyncWrite + Unpin,
+    {
+        self.io.write_all(&self.write_buf).await?;
+        self.io.flush().await?;
+        Ok(())
+    }
+}
+
+fn into_message(opcode: Opcode, payload: Bytes) -> Result<Message, Error> {
+    match opcode {
+        Opcode::Text => String::from_utf8(Vec::from(payload))
+            .map(Message::Text)
+            .map_err(|_| Error::InvalidUtf8),
+        _ => Ok(Message::Binary(payload)),
+    }
+}
+
+fn parse_close(payload: Bytes) -> Result<Option<CloseFrame>, Error> {
+    let (code, reason) = match &payload[..] {
+        [] => return Ok(None),
+        [a, b, reason @ ..] => (u16::from_be_bytes([*a, *b]), reason),
+        [_] => return Err(Error::Protocol("invalid close frame")),
+    };
+
+    // codes which are reserved or must not be sent
+    let valid = matches!(code, 1000..=1003 | 1007..=1014 | 3000..=4999);
+    if !valid {
+        return Err(Error::Protocol("invalid close code"));
+    }
+
+    let reason = str::from_utf8(reason).map_err(|_| Error::InvalidUtf8)?;
+    Ok(Some(CloseFrame::new(code, reason)))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test, futures_lite::future};
+
+    fn socket(input: &[u8]) -> WebSocket<test::Mock> {
+        let io = test::Mock::new(input.to_vec());
+        WebSocket::new(io, &Config::default())
+    }
+
+    fn sent(ws: &WebSocket<test::Mock>) -> Vec<(bool, u8, Vec<u8>)> {
+        test::decode(&ws.get_ref().output)
+    }
+
+    #[test]
+    fn fragmented() -> Result<(), Error> {
+        let mut ws = socket(b"\x01\x03hel\x89\x01p\x00\x01l\x80\x01o");
+
+        let ping = future::block_on(ws.recv()).expect("ping")?;
+        assert_eq!(ping, Message::Ping(Bytes::from_static(b"p")));
+
+        let text = future::block_on(ws.recv()).expect("text")?;
+        assert_eq!(text, Message::Text("hello".to_owned()));
+
+        assert_eq!(sent(&ws), [(true, 0xa, b"p".to_vec())]);
+        Ok(())
+    }
+
+    #[test]
+    fn send_fragmented() -> Result<(), Error> {
+        let mut ws = socket(b"");
+        ws.frame_size = 2;
+
+        future::block_on(ws.send(Message::Text("hello".to_owned())))?;
+        future::block_on(ws.send(Message::Binary(Bytes::new())))?;
+        assert_eq!(
+            sent(&ws),
+            [
+                (false, 0x1, b"he".to_vec()),
+                (false, 0x0, b"ll".to_vec()),
+                (true, 0x0, b"o".to_vec()),
+                (true, 0x2, vec![]),
+            ],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn closed_by_server() -> Result<(), Error> {
+        let mut ws = socket(b"\x88\x05\x03\xe8bye");
+
+        let close = future::block_on(ws.recv()).expect("close")?;
+        assert_eq!(close, Message::Close(Some(CloseFrame::new(1000, "bye"))));
+        assert!(future::block_on(ws.recv()).is_none());
+        assert!(matches!(
+            future::block_on(ws.send(Message::Text(String::new()))),
+            Err(Error::Closed),
+        ));
+
+        assert_eq!(sent(&ws), [(true, 0x8, b"\x03\xe8".to_vec())]);
+        Ok(())
+    }
+
+    #[test]
+    fn closed_by_client() -> Result<(), Error> {
+        let mut ws = socket(b"\x82\x01x\x88\x02\x03\xe8");
+
+        let frame = CloseFrame::new(CloseFrame::NORMAL, "done");
+        future::block_on(ws.close(Some(frame)))?;
+        assert!(future::block_on(ws.recv()).is_none());
+        assert_eq!(sent(&ws), [(true, 0x8, b"\x03\xe8done".to_vec())]);
+        Ok(())
+    }
+
+    #[test]
+    fn protocol_error() {
+        let cases: [(&[u8], u16); 4] = [
+            (b"\x81\x81\x00\x00\x00\x00x", 1002),
+            (b"\x80\x01x", 1002),
+            (b"\x81\x01\xff", 1007),
+            (b"\x01\x01x\x81\x01x", 1002),
+        ];
+
+        for (input, code) in cases {
+            let mut ws = socket(input);
+            let res = future::block_on(ws.recv()).expect("error");
+            assert_eq!(res.expect_err("invalid frame").close_code(), Some(code));
+            assert!(future::block_on(ws.recv()).is_none());
+            assert_eq!(sent(&ws), [(true, 0x8, code.to_be_bytes().to_vec())]);
+        }
+    }
+
+    #[test]
+    fn too_large_message() {
+        let mut ws = socket(b"\x01\x02ab\x80\x02cd");
+        ws.max_message_size = 3;
+
+        let res = future::block_on(ws.recv()).expect("error");
+        assert!(matches!(res, Err(Error::TooLargeMessage)));
+    }
+}
@@ -0,0 +1,87 @@
+use {
+    futures_lite::prelude::*,
+    std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+};
+
+/// The connection with a prepared input that records the output.
+pub(crate) struct Mock {
+    input: io::Cursor<Vec<u8>>,
+    pub output: Vec<u8>,
+}
+
+impl Mock {
+    pub fn new(input: Vec<u8>) -> Self {
+        Self {
+            input: io::Cursor::new(input),
+            output: vec![],
+        }
+    }
+}
+
+impl AsyncRead for Mock {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(io::Read::read(&mut self.input, buf))
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.output.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Decodes masked client frames as `(fin, opcode, payload)`.
+pub(crate) fn decode(mut buf: &[u8]) -> Vec<(bool, u8, Vec<u8>)> {
+    let mut frames = vec![];
+    while let &[b0, b1, ref rest @ ..] = buf {
+        assert_eq!(b1 & 0x80, 0x80, "the client frame must be masked");
+
+        let (len, rest) = match b1 & 0x7f {
+            126 => {
+                let (len, rest) = rest.split_at(2);
+                (usize::from(u16::from_be_bytes([len[0], len[1]])), rest)
+            }
+            127 => {
+                let (len, rest) = rest.split_at(8);
+                let len = u64::from_be_bytes(len.try_into().expect("8 bytes"));
+                (usize::try_from(len).expect("length fits"), rest)
+            }
+            len => (usize::from(len), rest),
+        };
+
+        let (key, rest) = rest.split_at(4);
+        let (payload, rest) = rest.split_at(len);
+        let payload = payload
+            .iter()
+            .zip(key.iter().cycle())
+            .map(|(b, k)| b ^ k)
+            .collect();
+
+        frames.push((b0 & 0x80 != 0, b0 & 0x0f, payload));
+        buf = rest;
+    }
+
+    assert!(buf.is_empty(), "incomplete frame");
+    frames
+}
@@ -1,20 +1,21 @@
 use {
-    crate::proto::Error,
+    crate::error::{Error, ErrorKind},
     bytes::Bytes,
     http::{
         Uri,
         uri::{Authority, Scheme},
     },
     std::{
-        error, fmt,
-        io::{self, ErrorKind},
+        error, fmt, io,
         net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        path::PathBuf,
+        str::FromStr,
     },
     url::Host,
 };
 
 /// The network address.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Address {
     pub host: Host,
     pub port: u16,
@@ -75,7 +76,7 @@ impl Address {
             match &self.host {
                 Host::Domain(domain) => Bytes::copy_from_slice(domain.as_bytes()),
                 Host::Ipv4(ip) => Bytes::from(ip.to_string()),
-                Host::Ipv6(ip) => Bytes::from(ip.to_string()),
+                Host::Ipv6(ip) => Bytes::from(format!("[{ip}]")),
             }
         } else {
             let host = &self.host;
@@ -101,6 +102,108 @@ impl TryFrom<Uri> for Address {
     }
 }
 
+/// The address of a Unix domain socket.
+///
+/// The socket is opened at the filesystem path, while the logical
+/// [address](Address) is used for the `Host` header.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnixAddress {
+    pub path: PathBuf,
+    pub addr: Address,
+}
+
+impl UnixAddress {
+    /// The scheme of unix socket uris.
+    pub const SCHEME: &str = "http+unix";
+
+    /// Creates new address with the logical `localhost` address.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            addr: Address::http("localhost"),
+        }
+    }
+
+    /// Parses the `http+unix` uri into the address
+    /// and the request uri with the logical address.
+    ///
+    /// The socket path is the percent-encoded host of the uri,
+    /// for example, `http+unix://%2Frun%2Fapp.sock/status` is split into
+    /// the `/run/app.sock` path and `http://localhost/status` uri.
+    /// The [`Uri`] type doesn't allow such hosts, so the uri is parsed
+    /// from a string.
+    ///
+    /// # Errors
+    /// Returns [`InvalidUri`] if url is not valid.
+    pub fn parse_uri(s: &str) -> Result<(Self, Uri), InvalidUri> {
+        let (scheme, rest) = s.split_once("://").ok_or(InvalidUri::NoScheme)?;
+        if !scheme.eq_ignore_ascii_case(Self::SCHEME) {
+            return Err(InvalidUri::NonHttpScheme);
+        }
+
+        let (host, path) = rest
+            .find(['/', '?', '#'])
+            .map_or((rest, ""), |n| rest.split_at(n));
+
+        let socket = percent_decode(host).ok_or(InvalidUri::InvalidHost)?;
+        if socket.is_empty() {
+            return Err(InvalidUri::InvalidHost);
+        }
+
+        let addr = Self::new(socket);
+        let uri = format!("http://{}{path}", addr.addr.host);
+        let uri = Uri::try_from(uri).map_err(|_| InvalidUri::InvalidHost)?;
+        Ok((addr, uri))
+    }
+}
+
+impl<P> From<(P, Address)> for UnixAddress
+where
+    P: Into<PathBuf>,
+{
+    fn from((path, addr): (P, Address)) -> Self {
+        Self {
+            path: path.into(),
+            addr,
+        }
+    }
+}
+
+impl FromStr for UnixAddress {
+    type Err = InvalidUri;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_uri(s).map(|(addr, _)| addr)
+    }
+}
+
+impl TryFrom<&str> for UnixAddress {
+    type Error = InvalidUri;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            let hex = str::from_utf8(&hex).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            out.push(b);
+        }
+    }
+
+    String::from_utf8(out).ok()
+}
+
 fn default_port(secure: bool) -> u16 {
     const HTTP: u16 = 80;
     const HTTPS: u16 = 443;
@@ -117,13 +220,13 @@ pub enum InvalidUri {
 
 impl From<InvalidUri> for io::Error {
     fn from(e: InvalidUri) -> Self {
-        Self::new(ErrorKind::InvalidInput, e)
+        Self::new(io::ErrorKind::InvalidInput, e)
     }
 }
 
 impl From<InvalidUri> for Error {
     fn from(e: InvalidUri) -> Self {
-        Self::Io(e.into())
+        Self::new(ErrorKind::Request, e)
     }
 }
 
@@ -181,3 +284,38 @@ impl IntoHost for IpAddr {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_uri() -> Result<(), InvalidUri> {
+        let (addr, uri) = UnixAddress::parse_uri("http+unix://%2Frun%2Fapp.sock/status?q")?;
+        assert_eq!(addr.path, PathBuf::from("/run/app.sock"));
+        assert_eq!(addr.addr, Address::http("localhost"));
+        assert_eq!(uri, "http://localhost/status?q");
+
+        let (addr, uri) = UnixAddress::parse_uri("http+unix://app.sock")?;
+        assert_eq!(addr.path, PathBuf::from("app.sock"));
+        assert_eq!(uri, "http://localhost/");
+
+        assert!(
+            matches!(
+                "http://localhost/".parse::<UnixAddress>(),
+                Err(InvalidUri::NonHttpScheme),
+            ),
+            "not a unix uri",
+        );
+
+        assert!(
+            matches!(
+                "http+unix://%ff/".parse::<UnixAddress>(),
+                Err(InvalidUri::InvalidHost),
+            ),
+            "invalid encoding",
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 use {
     crate::{
         client::Client,
+        error::Error,
         negotiate::Negotiate,
-        proto::{Error, Handshake, Request, Response, Session},
+        proto::{Handshake, Request, Response, Session},
     },
     areq_body::prelude::*,
     futures_lite::prelude::*,
+    http::HeaderMap,
     std::{
         io,
         pin::Pin,
@@ -105,6 +107,13 @@ where
             Self::Rhs { r } => r.try_clone().map(Self::rhs),
         }
     }
+
+    fn is_reusable(&self) -> bool {
+        match self {
+            Self::Lhs { l } => l.is_reusable(),
+            Self::Rhs { r } => r.is_reusable(),
+        }
+    }
 }
 
 impl<L, R> Future for Alt<L, R>
@@ -136,6 +145,13 @@ where
         }
     }
 
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        match self {
+            Self::Lhs { l } => l.trailers().await,
+            Self::Rhs { r } => r.trailers().await,
+        }
+    }
+
     fn size_hint(&self) -> Hint {
         match self {
             Self::Lhs { l } => l.size_hint(),
@@ -2,7 +2,15 @@
 
 pub use areq_body::*;
 
-use {crate::Request, bytes::Buf};
+use {crate::Request, bytes::Buf, std::io::Error};
+
+#[cfg(feature = "serde")]
+use {
+    crate::ErrorKind,
+    http::{HeaderValue, header},
+    serde::{Serialize, de::DeserializeOwned},
+    std::{io, marker::PhantomData},
+};
 
 pub trait IntoRequestBody {
     type Chunk: Buf;
@@ -24,3 +32,185 @@ where
         self.into_body()
     }
 }
+
+/// Reads a small body to the end, so the connection can be reused.
+/// Larger bodies are dropped.
+pub(crate) async fn drain<B>(mut body: B) -> Result<(), Error>
+where
+    B: Body,
+{
+    const MAX_DRAIN_LEN: usize = 64 * 1024;
+
+    let mut len = 0;
+    while len < MAX_DRAIN_LEN {
+        match body.chunk().await {
+            Some(chunk) => len += chunk?.remaining(),
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// The `application/x-www-form-urlencoded` request body.
+///
+/// # Example
+///
+/// ```
+/// use areq::{Error, body::Form, prelude::*};
+///
+/// async fn token<C>(client: &mut C) -> Result<String, Error>
+/// where
+///     C: Client<String>,
+/// {
+///     let form = Form::new(&[("grant_type", "client_credentials")])?;
+///     let uri = "/token".parse().expect("valid uri");
+///     let res = client.post(uri, form).await?;
+///     Ok(res.text().await?)
+/// }
+/// ```
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub struct Form<T>
+where
+    T: ?Sized,
+{
+    buffer: String,
+    ty: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> Form<T>
+where
+    T: ?Sized,
+{
+    /// Serializes the value into the form body.
+    ///
+    /// # Errors
+    /// Returns the [request](ErrorKind::Request) error
+    /// if the value can't be serialized.
+    pub fn new(t: &T) -> Result<Self, crate::Error>
+    where
+        T: Serialize,
+    {
+        let buffer =
+            serde_urlencoded::to_string(t).map_err(|e| crate::Error::new(ErrorKind::Request, e))?;
+
+        Ok(Self {
+            buffer,
+            ty: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> IntoRequestBody for Form<T>
+where
+    T: ?Sized,
+{
+    type Chunk = <String as Body>::Chunk;
+    type Body = String;
+
+    fn into_req_body(self) -> Self::Body {
+        self.buffer
+    }
+
+    fn upd_req(req: &mut Request<Self::Body>) {
+        set_form_content_type(req);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'str, T> IntoRequestBody for &'str Form<T>
+where
+    T: ?Sized,
+{
+    type Chunk = <&'str str as Body>::Chunk;
+    type Body = &'str str;
+
+    fn into_req_body(self) -> Self::Body {
+        &self.buffer
+    }
+
+    fn upd_req(req: &mut Request<Self::Body>) {
+        set_form_content_type(req);
+    }
+}
+
+#[cfg(feature = "serde")]
+fn set_form_content_type<B>(req: &mut Request<B>) {
+    req.headers_mut()
+        .entry(header::CONTENT_TYPE)
+        .or_insert(HeaderValue::from_static(
+            "application/x-www-form-urlencoded",
+        ));
+}
+
+/// Extension methods for a [body](Body) for working with forms.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub trait FormBodyExt: IntoBody {
+    /// Reads the body and deserializes it
+    /// from the `application/x-www-form-urlencoded` format.
+    async fn form<T>(self) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let v = self.vec().await?;
+        serde_urlencoded::from_bytes(&v).map_err(|e| Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<B> FormBodyExt for B where B: IntoBody {}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use {
+        super::*,
+        futures_lite::future,
+        http::{Method, Uri},
+        serde::Deserialize,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Token {
+        grant_type: String,
+        scope: Option<String>,
+    }
+
+    #[test]
+    fn form() -> Result<(), Error> {
+        let token = Token {
+            grant_type: String::from("client_credentials"),
+            scope: Some(String::from("read write")),
+        };
+
+        let form = Form::new(&token)?;
+        let mut req = Request::new(Method::POST, Uri::from_static("/"), (&form).into_req_body());
+        <&Form<Token>>::upd_req(&mut req);
+        assert_eq!(
+            req.headers()[header::CONTENT_TYPE],
+            "application/x-www-form-urlencoded",
+        );
+
+        assert_eq!(
+            *req.body(),
+            "grant_type=client_credentials&scope=read+write"
+        );
+
+        let decoded: Token = future::block_on(req.body().form())?;
+        assert_eq!(decoded, token);
+        Ok(())
+    }
+
+    #[test]
+    fn form_error() {
+        // nested values can't be encoded
+        let Err(e) = Form::new(&[("ids", [1, 2])]) else {
+            panic!("the value can't be serialized");
+        };
+
+        assert_eq!(e.kind(), ErrorKind::Request);
+    }
+}
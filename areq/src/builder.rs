@@ -0,0 +1,290 @@
+use {
+    crate::{
+        auth,
+        body::IntoRequestBody,
+        client::Client,
+        error::{Error, ErrorKind},
+        proto::{Request, Response},
+    },
+    http::{HeaderMap, HeaderName, HeaderValue, Method, Uri, header},
+    std::{fmt, marker::PhantomData},
+};
+
+/// The request builder.
+///
+/// Created by the [`request`](crate::ClientExt::request) method.
+/// Errors of building the request are deferred until it's
+/// [sent](RequestBuilder::send).
+///
+/// # Example
+///
+/// ```
+/// use areq::{Client, ClientExt, Error, http::Method};
+///
+/// async fn create<C>(client: &mut C) -> Result<(), Error>
+/// where
+///     C: Client<String>,
+/// {
+///     let uri = "/items".parse().expect("valid uri");
+///     client
+///         .request(Method::POST, uri)
+///         .header("x-request-id", "1")
+///         .bearer_auth("token")
+///         .body(String::from("item"))
+///         .send()
+///         .await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct RequestBuilder<'client, C, B, I = ()>
+where
+    C: ?Sized,
+{
+    client: &'client mut C,
+    req: Result<Request<I>, Error>,
+    body: PhantomData<fn() -> B>,
+}
+
+impl<'client, C, B> RequestBuilder<'client, C, B>
+where
+    C: ?Sized,
+{
+    pub(crate) fn new(client: &'client mut C, method: Method, uri: Uri) -> Self {
+        Self {
+            client,
+            req: Ok(Request::new(method, uri, ())),
+            body: PhantomData,
+        }
+    }
+}
+
+impl<'client, C, B, I> RequestBuilder<'client, C, B, I>
+where
+    C: ?Sized,
+{
+    fn and_then<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut Request<I>) -> Result<(), Error>,
+    {
+        if let Ok(req) = &mut self.req {
+            if let Err(e) = f(req) {
+                self.req = Err(e);
+            }
+        }
+
+        self
+    }
+
+    /// Appends the header.
+    pub fn header<K, V>(self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName, Error: Into<http::Error>>,
+        V: TryInto<HeaderValue, Error: Into<http::Error>>,
+    {
+        self.and_then(|req| {
+            let key = key.try_into().map_err(invalid)?;
+            let value = value.try_into().map_err(invalid)?;
+            req.headers_mut().append(key, value);
+            Ok(())
+        })
+    }
+
+    /// Extends the headers, replacing existing values of the same names.
+    pub fn headers(self, headers: HeaderMap) -> Self {
+        self.and_then(|req| {
+            req.headers_mut().extend(headers);
+            Ok(())
+        })
+    }
+
+    /// Appends serialized parameters to the query of the uri.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn query<T>(self, query: &T) -> Self
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        self.and_then(|req| {
+            let query = serde_urlencoded::to_string(query)
+                .map_err(|e| Error::new(ErrorKind::Request, e))?;
+            if query.is_empty() {
+                return Ok(());
+            }
+
+            let mut parts = http::uri::Parts::from(req.uri().clone());
+            let path = parts.path_and_query.as_ref().map_or("/", |p| p.path());
+            let path_and_query = match parts.path_and_query.as_ref().and_then(|p| p.query()) {
+                Some(prev) => format!("{path}?{prev}&{query}"),
+                None => format!("{path}?{query}"),
+            };
+
+            parts.path_and_query =
+                Some(http::uri::PathAndQuery::try_from(path_and_query).map_err(invalid)?);
+            *req.uri_mut() = Uri::from_parts(parts).map_err(invalid)?;
+            Ok(())
+        })
+    }
+
+    /// Sets the `Authorization` header with the bearer token.
+    pub fn bearer_auth<T>(self, token: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        self.and_then(|req| {
+            let mut value = HeaderValue::try_from(format!("Bearer {token}")).map_err(invalid)?;
+            value.set_sensitive(true);
+            req.headers_mut().insert(header::AUTHORIZATION, value);
+            Ok(())
+        })
+    }
+
+    /// Sets the `Authorization` header with the basic scheme.
+    pub fn basic_auth(self, user: &str, pass: &str) -> Self {
+        self.and_then(|req| {
+            let value = auth::basic_auth(user, pass);
+            req.headers_mut().insert(header::AUTHORIZATION, value);
+            Ok(())
+        })
+    }
+
+    /// Inserts the extension.
+    pub fn extension<T>(self, ext: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.and_then(|req| {
+            req.extensions_mut().insert(ext);
+            Ok(())
+        })
+    }
+
+    /// Sets the body.
+    ///
+    /// Like other request constructors, the body type
+    /// [updates](IntoRequestBody::upd_req) the request when it's sent,
+    /// for example to set the `Content-Type` header.
+    pub fn body<J>(self, body: J) -> RequestBuilder<'client, C, B, J> {
+        self.try_body(Ok::<_, Error>(body))
+    }
+
+    /// Sets the body or defers the error of its creation.
+    pub fn try_body<J, E>(self, body: Result<J, E>) -> RequestBuilder<'client, C, B, J>
+    where
+        E: Into<Error>,
+    {
+        let req = match (self.req, body) {
+            (Ok(req), Ok(body)) => Ok(req.map_body(|_| body)),
+            (Err(e), _) => Err(e),
+            (_, Err(e)) => Err(e.into()),
+        };
+
+        RequestBuilder {
+            client: self.client,
+            req,
+            body: PhantomData,
+        }
+    }
+
+    /// Builds and sends the request.
+    ///
+    /// # Errors
+    /// Returns the first error occurred while building the request
+    /// or the error of sending it.
+    pub async fn send(self) -> Result<Response<C::Body>, Error>
+    where
+        C: Client<B>,
+        I: IntoRequestBody<Body = B>,
+    {
+        let mut req = self.req?.map_body(I::into_req_body);
+        I::upd_req(&mut req);
+        self.client.send(req).await
+    }
+}
+
+fn invalid<E>(e: E) -> Error
+where
+    E: Into<http::Error>,
+{
+    Error::new(ErrorKind::Request, e.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::client::ClientExt, bytes::Bytes, futures_lite::future};
+
+    #[derive(Default)]
+    struct Record(Vec<Request<String>>);
+
+    impl Client<String> for Record {
+        type Body = Bytes;
+
+        async fn send(&mut self, req: Request<String>) -> Result<Response<Self::Body>, Error> {
+            self.0.push(req);
+            Ok(Response::new(http::Response::new(Bytes::new())))
+        }
+    }
+
+    #[test]
+    fn build() -> Result<(), Error> {
+        let mut client = Record::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/plain"));
+
+        let req = client
+            .request(Method::PUT, Uri::from_static("http://example.com/items"))
+            .header("x-id", "1")
+            .header("x-id", "2")
+            .headers(headers)
+            .basic_auth("user", "pass")
+            .extension(7_u32)
+            .body(String::from("item"))
+            .send();
+
+        future::block_on(req)?;
+
+        let [req] = &client.0[..] else {
+            panic!("one request should be sent");
+        };
+
+        assert_eq!(req.method(), Method::PUT);
+        assert_eq!(req.uri(), "http://example.com/items");
+        assert_eq!(req.headers().get_all("x-id").iter().count(), 2);
+        assert_eq!(req.headers()[header::ACCEPT], "text/plain");
+        assert_eq!(req.headers()[header::AUTHORIZATION], "Basic dXNlcjpwYXNz");
+        assert_eq!(req.extensions().get(), Some(&7_u32));
+        assert_eq!(req.body(), "item");
+        Ok(())
+    }
+
+    #[test]
+    fn deferred_error() {
+        let mut client = Record::default();
+        let req = client
+            .request(Method::GET, Uri::from_static("/"))
+            .header("invalid header", "value")
+            .bearer_auth("token")
+            .body(String::new())
+            .send();
+
+        let e = future::block_on(req).expect_err("the header name is invalid");
+        assert_eq!(e.kind(), ErrorKind::Request);
+        assert!(client.0.is_empty(), "the request shouldn't be sent");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn query() -> Result<(), Error> {
+        let mut client = Record::default();
+        let req = client
+            .request(Method::GET, Uri::from_static("/search?page=2"))
+            .query(&[("q", "a b"), ("lang", "en")])
+            .query(&[("sort", "new")])
+            .body(String::new())
+            .send();
+
+        future::block_on(req)?;
+        assert_eq!(client.0[0].uri(), "/search?page=2&q=a+b&lang=en&sort=new");
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
 use {
     crate::{
         body::{Body, IntoRequestBody},
-        proto::{Error, Request, Response},
+        builder::RequestBuilder,
+        error::Error,
+        layer::Layer,
+        proto::{Request, Response},
     },
     bytes::Bytes,
-    http::Uri,
+    http::{Method, Uri},
 };
 
 pub trait Client<B> {
@@ -18,9 +21,30 @@ pub trait Client<B> {
     {
         None
     }
+
+    /// Checks if the client can send more requests over its connection.
+    ///
+    /// A connection pool uses it to decide whether the connection can be
+    /// reused. By default, it's always `true`.
+    fn is_reusable(&self) -> bool {
+        true
+    }
 }
 
 pub trait ClientExt<B>: Client<B> {
+    fn with<L>(self, layer: L) -> L::Client
+    where
+        Self: Sized,
+        L: Layer<Self>,
+    {
+        layer.layer(self)
+    }
+
+    /// Starts building the request with the method and uri.
+    fn request(&mut self, method: Method, uri: Uri) -> RequestBuilder<'_, Self, B> {
+        RequestBuilder::new(self, method, uri)
+    }
+
     async fn get<I>(&mut self, uri: Uri, body: I) -> Result<Response<Self::Body>, Error>
     where
         I: IntoRequestBody<Body = B>,
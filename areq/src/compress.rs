@@ -0,0 +1,382 @@
+//! The request body compression.
+
+use {
+    crate::{
+        body::{Body, Hint, IntoRequestBody},
+        decompress::Coding,
+        proto::Request,
+    },
+    bytes::{Buf, Bytes},
+    http::{HeaderMap, HeaderValue, header},
+    std::{
+        io::{self, Write},
+        mem,
+    },
+};
+
+/// The request body compressed on the fly.
+///
+/// Wraps any [request body](IntoRequestBody) and sets the
+/// `Content-Encoding` header for the request. Since the size of
+/// compressed data is unknown in advance, the body is always sent
+/// [chunked](Hint::Chunked). An empty body is sent as is.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "gzip")] {
+/// use areq::{Request, compress::Compress, decompress::Coding, http::Uri};
+///
+/// let body = Compress::new("Hello, World!", Coding::Gzip);
+/// let req = Request::post(Uri::from_static("/"), body);
+/// assert_eq!(req.headers()["content-encoding"], "gzip");
+/// # }
+/// ```
+pub struct Compress<I> {
+    body: I,
+    coding: Coding,
+}
+
+impl<I> Compress<I> {
+    /// Creates the compressed body with the given coding.
+    #[inline]
+    pub fn new(body: I, coding: Coding) -> Self {
+        Self { body, coding }
+    }
+}
+
+impl<I> IntoRequestBody for Compress<I>
+where
+    I: IntoRequestBody,
+{
+    type Chunk = Bytes;
+    type Body = Encoded<I::Body>;
+
+    fn into_req_body(self) -> Self::Body {
+        Encoded::new(self.body.into_req_body(), self.coding)
+    }
+
+    fn upd_req(req: &mut Request<Self::Body>) {
+        // let the inner body update the request as if it was sent as is
+        if let Some(body) = req.body_mut().body.take() {
+            let mut inner = Request::new(req.method().clone(), req.uri().clone(), body);
+            mem::swap(inner.headers_mut(), req.headers_mut());
+            mem::swap(inner.extensions_mut(), req.extensions_mut());
+            I::upd_req(&mut inner);
+
+            let (mut parts, body) = http::Request::from(inner).into_parts();
+            mem::swap(&mut parts.headers, req.headers_mut());
+            mem::swap(&mut parts.extensions, req.extensions_mut());
+            req.body_mut().body = Some(body);
+        }
+
+        let body = req.body();
+        let coding = body.coding;
+        let encoded = !body.size_hint().is_empty();
+        let headers = req.headers_mut();
+        if encoded {
+            headers.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(coding.name()),
+            );
+
+            headers.remove(header::CONTENT_LENGTH);
+        }
+    }
+}
+
+/// The body compressed while reading.
+pub struct Encoded<B> {
+    // taken out only while the inner body updates the request
+    body: Option<B>,
+    coding: Coding,
+    encoder: Option<Encoder>,
+    error: Option<io::Error>,
+    end: bool,
+}
+
+impl<B> Encoded<B>
+where
+    B: Body,
+{
+    /// Wraps the body with an encoder of the given coding.
+    ///
+    /// If the encoder can't be created, the error
+    /// is returned when the body is read.
+    pub fn new(body: B, coding: Coding) -> Self {
+        let (encoder, error) = if body.size_hint().is_empty() {
+            (None, None)
+        } else {
+            match Encoder::new(coding) {
+                Ok(encoder) => (Some(encoder), None),
+                Err(e) => (None, Some(e)),
+            }
+        };
+
+        Self {
+            body: Some(body),
+            coding,
+            encoder,
+            error,
+            end: false,
+        }
+    }
+}
+
+impl<B> Body for Encoded<B>
+where
+    B: Body,
+{
+    type Chunk = Bytes;
+
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, io::Error>> {
+        if let Some(e) = self.error.take() {
+            self.end = true;
+            return Some(Err(e));
+        }
+
+        let body = self.body.as_mut()?;
+        let encoder = self.encoder.as_mut()?;
+        while !self.end {
+            match body.chunk().await {
+                Some(Ok(mut chunk)) => {
+                    while chunk.has_remaining() {
+                        let part = chunk.chunk();
+                        let n = part.len();
+                        if let Err(e) = encoder.write(part) {
+                            return Some(Err(e));
+                        }
+
+                        chunk.advance(n);
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    self.end = true;
+                    if let Err(e) = encoder.finish() {
+                        return Some(Err(e));
+                    }
+                }
+            }
+
+            let out = encoder.take();
+            if !out.is_empty() {
+                return Some(Ok(Bytes::from(out)));
+            }
+        }
+
+        None
+    }
+
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        self.body.as_mut()?.trailers().await
+    }
+
+    fn size_hint(&self) -> Hint {
+        if self.encoder.is_none() && self.error.is_none() {
+            return Hint::Empty;
+        }
+
+        Hint::Chunked { end: self.end }
+    }
+}
+
+enum Encoder {
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::write::ZlibEncoder<Vec<u8>>),
+    #[cfg(feature = "brotli")]
+    Brotli {
+        writer: Option<Box<brotli::CompressorWriter<Vec<u8>>>>,
+        out: Vec<u8>,
+    },
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(coding: Coding) -> io::Result<Self> {
+        let encoder = match coding {
+            #[cfg(feature = "gzip")]
+            Coding::Gzip => Self::Gzip(flate2::write::GzEncoder::new(
+                vec![],
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "deflate")]
+            Coding::Deflate => Self::Deflate(flate2::write::ZlibEncoder::new(
+                vec![],
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "brotli")]
+            Coding::Brotli => {
+                const BUFFER_SIZE: usize = 4096;
+                const QUALITY: u32 = 5;
+                const WINDOW: u32 = 22;
+
+                let writer = brotli::CompressorWriter::new(vec![], BUFFER_SIZE, QUALITY, WINDOW);
+                Self::Brotli {
+                    writer: Some(Box::new(writer)),
+                    out: vec![],
+                }
+            }
+            #[cfg(feature = "zstd")]
+            Coding::Zstd => Self::Zstd(zstd::stream::write::Encoder::new(
+                vec![],
+                zstd::DEFAULT_COMPRESSION_LEVEL,
+            )?),
+        };
+
+        Ok(encoder)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(e) => e.write_all(buf),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(e) => e.write_all(buf),
+            #[cfg(feature = "brotli")]
+            Self::Brotli { writer, .. } => match writer {
+                Some(e) => e.write_all(buf),
+                None => Err(io::Error::other("write to a finished encoder")),
+            },
+            #[cfg(feature = "zstd")]
+            Self::Zstd(e) => e.write_all(buf),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(e) => e.try_finish(),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(e) => e.try_finish(),
+            #[cfg(feature = "brotli")]
+            Self::Brotli { writer, out } => {
+                if let Some(e) = writer.take() {
+                    out.extend(e.into_inner());
+                }
+
+                Ok(())
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd(e) => e.do_finish(),
+        }
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        let out = match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(e) => e.get_mut(),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(e) => e.get_mut(),
+            #[cfg(feature = "brotli")]
+            Self::Brotli { writer, out } => match writer {
+                Some(e) => e.get_mut(),
+                None => out,
+            },
+            #[cfg(feature = "zstd")]
+            Self::Zstd(e) => e.get_mut(),
+        };
+
+        mem::take(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{body::BodyExt, decompress::Decoded},
+        futures_lite::future,
+        http::Uri,
+    };
+
+    /// The body that sets its own content type like `Json` does.
+    struct Text(&'static str);
+
+    impl IntoRequestBody for Text {
+        type Chunk = &'static [u8];
+        type Body = &'static str;
+
+        fn into_req_body(self) -> Self::Body {
+            self.0
+        }
+
+        fn upd_req(req: &mut Request<Self::Body>) {
+            req.headers_mut()
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        }
+    }
+
+    const TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. \
+        Lorem ipsum dolor sit amet, consectetur adipiscing elit.";
+
+    #[test]
+    fn compress() -> Result<(), io::Error> {
+        for &coding in Coding::ALL {
+            let req = Request::post(Uri::from_static("/"), Compress::new(Text(TEXT), coding));
+            assert_eq!(req.headers()[header::CONTENT_ENCODING], coding.name());
+            assert_eq!(req.headers()[header::CONTENT_TYPE], "text/plain");
+
+            let body = http::Request::from(req).into_body();
+            assert!(body.size_hint().is_chunked(), "{coding:?}");
+
+            let compressed = future::block_on(body.vec())?;
+            assert_ne!(compressed, TEXT.as_bytes());
+
+            let decoded = Decoded::new(Bytes::from(compressed), Some(coding)).expect("decoder");
+            let text = future::block_on(decoded.text())?;
+            assert_eq!(text, TEXT);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_request() {
+        /// The body that sets a header by the request it's sent with.
+        struct Path;
+
+        impl IntoRequestBody for Path {
+            type Chunk = &'static [u8];
+            type Body = &'static str;
+
+            fn into_req_body(self) -> Self::Body {
+                TEXT
+            }
+
+            fn upd_req(req: &mut Request<Self::Body>) {
+                let path = HeaderValue::from_str(req.uri().path()).expect("valid path");
+                req.headers_mut().insert("x-path", path);
+            }
+        }
+
+        for &coding in Coding::ALL {
+            let body = Compress::new(Path, coding).into_req_body();
+            let mut req = Request::new(http::Method::PUT, Uri::from_static("/path"), body);
+            req.headers_mut()
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+
+            Compress::<Path>::upd_req(&mut req);
+            assert_eq!(req.headers()["x-path"], "/path");
+            assert_eq!(req.headers()[header::CONTENT_TYPE], "text/plain");
+            assert_eq!(req.headers()[header::CONTENT_ENCODING], coding.name());
+        }
+    }
+
+    #[test]
+    fn empty() -> Result<(), io::Error> {
+        for &coding in Coding::ALL {
+            let req = Request::post(Uri::from_static("/"), Compress::new((), coding));
+            assert!(!req.headers().contains_key(header::CONTENT_ENCODING));
+
+            let body = http::Request::from(req).into_body();
+            assert!(body.size_hint().is_empty(), "{coding:?}");
+            assert!(future::block_on(body.vec())?.is_empty(), "{coding:?}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,806 @@
+//! The cookie storage.
+
+use {
+    crate::{
+        addr::Address,
+        client::Client,
+        error::Error,
+        layer::Layer,
+        proto::{Request, Response},
+    },
+    http::{HeaderMap, HeaderValue, Uri, header, uri::Authority},
+    std::{
+        fmt,
+        net::IpAddr,
+        sync::{Arc, Mutex, MutexGuard},
+        time::{Duration, SystemTime},
+    },
+};
+
+/// The cookie stored in a [jar](CookieJar).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    expires: Option<SystemTime>,
+    created: SystemTime,
+}
+
+impl Cookie {
+    /// Returns the cookie name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the cookie value.
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the cookie domain.
+    #[inline]
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Checks if the cookie is sent only to the exact domain.
+    #[inline]
+    pub fn is_host_only(&self) -> bool {
+        self.host_only
+    }
+
+    /// Returns the cookie path.
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Checks if the cookie is sent only over secure connections.
+    #[inline]
+    pub fn is_secure(&self) -> bool {
+        self.secure
+    }
+
+    /// Checks if the cookie has the `HttpOnly` attribute.
+    #[inline]
+    pub fn is_http_only(&self) -> bool {
+        self.http_only
+    }
+
+    /// Returns the expiry time or `None` for a session cookie.
+    #[inline]
+    pub fn expires(&self) -> Option<SystemTime> {
+        self.expires
+    }
+
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url<'_>, now: SystemTime) -> bool {
+        let domain_match = if self.host_only {
+            url.host == self.domain
+        } else {
+            domain_match(&url.host, &self.domain)
+        };
+
+        domain_match
+            && path_match(url.path, &self.path)
+            && (!self.secure || url.secure)
+            && !self.is_expired(now)
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// The trait to check public suffixes like `com` or `co.uk`.
+///
+/// Cookies with a public suffix domain are rejected, so a site
+/// can't set a cookie for every other site under the suffix.
+/// Without a list, only single-label domains are treated as
+/// public suffixes.
+pub trait PublicSuffix {
+    /// Checks if the domain is a public suffix.
+    fn is_public_suffix(&self, domain: &str) -> bool;
+}
+
+#[cfg(feature = "publicsuffix")]
+#[cfg_attr(docsrs, doc(cfg(feature = "publicsuffix")))]
+impl PublicSuffix for publicsuffix::List {
+    fn is_public_suffix(&self, domain: &str) -> bool {
+        use publicsuffix::Psl;
+
+        self.suffix(domain.as_bytes())
+            .is_some_and(|suffix| suffix.is_known() && suffix.as_bytes() == domain.as_bytes())
+    }
+}
+
+/// The cookie storage.
+///
+/// The jar follows [RFC 6265] to store cookies from `Set-Cookie`
+/// headers and to select cookies for a request by its domain,
+/// path, scheme and cookie expiry time.
+///
+/// With the `serde` feature the jar can be serialized as a list of
+/// persistent cookies. Session cookies are skipped.
+///
+/// [RFC 6265]: https://www.rfc-editor.org/rfc/rfc6265
+#[derive(Default)]
+pub struct CookieJar {
+    cookies: Mutex<Vec<Cookie>>,
+    suffixes: Option<Box<dyn PublicSuffix + Send + Sync>>,
+}
+
+impl CookieJar {
+    /// Creates an empty jar.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the list of [public suffixes](PublicSuffix).
+    #[inline]
+    pub fn public_suffix<P>(mut self, suffixes: P) -> Self
+    where
+        P: PublicSuffix + Send + Sync + 'static,
+    {
+        self.suffixes = Some(Box::new(suffixes));
+        self
+    }
+
+    /// Stores cookies from all `Set-Cookie` headers of a response
+    /// to a request with the given uri.
+    pub fn store(&self, uri: &Uri, headers: &HeaderMap) {
+        if let Some(url) = Url::new(uri, None, None) {
+            self.store_url(&url, headers);
+        }
+    }
+
+    /// Stores a cookie from the `Set-Cookie` header value.
+    pub fn store_str(&self, uri: &Uri, set_cookie: &str) {
+        if let Some(url) = Url::new(uri, None, None) {
+            self.insert(&mut self.lock(), &url, set_cookie, SystemTime::now());
+        }
+    }
+
+    /// Returns the `Cookie` header value for a request to the uri.
+    pub fn header(&self, uri: &Uri) -> Option<HeaderValue> {
+        let url = Url::new(uri, None, None)?;
+        self.header_value(&url, SystemTime::now())
+    }
+
+    /// Returns a copy of all stored unexpired cookies.
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let now = SystemTime::now();
+        let mut cookies = self.lock();
+        cookies.retain(|cookie| !cookie.is_expired(now));
+        cookies.clone()
+    }
+
+    /// Removes all cookies.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Removes all session cookies.
+    pub fn clear_session(&self) {
+        self.lock().retain(|cookie| cookie.expires.is_some());
+    }
+
+    fn store_url(&self, url: &Url<'_>, headers: &HeaderMap) {
+        let now = SystemTime::now();
+        let mut cookies = self.lock();
+        for value in headers.get_all(header::SET_COOKIE) {
+            if let Ok(value) = value.to_str() {
+                self.insert(&mut cookies, url, value, now);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Cookie>> {
+        self.cookies.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_public_suffix(&self, domain: &str) -> bool {
+        match &self.suffixes {
+            Some(suffixes) => suffixes.is_public_suffix(domain),
+            None => !domain.contains('.'),
+        }
+    }
+
+    fn header_value(&self, url: &Url<'_>, now: SystemTime) -> Option<HeaderValue> {
+        let mut cookies = self.lock();
+        cookies.retain(|cookie| !cookie.is_expired(now));
+
+        let mut matched: Vec<_> = cookies
+            .iter()
+            .filter(|cookie| cookie.matches(url, now))
+            .collect();
+
+        if matched.is_empty() {
+            return None;
+        }
+
+        // longer paths first, then earlier created
+        matched.sort_by(|a, b| {
+            b.path
+                .len()
+                .cmp(&a.path.len())
+                .then(a.created.cmp(&b.created))
+        });
+
+        let value = matched
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        HeaderValue::from_str(&value).ok()
+    }
+
+    fn insert(&self, cookies: &mut Vec<Cookie>, url: &Url<'_>, set_cookie: &str, now: SystemTime) {
+        let Some(mut cookie) = self.parse(url, set_cookie, now) else {
+            return;
+        };
+
+        if let Some(old) = cookies.iter().position(|old| old.same(&cookie)) {
+            cookie.created = cookies[old].created;
+            cookies.remove(old);
+        }
+
+        if !cookie.is_expired(now) {
+            cookies.push(cookie);
+        }
+    }
+
+    /// Parses the cookie according to the RFC 6265 storage model.
+    fn parse(&self, url: &Url<'_>, set_cookie: &str, now: SystemTime) -> Option<Cookie> {
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let (name, value) = (name.trim(), value.trim());
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut expires = None;
+        let mut max_age = None;
+        let mut domain = None;
+        let mut path = None;
+        let mut secure = false;
+        let mut http_only = false;
+        for attr in parts {
+            let (key, val) = attr.split_once('=').unwrap_or((attr, ""));
+            let (key, val) = (key.trim(), val.trim());
+            if key.eq_ignore_ascii_case("expires") {
+                expires = httpdate::parse_http_date(val).ok().or(expires);
+            } else if key.eq_ignore_ascii_case("max-age") {
+                max_age = parse_max_age(val, now).or(max_age);
+            } else if key.eq_ignore_ascii_case("domain") {
+                let val = val.strip_prefix('.').unwrap_or(val);
+                if !val.is_empty() {
+                    domain = Some(val.to_ascii_lowercase());
+                }
+            } else if key.eq_ignore_ascii_case("path") {
+                path = val.starts_with('/').then(|| val.to_owned());
+            } else if key.eq_ignore_ascii_case("secure") {
+                secure = true;
+            } else if key.eq_ignore_ascii_case("httponly") {
+                http_only = true;
+            }
+        }
+
+        let (domain, host_only) = match domain {
+            Some(domain) if self.is_public_suffix(&domain) => {
+                if domain != url.host {
+                    return None;
+                }
+
+                (domain, true)
+            }
+            Some(domain) => {
+                if !domain_match(&url.host, &domain) {
+                    return None;
+                }
+
+                (domain, false)
+            }
+            None => (url.host.clone(), true),
+        };
+
+        // a secure cookie can't be set over an insecure connection
+        if secure && !url.secure {
+            return None;
+        }
+
+        Some(Cookie {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            domain,
+            host_only,
+            path: path.unwrap_or_else(|| default_path(url.path).to_owned()),
+            secure,
+            http_only,
+            expires: max_age.or(expires),
+            created: now,
+        })
+    }
+}
+
+impl fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieJar")
+            .field("cookies", &*self.lock())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::Serialize for CookieJar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let now = SystemTime::now();
+        let cookies = self.lock();
+        serializer.collect_seq(
+            cookies
+                .iter()
+                .filter(|cookie| cookie.expires.is_some() && !cookie.is_expired(now)),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for CookieJar {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let cookies = Vec::deserialize(deserializer)?;
+        Ok(Self {
+            cookies: Mutex::new(cookies),
+            suffixes: None,
+        })
+    }
+}
+
+/// The request target used to match cookies.
+struct Url<'uri> {
+    host: String,
+    path: &'uri str,
+    secure: bool,
+}
+
+impl<'uri> Url<'uri> {
+    /// Takes the host from the uri or, if it's relative, from
+    /// the base address or the `Host` header.
+    fn new(uri: &'uri Uri, base: Option<&Address>, host: Option<&HeaderValue>) -> Option<Self> {
+        let (host, secure) = match (uri.host(), base) {
+            (Some(host), _) => (host.to_owned(), uri.scheme_str() == Some("https")),
+            (None, Some(base)) => (base.host.to_string(), base.secure),
+            (None, None) => {
+                let host = host?.to_str().ok()?;
+                host.parse::<Authority>().ok()?;
+                let host = host
+                    .rsplit_once(':')
+                    .filter(|(_, port)| port.bytes().all(|b| b.is_ascii_digit()))
+                    .map_or(host, |(host, _)| host);
+
+                (host.to_owned(), false)
+            }
+        };
+
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            host: host.to_ascii_lowercase(),
+            path: uri.path(),
+            secure,
+        })
+    }
+}
+
+fn parse_max_age(val: &str, now: SystemTime) -> Option<SystemTime> {
+    let secs: i64 = val.parse().ok()?;
+    match u64::try_from(secs) {
+        Ok(secs) if secs > 0 => Some(
+            now.checked_add(Duration::from_secs(secs))
+                .unwrap_or(now + Duration::from_secs(u32::MAX.into())),
+        ),
+        _ => Some(SystemTime::UNIX_EPOCH),
+    }
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|rest| rest.ends_with('.'))
+            && host.parse::<IpAddr>().is_err()
+}
+
+fn path_match(path: &str, cookie_path: &str) -> bool {
+    path.strip_prefix(cookie_path)
+        .is_some_and(|rest| cookie_path.ends_with('/') || rest.is_empty() || rest.starts_with('/'))
+}
+
+fn default_path(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(n) => &path[..n],
+    }
+}
+
+/// The layer that sends and stores cookies using a [jar](CookieJar).
+///
+/// Cookies from the jar are added to a request `Cookie` header, and
+/// cookies from a response `Set-Cookie` headers are stored to the jar.
+/// The request domain is taken from its uri or, if the uri is relative,
+/// from the [base address](Cookies::base) of the connection.
+///
+/// Without the base address, the domain of a relative uri is taken from
+/// the `Host` header and the request is considered insecure. Connection
+/// clients like [`H1`](crate::http1::H1) set this header only after the
+/// layer, so set the base address when the layer wraps such a client.
+#[derive(Clone, Debug, Default)]
+pub struct Cookies {
+    jar: Arc<CookieJar>,
+    base: Option<Address>,
+}
+
+impl Cookies {
+    /// Creates the layer with the shared jar.
+    #[inline]
+    pub fn new(jar: Arc<CookieJar>) -> Self {
+        Self { jar, base: None }
+    }
+
+    /// Sets the address of the connection used for requests with
+    /// relative uris. Its scheme decides if the request is secure.
+    #[inline]
+    pub fn base(mut self, addr: Address) -> Self {
+        self.base = Some(addr);
+        self
+    }
+
+    /// Returns the cookie jar.
+    #[inline]
+    pub fn jar(&self) -> &Arc<CookieJar> {
+        &self.jar
+    }
+}
+
+impl<C> Layer<C> for Cookies {
+    type Client = CookiesClient<C>;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        CookiesClient {
+            client,
+            jar: self.jar.clone(),
+            base: self.base.clone(),
+        }
+    }
+}
+
+/// The client created by the [`Cookies`] layer.
+#[derive(Debug)]
+pub struct CookiesClient<C> {
+    client: C,
+    jar: Arc<CookieJar>,
+    base: Option<Address>,
+}
+
+impl<B, C> Client<B> for CookiesClient<C>
+where
+    C: Client<B>,
+{
+    type Body = C::Body;
+
+    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let uri = req.uri().clone();
+        let url = Url::new(&uri, self.base.as_ref(), req.headers().get(header::HOST));
+        let cookie = url
+            .as_ref()
+            .filter(|_| !req.headers().contains_key(header::COOKIE))
+            .and_then(|url| self.jar.header_value(url, SystemTime::now()));
+
+        if let Some(cookie) = cookie {
+            req.headers_mut().insert(header::COOKIE, cookie);
+        }
+
+        let res = self.client.send(req).await?;
+        if let Some(url) = &url {
+            self.jar.store_url(url, res.headers());
+        }
+
+        Ok(res)
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            client: self.client.try_clone()?,
+            jar: self.jar.clone(),
+            base: self.base.clone(),
+        })
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::ClientExt, bytes::Bytes, futures_lite::future};
+
+    fn header(jar: &CookieJar, uri: &'static str) -> Option<String> {
+        let value = jar.header(&Uri::from_static(uri))?;
+        Some(value.to_str().expect("ascii value").to_owned())
+    }
+
+    #[test]
+    fn store() {
+        let jar = CookieJar::new();
+        let uri = Uri::from_static("http://example.com/a/b");
+        jar.store_str(&uri, "id=1");
+        jar.store_str(&uri, "lang=en; Path=/");
+        jar.store_str(&uri, "invalid");
+        jar.store_str(&uri, "=empty");
+
+        assert_eq!(
+            header(&jar, "http://example.com/a/c").as_deref(),
+            Some("id=1; lang=en")
+        );
+        assert_eq!(
+            header(&jar, "http://example.com/").as_deref(),
+            Some("lang=en")
+        );
+        assert_eq!(header(&jar, "http://sub.example.com/a"), None);
+        assert_eq!(header(&jar, "http://other.com/"), None);
+    }
+
+    #[test]
+    fn replace() {
+        let jar = CookieJar::new();
+        let uri = Uri::from_static("http://example.com/");
+        jar.store_str(&uri, "id=1");
+        jar.store_str(&uri, "id=2");
+        assert_eq!(header(&jar, "http://example.com/").as_deref(), Some("id=2"));
+
+        jar.store_str(&uri, "id=3; Max-Age=0");
+        assert_eq!(header(&jar, "http://example.com/"), None);
+        assert!(jar.cookies().is_empty(), "the cookie is removed");
+    }
+
+    #[test]
+    fn domain() {
+        let jar = CookieJar::new();
+        let uri = Uri::from_static("http://www.example.com/");
+        jar.store_str(&uri, "a=1; Domain=.Example.com");
+        jar.store_str(&uri, "b=2; Domain=other.com");
+        jar.store_str(&uri, "c=3; Domain=sub.www.example.com");
+
+        assert_eq!(header(&jar, "http://example.com/").as_deref(), Some("a=1"));
+        assert_eq!(
+            header(&jar, "http://x.example.com/").as_deref(),
+            Some("a=1")
+        );
+        assert_eq!(header(&jar, "http://badexample.com/"), None);
+        assert_eq!(jar.cookies().len(), 1);
+    }
+
+    #[test]
+    fn public_suffix() {
+        struct List;
+
+        impl PublicSuffix for List {
+            fn is_public_suffix(&self, domain: &str) -> bool {
+                matches!(domain, "com" | "co.uk")
+            }
+        }
+
+        let jar = CookieJar::new().public_suffix(List);
+        let uri = Uri::from_static("http://example.co.uk/");
+        jar.store_str(&uri, "a=1; Domain=co.uk");
+        assert_eq!(header(&jar, "http://example.co.uk/"), None);
+
+        // the default check rejects single-label domains only
+        let jar = CookieJar::new();
+        jar.store_str(&Uri::from_static("http://example.com/"), "a=1; Domain=com");
+        assert_eq!(header(&jar, "http://example.com/"), None);
+
+        // the host itself may be a public suffix
+        let jar = CookieJar::new();
+        jar.store_str(
+            &Uri::from_static("http://localhost/"),
+            "a=1; Domain=localhost",
+        );
+        assert_eq!(header(&jar, "http://localhost/").as_deref(), Some("a=1"));
+    }
+
+    #[test]
+    fn secure() {
+        let jar = CookieJar::new();
+        jar.store_str(&Uri::from_static("http://example.com/"), "a=1; Secure");
+        assert!(jar.cookies().is_empty(), "insecure origin");
+
+        jar.store_str(&Uri::from_static("https://example.com/"), "a=1; Secure");
+        assert_eq!(header(&jar, "http://example.com/"), None);
+        assert_eq!(header(&jar, "https://example.com/").as_deref(), Some("a=1"));
+    }
+
+    #[test]
+    fn expiry() {
+        let jar = CookieJar::new();
+        let uri = Uri::from_static("http://example.com/");
+        jar.store_str(&uri, "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+        jar.store_str(
+            &uri,
+            "b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=60",
+        );
+        jar.store_str(&uri, "c=3; Max-Age=-1");
+        jar.store_str(&uri, "d=4; Expires=Fri, 01 Jan 2100 00:00:00 GMT");
+
+        assert_eq!(
+            header(&jar, "http://example.com/").as_deref(),
+            Some("b=2; d=4")
+        );
+
+        jar.clear_session();
+        assert_eq!(jar.cookies().len(), 2);
+        jar.clear();
+        assert!(jar.cookies().is_empty(), "the jar is cleared");
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(default_path(""), "/");
+        assert_eq!(default_path("/"), "/");
+        assert_eq!(default_path("/a"), "/");
+        assert_eq!(default_path("/a/b"), "/a");
+
+        assert!(path_match("/a", "/a"), "same path");
+        assert!(path_match("/a/b", "/a"), "sub path");
+        assert!(path_match("/a/b", "/a/"), "sub path with slash");
+        assert!(!path_match("/ab", "/a"), "other path");
+        assert!(!path_match("/", "/a"), "parent path");
+    }
+
+    /// Sets a cookie on the first request and echoes the `Cookie` header.
+    struct Mock;
+
+    impl Client<()> for Mock {
+        type Body = Bytes;
+
+        async fn send(&mut self, req: Request<()>) -> Result<Response<Self::Body>, Error> {
+            let cookie = req
+                .headers()
+                .get(header::COOKIE)
+                .map(|value| Bytes::copy_from_slice(value.as_bytes()))
+                .unwrap_or_default();
+
+            let res = http::Response::builder()
+                .header(header::SET_COOKIE, "session=abc; HttpOnly")
+                .header(header::SET_COOKIE, "theme=dark; Path=/")
+                .body(cookie)
+                .expect("valid response");
+
+            Ok(Response::new(res))
+        }
+    }
+
+    #[test]
+    fn layer() -> Result<(), Error> {
+        let cookies = Cookies::default();
+        let mut client = Mock.with(cookies.clone());
+        future::block_on(async {
+            let res = client.get(Uri::from_static("http://a.com/"), ()).await?;
+            assert!(
+                http::Response::from(res).into_body().is_empty(),
+                "no cookies"
+            );
+
+            let res = client.get(Uri::from_static("http://a.com/x"), ()).await?;
+            let body = http::Response::from(res).into_body();
+            assert_eq!(body, "session=abc; theme=dark");
+
+            // a relative uri is matched by the host header
+            let mut req = Request::get(Uri::from_static("/"), ());
+            req.headers_mut()
+                .insert(header::HOST, HeaderValue::from_static("a.com:80"));
+
+            let res = client.send(req).await?;
+            let body = http::Response::from(res).into_body();
+            assert_eq!(body, "session=abc; theme=dark");
+            Ok::<_, Error>(())
+        })?;
+
+        let stored = cookies.jar().cookies();
+        assert_eq!(stored.len(), 2);
+        assert!(stored[0].is_http_only(), "http only");
+        Ok(())
+    }
+
+    #[cfg(feature = "http1")]
+    #[test]
+    fn http1() -> Result<(), Error> {
+        use crate::{Address, Session, http1::Http1, proto::Handshake, test};
+
+        const RESPONSE: &[u8] = b"\
+            HTTP/1.1 200 OK\r\n\
+            set-cookie: id=1; Secure\r\n\
+            content-length: 0\r\n\
+            \r\n\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 0\r\n\
+            \r\n";
+
+        let addr = Address::https("example.com");
+        let cookies = Cookies::default().base(addr.clone());
+        let mut io = test::Mock::new(RESPONSE);
+        future::block_on(async {
+            let se = Session { addr, io: &mut io };
+            let (client, conn) = Http1::default().handshake(se).await?;
+            let mut client = client.with(cookies.clone());
+            let send = async move {
+                client.get(Uri::from_static("/"), ()).await?;
+                client.get(Uri::from_static("/a"), ()).await?;
+                Ok::<_, Error>(())
+            };
+
+            let (res, ()) = future::zip(send, conn).await;
+            res
+        })?;
+
+        let stored = cookies.jar().cookies();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].domain(), "example.com");
+        assert!(stored[0].is_secure(), "secure cookie over https");
+
+        let output = String::from_utf8(io.output).expect("utf-8");
+        let (first, second) = output
+            .split_once("\r\n\r\n")
+            .expect("two requests are written");
+
+        assert!(!first.contains("cookie:"), "no cookies at first");
+        assert!(
+            second.contains("\r\ncookie: id=1\r\n"),
+            "the cookie is sent"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn persist() {
+        let jar = CookieJar::new();
+        let uri = Uri::from_static("http://example.com/");
+        jar.store_str(&uri, "session=1");
+        jar.store_str(&uri, "persistent=2; Max-Age=3600");
+
+        let json = serde_json::to_string(&jar).expect("serialize jar");
+        let jar: CookieJar = serde_json::from_str(&json).expect("deserialize jar");
+        assert_eq!(
+            header(&jar, "http://example.com/").as_deref(),
+            Some("persistent=2")
+        );
+    }
+}
@@ -0,0 +1,581 @@
+//! The response decompression.
+
+use {
+    crate::{
+        body::{Body, Hint},
+        client::Client,
+        error::Error,
+        layer::Layer,
+        proto::{Request, Response},
+    },
+    bytes::Bytes,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    std::{
+        io::{self, Write},
+        mem,
+    },
+};
+
+/// The content coding supported by the decompression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Coding {
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    Gzip,
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    Deflate,
+    #[cfg(feature = "brotli")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "brotli")))]
+    Brotli,
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    Zstd,
+}
+
+impl Coding {
+    /// All codings enabled by crate features.
+    pub const ALL: &[Self] = &[
+        #[cfg(feature = "gzip")]
+        Self::Gzip,
+        #[cfg(feature = "deflate")]
+        Self::Deflate,
+        #[cfg(feature = "brotli")]
+        Self::Brotli,
+        #[cfg(feature = "zstd")]
+        Self::Zstd,
+    ];
+
+    /// Returns the coding name used in HTTP headers.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip => "gzip",
+            #[cfg(feature = "deflate")]
+            Self::Deflate => "deflate",
+            #[cfg(feature = "brotli")]
+            Self::Brotli => "br",
+            #[cfg(feature = "zstd")]
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Parses the coding from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+
+        #[cfg(feature = "gzip")]
+        if name.eq_ignore_ascii_case("x-gzip") {
+            return Some(Self::Gzip);
+        }
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|coding| name.eq_ignore_ascii_case(coding.name()))
+    }
+
+    /// Returns the response coding if it's the single supported one.
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let mut values = headers.get_all(header::CONTENT_ENCODING).iter();
+        let value = values.next()?.to_str().ok()?;
+        if values.next().is_some() || value.contains(',') {
+            return None;
+        }
+
+        Self::from_name(value)
+    }
+
+    fn accept_encoding() -> HeaderValue {
+        let value = Self::ALL
+            .iter()
+            .map(|coding| coding.name())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        HeaderValue::from_str(&value).expect("coding names should be valid header values")
+    }
+}
+
+/// The layer that decompresses response bodies.
+///
+/// If a request has no `Accept-Encoding` header, the client
+/// advertises all [codings](Coding) enabled by crate features.
+/// When a response is encoded with one of them, its body is
+/// decoded while reading, and the `Content-Encoding` and
+/// `Content-Length` headers are removed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Decompress;
+
+impl<C> Layer<C> for Decompress {
+    type Client = DecompressClient<C>;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        DecompressClient(client)
+    }
+}
+
+/// The client created by the [`Decompress`] layer.
+#[derive(Debug)]
+pub struct DecompressClient<C>(C);
+
+impl<B, C> Client<B> for DecompressClient<C>
+where
+    C: Client<B>,
+{
+    type Body = Decoded<C::Body>;
+
+    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let head = req.method() == Method::HEAD;
+        req.headers_mut()
+            .entry(header::ACCEPT_ENCODING)
+            .or_insert_with(Coding::accept_encoding);
+
+        let mut res = self.0.send(req).await?;
+        let bodiless = head
+            || matches!(
+                res.status(),
+                StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED
+            );
+
+        let coding = if bodiless {
+            None
+        } else {
+            Coding::from_headers(res.headers())
+        };
+
+        if coding.is_some() {
+            res.headers_mut().remove(header::CONTENT_ENCODING);
+            res.headers_mut().remove(header::CONTENT_LENGTH);
+        }
+
+        let decoder = coding.map(Decoder::new).transpose().map_err(Error::body)?;
+        Ok(res.map(|body| Decoded::with_decoder(body, decoder)))
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        self.0.try_clone().map(Self)
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.0.is_reusable()
+    }
+}
+
+/// The response body decoded while reading.
+pub struct Decoded<B> {
+    body: B,
+    decoder: Option<Decoder>,
+    state: State,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Empty,
+    Read,
+    End,
+}
+
+impl<B> Decoded<B> {
+    /// Wraps the body with a decoder of the given coding.
+    ///
+    /// If the coding is `None`, the body is returned as is.
+    ///
+    /// # Errors
+    /// Returns an error if the decoder can't be created.
+    pub fn new(body: B, coding: Option<Coding>) -> Result<Self, io::Error> {
+        let decoder = coding.map(Decoder::new).transpose()?;
+        Ok(Self::with_decoder(body, decoder))
+    }
+
+    fn with_decoder(body: B, decoder: Option<Decoder>) -> Self {
+        Self {
+            body,
+            decoder,
+            state: State::Empty,
+        }
+    }
+
+    /// Returns the inner body.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.body
+    }
+}
+
+impl<B> Body for Decoded<B>
+where
+    B: Body<Chunk = Bytes>,
+{
+    type Chunk = Bytes;
+
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, io::Error>> {
+        let Some(decoder) = &mut self.decoder else {
+            return self.body.chunk().await;
+        };
+
+        while self.state != State::End {
+            match self.body.chunk().await {
+                Some(Ok(chunk)) => {
+                    self.state = State::Read;
+                    if let Err(e) = decoder.write(&chunk) {
+                        return Some(Err(e));
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    // an empty body has nothing to decode
+                    let read = self.state == State::Read;
+                    self.state = State::End;
+                    if read {
+                        if let Err(e) = decoder.finish() {
+                            return Some(Err(e));
+                        }
+                    }
+                }
+            }
+
+            let out = decoder.take();
+            if !out.is_empty() {
+                return Some(Ok(Bytes::from(out)));
+            }
+        }
+
+        None
+    }
+
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        self.body.trailers().await
+    }
+
+    fn size_hint(&self) -> Hint {
+        match self.decoder {
+            Some(_) => Hint::Chunked {
+                end: self.state == State::End,
+            },
+            None => self.body.size_hint(),
+        }
+    }
+}
+
+enum Decoder {
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzDecoder<Vec<u8>>),
+    #[cfg(feature = "deflate")]
+    Deflate(Zlib),
+    #[cfg(feature = "brotli")]
+    Brotli(Box<brotli::DecompressorWriter<Vec<u8>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::zio::Writer<Vec<u8>, zstd::stream::raw::Decoder<'static>>),
+}
+
+impl Decoder {
+    fn new(coding: Coding) -> io::Result<Self> {
+        let decoder = match coding {
+            #[cfg(feature = "gzip")]
+            Coding::Gzip => Self::Gzip(flate2::write::GzDecoder::new(vec![])),
+            #[cfg(feature = "deflate")]
+            Coding::Deflate => Self::Deflate(Zlib::new()),
+            #[cfg(feature = "brotli")]
+            Coding::Brotli => {
+                const BUFFER_SIZE: usize = 4096;
+                Self::Brotli(Box::new(brotli::DecompressorWriter::new(
+                    vec![],
+                    BUFFER_SIZE,
+                )))
+            }
+            #[cfg(feature = "zstd")]
+            Coding::Zstd => Self::Zstd(zstd::stream::zio::Writer::new(
+                vec![],
+                zstd::stream::raw::Decoder::new()?,
+            )),
+        };
+
+        Ok(decoder)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(d) => d.write_all(buf),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(d) => d.write_all(buf),
+            #[cfg(feature = "brotli")]
+            Self::Brotli(d) => d.write_all(buf),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(d) => d.write_all(buf),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(d) => d.try_finish(),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(d) => d.finish(),
+            #[cfg(feature = "brotli")]
+            Self::Brotli(d) => d.close(),
+            // fails with `UnexpectedEof` on an incomplete frame
+            #[cfg(feature = "zstd")]
+            Self::Zstd(d) => d.finish(),
+        }
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        let out = match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip(d) => d.get_mut(),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(d) => &mut d.out,
+            #[cfg(feature = "brotli")]
+            Self::Brotli(d) => d.get_mut(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(d) => d.writer_mut(),
+        };
+
+        mem::take(out)
+    }
+}
+
+/// The zlib decoder that tracks the end of the stream.
+///
+/// Unlike [`ZlibDecoder`](flate2::write::ZlibDecoder),
+/// it detects a truncated stream when finished.
+#[cfg(feature = "deflate")]
+struct Zlib {
+    inflate: flate2::Decompress,
+    out: Vec<u8>,
+    end: bool,
+}
+
+#[cfg(feature = "deflate")]
+impl Zlib {
+    fn new() -> Self {
+        Self {
+            inflate: flate2::Decompress::new(true),
+            out: vec![],
+            end: false,
+        }
+    }
+
+    fn decode(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        const BUFFER_SIZE: usize = 4096;
+
+        while !buf.is_empty() && !self.end {
+            self.out.reserve(BUFFER_SIZE);
+
+            let total_in = self.inflate.total_in();
+            let total_out = self.inflate.total_out();
+            let status = self
+                .inflate
+                .decompress_vec(buf, &mut self.out, flate2::FlushDecompress::None)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            let read = (self.inflate.total_in() - total_in) as usize;
+            let written = self.inflate.total_out() - total_out;
+            buf = &buf[read..];
+
+            match status {
+                flate2::Status::StreamEnd => self.end = true,
+                flate2::Status::Ok if read > 0 || written > 0 => {}
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt stream")),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.end {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "incomplete deflate stream",
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl Write for Zlib {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // the data after the end of the stream is ignored
+        self.decode(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ClientExt, body::BodyExt},
+        areq_body::Chunked,
+        futures_lite::{future, stream},
+        http::Uri,
+    };
+
+    const TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. \
+        Lorem ipsum dolor sit amet, consectetur adipiscing elit.";
+
+    fn encode(coding: Coding) -> Vec<u8> {
+        let text = TEXT.as_bytes();
+        match coding {
+            #[cfg(feature = "gzip")]
+            Coding::Gzip => {
+                let mut e = flate2::write::GzEncoder::new(vec![], flate2::Compression::fast());
+                e.write_all(text).expect("encode");
+                e.finish().expect("finish")
+            }
+            #[cfg(feature = "deflate")]
+            Coding::Deflate => {
+                let mut e = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::fast());
+                e.write_all(text).expect("encode");
+                e.finish().expect("finish")
+            }
+            #[cfg(feature = "brotli")]
+            Coding::Brotli => {
+                let mut e = brotli::CompressorWriter::new(vec![], 4096, 5, 22);
+                e.write_all(text).expect("encode");
+                e.into_inner()
+            }
+            #[cfg(feature = "zstd")]
+            Coding::Zstd => zstd::encode_all(text, 3).expect("encode"),
+        }
+    }
+
+    type Chunks = Chunked<stream::Iter<std::vec::IntoIter<io::Result<Bytes>>>>;
+
+    /// Responds with the body split into small chunks.
+    struct Mock {
+        coding: &'static str,
+        body: Vec<u8>,
+        accept: Option<HeaderValue>,
+    }
+
+    impl Client<()> for Mock {
+        type Body = Chunks;
+
+        async fn send(&mut self, req: Request<()>) -> Result<Response<Self::Body>, Error> {
+            self.accept = req.headers().get(header::ACCEPT_ENCODING).cloned();
+            let chunks: Vec<_> = self
+                .body
+                .chunks(7)
+                .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+                .collect();
+
+            let res = http::Response::builder()
+                .header(header::CONTENT_ENCODING, self.coding)
+                .header(header::CONTENT_LENGTH, self.body.len())
+                .body(Chunked(stream::iter(chunks)))
+                .expect("valid response");
+
+            Ok(Response::new(res))
+        }
+    }
+
+    fn fetch(coding: &'static str, body: Vec<u8>) -> (Response<Vec<u8>>, Option<HeaderValue>) {
+        let mock = Mock {
+            coding,
+            body,
+            accept: None,
+        };
+
+        let mut client = mock.with(Decompress);
+        let res = future::block_on(async {
+            let res = client.get(Uri::from_static("/"), ()).await?;
+            let (parts, body) = http::Response::from(res).into_parts();
+            let body = body.vec().await?;
+            Ok::<_, Error>(Response::new(http::Response::from_parts(parts, body)))
+        })
+        .expect("fetch response");
+
+        (res, client.0.accept)
+    }
+
+    #[test]
+    fn decode() {
+        for &coding in Coding::ALL {
+            let (res, accept) = fetch(coding.name(), encode(coding));
+            assert!(
+                !res.headers().contains_key(header::CONTENT_ENCODING),
+                "{coding:?}"
+            );
+            assert!(
+                !res.headers().contains_key(header::CONTENT_LENGTH),
+                "{coding:?}"
+            );
+            assert_eq!(http::Response::from(res).into_body(), TEXT.as_bytes());
+            assert_eq!(accept, Some(Coding::accept_encoding()));
+        }
+    }
+
+    #[test]
+    fn unsupported() {
+        let (res, _) = fetch("compress", TEXT.into());
+        assert_eq!(res.headers()[header::CONTENT_ENCODING], "compress");
+        assert_eq!(http::Response::from(res).into_body(), TEXT.as_bytes());
+    }
+
+    #[test]
+    fn empty() {
+        for &coding in Coding::ALL {
+            let (res, _) = fetch(coding.name(), vec![]);
+            assert!(
+                http::Response::from(res).into_body().is_empty(),
+                "{coding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for &coding in Coding::ALL {
+            let mock = Mock {
+                coding: coding.name(),
+                body: TEXT.into(),
+                accept: None,
+            };
+
+            let mut client = mock.with(Decompress);
+            let res = future::block_on(async {
+                let res = client.get(Uri::from_static("/"), ()).await?;
+                http::Response::from(res).into_body().vec().await?;
+                Ok::<_, Error>(())
+            });
+
+            assert!(res.is_err(), "{coding:?}");
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        for &coding in Coding::ALL {
+            let mut body = encode(coding);
+            body.truncate(body.len() / 2);
+
+            let mock = Mock {
+                coding: coding.name(),
+                body,
+                accept: None,
+            };
+
+            let mut client = mock.with(Decompress);
+            let res = future::block_on(async {
+                let res = client.get(Uri::from_static("/"), ()).await?;
+                http::Response::from(res).into_body().vec().await
+            });
+
+            assert!(res.is_err(), "{coding:?}");
+        }
+    }
+}
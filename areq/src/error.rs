@@ -0,0 +1,416 @@
+//! The client error.
+
+use {
+    crate::time::Elapsed,
+    http::{Method, StatusCode, Uri},
+    std::{convert::Infallible, error, fmt, io},
+};
+
+type Source = Box<dyn error::Error + Send + Sync>;
+
+/// The client error.
+///
+/// The error has a [kind](ErrorKind) to decide how to handle it,
+/// the method and uri of the failed request when they are known,
+/// and the underlying error as its [source](error::Error::source).
+pub struct Error {
+    inner: Box<Inner>,
+}
+
+struct Inner {
+    kind: ErrorKind,
+    source: Option<Source>,
+    method: Option<Method>,
+    uri: Option<Uri>,
+    status: Option<StatusCode>,
+}
+
+impl Error {
+    /// Creates the error of the kind with the source.
+    pub fn new<E>(kind: ErrorKind, source: E) -> Self
+    where
+        E: Into<Source>,
+    {
+        Self::with_source(kind, Some(source.into()))
+    }
+
+    fn with_source(kind: ErrorKind, source: Option<Source>) -> Self {
+        Self {
+            inner: Box::new(Inner {
+                kind,
+                source,
+                method: None,
+                uri: None,
+                status: None,
+            }),
+        }
+    }
+
+    /// Creates the error of the failed connection.
+    ///
+    /// The I/O error that timed out keeps the [timeout](ErrorKind::Timeout) kind.
+    pub fn connect(e: io::Error) -> Self {
+        let e = Self::from(e);
+        match e.kind() {
+            ErrorKind::Timeout => e,
+            _ => Self {
+                inner: Box::new(Inner {
+                    kind: ErrorKind::Connect,
+                    ..*e.inner
+                }),
+            },
+        }
+    }
+
+    /// Creates the error of the failed TLS handshake.
+    #[cfg(feature = "tls")]
+    pub(crate) fn tls(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::TimedOut {
+            return Self::from(e);
+        }
+
+        // preserve the rustls error as the source
+        let source = if e
+            .get_ref()
+            .is_some_and(|e| e.is::<futures_rustls::rustls::Error>())
+        {
+            e.into_inner()
+                .expect("the error should have an inner error")
+        } else {
+            Box::new(e)
+        };
+
+        Self::with_source(ErrorKind::Tls, Some(source))
+    }
+
+    /// Creates the error of a failed body.
+    pub(crate) fn body(e: io::Error) -> Self {
+        Self::new(ErrorKind::Body, e)
+    }
+
+    /// Creates the error of the unexpected response status.
+    pub fn from_status(status: StatusCode) -> Self {
+        let mut e = Self::with_source(ErrorKind::Status, None);
+        e.inner.status = Some(status);
+        e
+    }
+
+    pub(crate) fn invalid_host() -> Self {
+        Self::new(ErrorKind::Request, "invalid host")
+    }
+
+    /// Attaches the method and uri of the failed request.
+    ///
+    /// An already known request is kept, so the context
+    /// is set by the innermost client.
+    pub fn with_request(mut self, method: Method, uri: Uri) -> Self {
+        if self.inner.method.is_none() {
+            self.inner.method = Some(method);
+            self.inner.uri = Some(uri);
+        }
+
+        self
+    }
+
+    /// Returns the error kind.
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.inner.kind
+    }
+
+    /// Returns the method of the failed request.
+    #[inline]
+    pub fn method(&self) -> Option<&Method> {
+        self.inner.method.as_ref()
+    }
+
+    /// Returns the uri of the failed request.
+    #[inline]
+    pub fn uri(&self) -> Option<&Uri> {
+        self.inner.uri.as_ref()
+    }
+
+    /// Returns the response status of the [status](ErrorKind::Status) error.
+    #[inline]
+    pub fn status(&self) -> Option<StatusCode> {
+        self.inner.status
+    }
+
+    /// Returns the underlying error.
+    #[inline]
+    pub fn get_ref(&self) -> Option<&(dyn error::Error + Send + Sync + 'static)> {
+        self.inner.source.as_deref()
+    }
+
+    /// Checks if the connection could not be established.
+    ///
+    /// This includes the [connect timeout](Elapsed::Connect).
+    pub fn is_connect(&self) -> bool {
+        self.kind() == ErrorKind::Connect
+            || self
+                .get_ref()
+                .and_then(|e| e.downcast_ref())
+                .is_some_and(|e| matches!(e, Elapsed::Connect))
+    }
+
+    /// Checks if the TLS handshake failed.
+    #[inline]
+    pub fn is_tls(&self) -> bool {
+        self.kind() == ErrorKind::Tls
+    }
+
+    /// Checks if the error is caused by a timeout.
+    #[inline]
+    pub fn is_timeout(&self) -> bool {
+        self.kind() == ErrorKind::Timeout
+    }
+
+    /// Checks if the peer violated the protocol.
+    #[inline]
+    pub fn is_protocol(&self) -> bool {
+        self.kind() == ErrorKind::Protocol
+    }
+
+    /// Checks if the error is related to a body.
+    #[inline]
+    pub fn is_body(&self) -> bool {
+        self.kind() == ErrorKind::Body
+    }
+
+    /// Checks if the connection was closed.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.kind() == ErrorKind::Closed
+    }
+
+    /// Checks if the error is caused by a response status.
+    #[inline]
+    pub fn is_status(&self) -> bool {
+        self.kind() == ErrorKind::Status
+    }
+
+    /// Checks if a redirect could not be followed.
+    #[inline]
+    pub fn is_redirect(&self) -> bool {
+        self.kind() == ErrorKind::Redirect
+    }
+
+    /// Returns the underlying I/O error if there is one.
+    pub fn try_into_io(self) -> Result<io::Error, Self> {
+        let Inner {
+            kind,
+            source,
+            method,
+            uri,
+            status,
+        } = *self.inner;
+
+        match source.map(|e| e.downcast()) {
+            Some(Ok(e)) => Ok(*e),
+            source => Err(Self {
+                inner: Box::new(Inner {
+                    kind,
+                    source: source.map(|e| e.expect_err("the error shouldn't be I/O")),
+                    method,
+                    uri,
+                    status,
+                }),
+            }),
+        }
+    }
+}
+
+/// The kind of [error](Error).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The connection could not be established.
+    Connect,
+    /// The TLS handshake failed.
+    Tls,
+    /// An operation timed out.
+    Timeout,
+    /// The peer violated the protocol.
+    Protocol,
+    /// A request or response body failed.
+    Body,
+    /// The connection was closed.
+    Closed,
+    /// The response has an error status.
+    Status,
+    /// A redirect could not be followed.
+    Redirect,
+    /// The request is invalid.
+    Request,
+    /// Any other I/O error.
+    Io,
+}
+
+impl ErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Connect => "connect error",
+            Self::Tls => "tls error",
+            Self::Timeout => "timed out",
+            Self::Protocol => "protocol error",
+            Self::Body => "body error",
+            Self::Closed => "connection closed",
+            Self::Status => "error status",
+            Self::Redirect => "redirect error",
+            Self::Request => "invalid request",
+            Self::Io => "io error",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        // unwrap the error converted into I/O before
+        let e = match e.downcast::<Self>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+
+        if e.get_ref().is_some_and(|e| e.is::<Elapsed>()) {
+            return Self::new(ErrorKind::Timeout, e);
+        }
+
+        #[cfg(feature = "http1")]
+        let e = match e.downcast::<areq_h1::Error>() {
+            Ok(e) => return Self::from(e),
+            Err(e) => e,
+        };
+
+        let kind = match e.kind() {
+            io::ErrorKind::TimedOut => ErrorKind::Timeout,
+            io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::NotConnected => ErrorKind::Closed,
+            io::ErrorKind::InvalidData => ErrorKind::Protocol,
+            _ => ErrorKind::Io,
+        };
+
+        Self::new(kind, e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        e.try_into_io().unwrap_or_else(|e| {
+            let kind = match e.kind() {
+                ErrorKind::Timeout => io::ErrorKind::TimedOut,
+                ErrorKind::Protocol => io::ErrorKind::InvalidData,
+                ErrorKind::Closed => io::ErrorKind::ConnectionAborted,
+                ErrorKind::Request => io::ErrorKind::InvalidInput,
+                _ => io::ErrorKind::Other,
+            };
+
+            Self::new(kind, e)
+        })
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Error");
+        s.field("kind", &self.inner.kind);
+        if let Some(method) = &self.inner.method {
+            s.field("method", method);
+        }
+
+        if let Some(uri) = &self.inner.uri {
+            s.field("uri", uri);
+        }
+
+        if let Some(status) = &self.inner.status {
+            s.field("status", status);
+        }
+
+        if let Some(source) = &self.inner.source {
+            s.field("source", source);
+        }
+
+        s.finish()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind())?;
+        if let Some(status) = self.inner.status {
+            write!(f, " {status}")?;
+        }
+
+        if let (Some(method), Some(uri)) = (&self.inner.method, &self.inner.uri) {
+            write!(f, " for {method} {uri}")?;
+        }
+
+        if let Some(source) = &self.inner.source {
+            write!(f, ": {source}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        let source = self.inner.source.as_deref()?;
+        Some(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_kinds() {
+        let e = Error::from(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert!(e.is_closed());
+
+        let e = Error::from(io::Error::from(Elapsed::FirstByte));
+        assert!(e.is_timeout());
+        assert!(!e.is_connect());
+
+        let e = Error::connect(io::Error::from(io::ErrorKind::ConnectionRefused));
+        assert!(e.is_connect());
+        assert_eq!(
+            e.try_into_io().expect("io error").kind(),
+            io::ErrorKind::ConnectionRefused,
+        );
+
+        let e = Error::from(Elapsed::Connect);
+        assert!(e.is_timeout());
+        assert!(e.is_connect());
+    }
+
+    #[test]
+    fn round_trip() {
+        let uri = Uri::from_static("http://example.com/");
+        let e = Error::from_status(StatusCode::NOT_FOUND).with_request(Method::GET, uri.clone());
+        let e = Error::from(io::Error::from(e));
+        assert!(e.is_status());
+        assert_eq!(e.status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(e.method(), Some(&Method::GET));
+        assert_eq!(e.uri(), Some(&uri));
+        assert_eq!(
+            e.to_string(),
+            "error status 404 Not Found for GET http://example.com/",
+        );
+    }
+}
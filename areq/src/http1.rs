@@ -4,14 +4,18 @@ use {
     crate::{
         body::prelude::*,
         client::Client,
+        error::{Error, ErrorKind},
         negotiate::Negotiate,
-        proto::{Error, Handshake, Request, Response, Session},
+        proto::{Handshake, Request, Response, Session},
+        time::{SharedTimer, Timer},
     },
-    areq_h1::Config,
     futures_lite::prelude::*,
     http::{HeaderValue, Version, header},
+    std::time::Duration,
 };
 
+pub use areq_h1::Config;
+
 #[derive(Clone, Default)]
 pub struct Http1 {
     conf: Config,
@@ -19,6 +23,28 @@ pub struct Http1 {
 
 impl Http1 {
     const ALPN: &[u8] = b"http/1.1";
+
+    /// Creates the http/1.1 handshake with the given [configuration](Config).
+    #[inline]
+    pub fn with_config(conf: Config) -> Self {
+        Self { conf }
+    }
+
+    /// Enables the `Expect: 100-continue` mechanism for requests with a body.
+    ///
+    /// The [timer](Timer) is used to wait for the timeout.
+    /// See [`Config::expect_continue`] for details.
+    pub fn expect_continue<T>(mut self, timeout: Duration, timer: T) -> Self
+    where
+        T: Timer + Send + Sync + 'static,
+    {
+        let timer = SharedTimer::new(timer);
+        self.conf = self
+            .conf
+            .expect_continue(timeout, move |dur| timer.sleep(dur));
+
+        self
+    }
 }
 
 impl<I, B> Handshake<I, B> for Http1
@@ -35,7 +61,7 @@ where
         let Session { addr, io } = se;
         let (reqs, conn) = self.conf.handshake(io);
         let host =
-            HeaderValue::from_maybe_shared(addr.host_value()).map_err(|_| Error::InvalidHost)?;
+            HeaderValue::from_maybe_shared(addr.host_value()).map_err(|_| Error::invalid_host())?;
 
         let client = H1 { reqs, host };
         Ok((client, conn))
@@ -91,14 +117,92 @@ where
 
     async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
         self.prepare(&mut req);
+        let method = req.method().clone();
+        let uri = req.uri().clone();
         let req = req.map_body(B::into_body).into();
-        let res = self.reqs.send(req).await?;
-        Ok(Response::new(res))
+        match self.reqs.send(req).await {
+            Ok(res) => Ok(Response::new(res)),
+            Err(e) => Err(Error::from(e).with_request(method, uri)),
+        }
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.reqs.is_reusable()
     }
 }
 
 impl From<areq_h1::Error> for Error {
     fn from(e: areq_h1::Error) -> Self {
-        Self::Io(e.into())
+        let kind = match e {
+            areq_h1::Error::Io(e) => return Self::from(e),
+            areq_h1::Error::Closed => ErrorKind::Closed,
+            areq_h1::Error::Parse(_)
+            | areq_h1::Error::TooLargeInput
+            | areq_h1::Error::UnsupportedVersion(_) => ErrorKind::Protocol,
+        };
+
+        Self::new(kind, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{Address, test},
+        futures_lite::future,
+        http::{HeaderMap, Method, Uri},
+    };
+
+    #[test]
+    fn trailers() -> Result<(), Error> {
+        const RESPONSE: &[u8] = b"\
+            HTTP/1.1 200 OK\r\n\
+            transfer-encoding: chunked\r\n\
+            \r\n\
+            2\r\nhi\r\n\
+            0\r\n\
+            digest: sha-256=def\r\n\
+            \r\n";
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        let mut io = test::Mock::new(RESPONSE);
+        let (data, received) = future::block_on(async {
+            let se = Session {
+                addr: Address::http("localhost"),
+                io: &mut io,
+            };
+
+            let (mut client, conn) = Http1::default().handshake(se).await?;
+            let send = async move {
+                let body = "hi".with_trailers(trailers);
+                let req = Request::new(Method::POST, Uri::from_static("/"), body);
+                let mut body = client.send(req).await?.into_body();
+                let data = body.chunk().await.transpose()?;
+                assert!(body.chunk().await.is_none(), "the data ends");
+
+                let received = body.trailers().await.transpose()?;
+                Ok::<_, Error>((data, received))
+            };
+
+            let (res, ()) = future::zip(send, conn).await;
+            res
+        })?;
+
+        assert_eq!(data.as_deref(), Some(&b"hi"[..]));
+        assert_eq!(
+            received.as_ref().and_then(|map| map.get("digest")),
+            Some(&HeaderValue::from_static("sha-256=def")),
+        );
+
+        let output = String::from_utf8(io.output).expect("utf-8");
+        assert!(
+            output.ends_with("\r\n\r\n2\r\nhi\r\n0\r\ndigest: sha-256=abc\r\n\r\n"),
+            "the trailers follow the chunked body",
+        );
+        Ok(())
     }
 }
@@ -4,24 +4,51 @@ use {
     crate::{
         body::prelude::*,
         client::Client,
+        error::{Error, ErrorKind},
         io::Io,
         negotiate::Negotiate,
-        proto::{Error, Handshake, Request, Response, Session},
+        proto::{Handshake, Request, Response, Session},
+        time::{SharedTimer, Timer},
     },
     bytes::{Buf, Bytes},
     futures_lite::prelude::*,
     h2::client,
-    http::{HeaderValue, Version, header},
-    std::{future, io},
+    http::{HeaderMap, HeaderValue, StatusCode, Version, header},
+    std::{
+        future, io,
+        pin::Pin,
+        task::{Poll, ready},
+        time::Duration,
+    },
 };
 
 #[derive(Clone, Default)]
 pub struct Http2 {
     build: client::Builder,
+    expect: Option<(Duration, SharedTimer)>,
 }
 
 impl Http2 {
     const ALPN: &[u8] = b"h2";
+
+    /// Enables the `Expect: 100-continue` mechanism for requests with a body.
+    ///
+    /// The request headers are sent first, then the body is sent only after
+    /// the server responds with `100 Continue` or the timeout elapses.
+    /// If the server responds with a final status instead, the body is
+    /// skipped and the response is returned.
+    ///
+    /// The [timer](Timer) is used to wait for the timeout. Requests with
+    /// the `Expect: 100-continue` header set explicitly are handled the
+    /// same way. If the mechanism isn't enabled, the body of such requests
+    /// is sent right away, since a server may ignore the expectation.
+    pub fn expect_continue<T>(mut self, timeout: Duration, timer: T) -> Self
+    where
+        T: Timer + Send + Sync + 'static,
+    {
+        self.expect = Some((timeout, SharedTimer::new(timer)));
+        self
+    }
 }
 
 impl<I, B> Handshake<I, B> for Http2
@@ -37,11 +64,12 @@ where
     ) -> Result<(Self::Client, impl Future<Output = ()>), Error> {
         let Session { addr, io } = se;
         let io = Io::new(io);
+        let expect = self.expect;
         let (send, conn) = self.build.handshake(io).await?;
         let host =
-            HeaderValue::from_maybe_shared(addr.host_value()).map_err(|_| Error::InvalidHost)?;
+            HeaderValue::from_maybe_shared(addr.host_value()).map_err(|_| Error::invalid_host())?;
 
-        let client = H2 { send, host };
+        let client = H2 { send, host, expect };
         let conn = async {
             _ = conn.await;
         };
@@ -71,6 +99,7 @@ where
 {
     send: client::SendRequest<Flow<B::Chunk>>,
     host: HeaderValue,
+    expect: Option<(Duration, SharedTimer)>,
 }
 
 impl<B> H2<B>
@@ -98,68 +127,136 @@ where
     async fn ready(&mut self) -> Result<(), h2::Error> {
         future::poll_fn(|cx| self.send.poll_ready(cx)).await
     }
-}
 
-impl<B> Clone for H2<B>
-where
-    B: IntoBody,
-{
-    fn clone(&self) -> Self {
-        Self {
-            send: self.send.clone(),
-            host: self.host.clone(),
-        }
-    }
-}
-
-impl<B> Client<B> for H2<B>
-where
-    B: IntoBody,
-{
-    type Body = BodyH2;
-
-    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+    async fn send_request(&mut self, mut req: Request<B>) -> Result<Response<BodyH2>, Error> {
         self.prepare(&mut req);
 
         let (head, body) = http::Request::from(req).into_parts();
-        let header_req = http::Request::from_parts(head, ());
+        let mut header_req = http::Request::from_parts(head, ());
 
         let mut body = body.into_body();
         let size = body.size_hint();
         let end = size.end();
 
+        if self.expect.is_some() && !end {
+            header_req.headers_mut().insert(
+                header::EXPECT,
+                const { HeaderValue::from_static("100-continue") },
+            );
+        }
+
+        // without the timeout the body is sent right away,
+        // since a server may ignore the expectation
+        let expect = self.expect.clone().filter(|_| {
+            !end && header_req
+                .headers()
+                .get(header::EXPECT)
+                .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"100-continue"))
+        });
+
         self.ready().await?;
-        let (resfu, mut send_body) = self.send.send_request(header_req, end)?;
+        let (mut resfu, mut send_body) = self.send.send_request(header_req, end)?;
+
+        // the final response received before the body is sent
+        let mut early = None;
 
         'body: {
             if end {
                 break 'body;
             }
 
+            if let Some((timeout, timer)) = expect {
+                let wait = future::poll_fn(|cx| {
+                    while let Poll::Ready(Some(res)) = resfu.poll_informational(cx) {
+                        if res?.status() == StatusCode::CONTINUE {
+                            return Poll::Ready(Ok::<_, h2::Error>(true));
+                        }
+                    }
+
+                    early = Some(ready!(Pin::new(&mut resfu).poll(cx)));
+                    Poll::Ready(Ok(false))
+                });
+
+                let timeout = async {
+                    timer.sleep(timeout).await;
+                    Ok(true)
+                };
+
+                // if the body is skipped, the stream
+                // is reset once the response is dropped
+                if !wait.or(timeout).await? {
+                    break 'body;
+                }
+            }
+
             match size {
                 Hint::Empty => unreachable!(),
                 Hint::Full { .. } => {
-                    let chunk = body.take_full().await?;
+                    let chunk = body.take_full().await.map_err(Error::body)?;
                     send_body.send_data(chunk.map_or(Flow::End, Flow::Next), true)?;
                 }
                 Hint::Chunked { .. } => {
+                    let mut last = None;
                     while let Some(chunk) = body.chunk().await {
-                        let end = body.size_hint().end();
-                        send_body.send_data(Flow::Next(chunk?), end)?;
-
-                        if end {
-                            break 'body;
+                        let chunk = chunk.map_err(Error::body)?;
+                        if body.size_hint().end() {
+                            last = Some(chunk);
+                            break;
                         }
+
+                        send_body.send_data(Flow::Next(chunk), false)?;
+                    }
+
+                    // the stream ends with the trailers if there are any
+                    let trailers = body.trailers().await.transpose().map_err(Error::body)?;
+                    if last.is_some() || trailers.is_none() {
+                        let last = last.map_or(Flow::End, Flow::Next);
+                        send_body.send_data(last, trailers.is_none())?;
                     }
 
-                    send_body.send_data(Flow::End, true)?;
+                    if let Some(trailers) = trailers {
+                        send_body.send_trailers(trailers)?;
+                    }
                 }
             }
         }
 
-        let res = resfu.await?.map(BodyH2);
+        let res = match early {
+            Some(res) => res?,
+            None => resfu.await?,
+        };
+
+        let res = res.map(|recv| BodyH2 { recv, end: false });
         Ok(Response::new(res))
     }
+}
+
+impl<B> Clone for H2<B>
+where
+    B: IntoBody,
+{
+    fn clone(&self) -> Self {
+        Self {
+            send: self.send.clone(),
+            host: self.host.clone(),
+            expect: self.expect.clone(),
+        }
+    }
+}
+
+impl<B> Client<B> for H2<B>
+where
+    B: IntoBody,
+{
+    type Body = BodyH2;
+
+    async fn send(&mut self, req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        self.send_request(req)
+            .await
+            .map_err(|e| e.with_request(method, uri))
+    }
 
     fn try_clone(&self) -> Option<Self> {
         Some(self.clone())
@@ -197,19 +294,31 @@ where
     }
 }
 
-pub struct BodyH2(h2::RecvStream);
+pub struct BodyH2 {
+    recv: h2::RecvStream,
+    end: bool,
+}
 
 impl Body for BodyH2 {
     type Chunk = Bytes;
 
     async fn chunk(&mut self) -> Option<Result<Self::Chunk, io::Error>> {
-        let res = self.0.data().await?;
+        let Some(res) = self.recv.data().await else {
+            self.end = true;
+            return None;
+        };
+
         Some(res.map_err(into_io_error))
     }
 
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        let res = self.recv.trailers().await;
+        res.map_err(into_io_error).transpose()
+    }
+
     fn size_hint(&self) -> Hint {
         Hint::Chunked {
-            end: self.0.is_end_stream(),
+            end: self.end || self.recv.is_end_stream(),
         }
     }
 }
@@ -224,6 +333,235 @@ fn into_io_error(e: h2::Error) -> io::Error {
 
 impl From<h2::Error> for Error {
     fn from(e: h2::Error) -> Self {
-        Self::Io(into_io_error(e))
+        if e.is_io() {
+            let e = e.into_io().expect("the error should be IO");
+            return Self::from(e);
+        }
+
+        // the stream is refused or the connection is gracefully
+        // closed, so the request wasn't processed by the server
+        let closed = e.reason() == Some(h2::Reason::REFUSED_STREAM)
+            || e.is_go_away() && e.reason() == Some(h2::Reason::NO_ERROR);
+
+        let kind = if closed {
+            ErrorKind::Closed
+        } else {
+            ErrorKind::Protocol
+        };
+
+        Self::new(kind, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{Address, test::Duplex},
+        futures_lite::future,
+        h2::{RecvStream, server::SendResponse},
+        http::{Method, Uri},
+    };
+
+    /// Sends the request to the in-memory server
+    /// which handles it with the `handle` function,
+    /// then reads the response with the `read` function.
+    fn serve<B, F, U, R, V, T>(
+        http2: Http2,
+        req: Request<B>,
+        handle: F,
+        read: R,
+    ) -> Result<T, Error>
+    where
+        B: IntoBody,
+        F: FnOnce(http::Request<RecvStream>, SendResponse<Bytes>) -> U,
+        U: Future<Output = Result<(), h2::Error>>,
+        R: FnOnce(Response<BodyH2>) -> V,
+        V: Future<Output = Result<T, Error>>,
+    {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let server = async move {
+            let mut conn = h2::server::handshake(server_io).await?;
+            let (req, respond) = conn.accept().await.expect("the request")?;
+            let handle = handle(req, respond);
+            let drive = async {
+                // drive the connection until the client is gone
+                while let Some(Ok(_)) = conn.accept().await {}
+            };
+
+            let (res, ()) = future::zip(handle, drive).await;
+            res
+        };
+
+        let client = async move {
+            let se = Session {
+                addr: Address::http("localhost"),
+                io: Duplex(client_io),
+            };
+
+            let (mut client, conn) = http2.handshake(se).await?;
+            let send = async move {
+                let res = client.send(req).await?;
+                read(res).await
+            };
+
+            // the connection is dropped once the response is read,
+            // so the server sees the client is gone
+            let conn = async {
+                conn.await;
+                Err(Error::from(io::Error::from(
+                    io::ErrorKind::ConnectionAborted,
+                )))
+            };
+
+            future::or(send, conn).await
+        };
+
+        let (server, res) = future::block_on(future::zip(server, client));
+        server.map_err(Error::from)?;
+        res
+    }
+
+    async fn read_body(body: &mut RecvStream) -> Result<Vec<u8>, h2::Error> {
+        let mut data = vec![];
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk?;
+            body.flow_control().release_capacity(chunk.len())?;
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok(data)
+    }
+
+    fn status(status: StatusCode) -> http::Response<()> {
+        let mut res = http::Response::new(());
+        *res.status_mut() = status;
+        res
+    }
+
+    fn post(body: &'static str) -> Request<&'static str> {
+        Request::new(Method::POST, Uri::from_static("http://localhost/"), body)
+    }
+
+    #[test]
+    fn expect_continue() -> Result<(), Error> {
+        let http2 =
+            Http2::default().expect_continue(Duration::from_secs(1), |_| future::pending::<()>());
+        let res = serve(
+            http2,
+            post("hello"),
+            |req, mut respond| async move {
+                assert_eq!(req.headers()[header::EXPECT], "100-continue");
+                respond.send_informational(status(StatusCode::CONTINUE))?;
+
+                let mut body = req.into_body();
+                assert_eq!(read_body(&mut body).await?, b"hello");
+                respond.send_response(status(StatusCode::OK), true)?;
+                Ok(())
+            },
+            async |res| Ok(res.status()),
+        )?;
+
+        assert_eq!(res, StatusCode::OK);
+        Ok(())
+    }
+
+    #[test]
+    fn expect_rejected() -> Result<(), Error> {
+        let http2 =
+            Http2::default().expect_continue(Duration::from_secs(1), |_| future::pending::<()>());
+        let res = serve(
+            http2,
+            post("hello"),
+            |req, mut respond| async move {
+                assert_eq!(req.headers()[header::EXPECT], "100-continue");
+                respond.send_response(status(StatusCode::PAYLOAD_TOO_LARGE), true)?;
+                Ok(())
+            },
+            async |res| Ok(res.status()),
+        )?;
+
+        // the body is skipped once the final response is received
+        assert_eq!(res, StatusCode::PAYLOAD_TOO_LARGE);
+        Ok(())
+    }
+
+    #[test]
+    fn expect_timeout() -> Result<(), Error> {
+        // the server doesn't respond with 100 continue,
+        // so the body is sent after the timeout
+        let http2 = Http2::default().expect_continue(Duration::from_secs(1), |_| future::ready(()));
+        let res = serve(
+            http2,
+            post("hello"),
+            |req, mut respond| async move {
+                assert_eq!(req.headers()[header::EXPECT], "100-continue");
+
+                let mut body = req.into_body();
+                assert_eq!(read_body(&mut body).await?, b"hello");
+                respond.send_response(status(StatusCode::OK), true)?;
+                Ok(())
+            },
+            async |res| Ok(res.status()),
+        )?;
+
+        assert_eq!(res, StatusCode::OK);
+        Ok(())
+    }
+
+    #[test]
+    fn send_trailers() -> Result<(), Error> {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        let body = "hello".with_trailers(trailers.clone());
+        let req = Request::new(Method::POST, Uri::from_static("http://localhost/"), body);
+        let res = serve(
+            Http2::default(),
+            req,
+            |req, mut respond| async move {
+                let mut body = req.into_body();
+                assert_eq!(read_body(&mut body).await?, b"hello");
+                assert_eq!(body.trailers().await?, Some(trailers));
+                respond.send_response(status(StatusCode::OK), true)?;
+                Ok(())
+            },
+            async |res| Ok(res.status()),
+        )?;
+
+        assert_eq!(res, StatusCode::OK);
+        Ok(())
+    }
+
+    #[test]
+    fn recv_trailers() -> Result<(), Error> {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("digest", HeaderValue::from_static("sha-256=abc"));
+
+        let sent = trailers.clone();
+        let req = Request::new(Method::GET, Uri::from_static("http://localhost/"), ());
+        let (data, received) = serve(
+            Http2::default(),
+            req,
+            |_, mut respond| async move {
+                let mut send = respond.send_response(status(StatusCode::OK), false)?;
+                send.send_data(Bytes::from_static(b"hello"), false)?;
+                send.send_trailers(sent)?;
+                Ok(())
+            },
+            async |res| {
+                let mut body = res.into_body();
+                let data = body.chunk().await.transpose()?;
+                assert!(body.chunk().await.is_none(), "the data ends");
+                assert!(body.size_hint().end(), "the body ends");
+
+                let received = body.trailers().await.transpose()?;
+                Ok((data, received))
+            },
+        )?;
+
+        assert_eq!(data.as_deref(), Some(&b"hello"[..]));
+        assert_eq!(received, Some(trailers));
+        Ok(())
     }
 }
@@ -0,0 +1,490 @@
+//! The client middleware.
+
+use {
+    crate::{
+        body::Body,
+        client::Client,
+        error::Error,
+        proto::{Request, Response},
+    },
+    bytes::Bytes,
+    http::{HeaderMap, HeaderName, HeaderValue, header::Entry},
+    std::{fmt, sync::Arc},
+};
+
+/// The trait to wrap a [client](Client) into another one.
+///
+/// Layers can be applied to a client with
+/// [`with`](crate::ClientExt::with) or composed
+/// together with a [builder](Builder).
+pub trait Layer<C> {
+    /// The wrapped client type.
+    type Client;
+
+    /// Wraps the client.
+    fn layer(&self, client: C) -> Self::Client;
+}
+
+impl<C, L> Layer<C> for &L
+where
+    L: Layer<C>,
+{
+    type Client = L::Client;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        (**self).layer(client)
+    }
+}
+
+/// The layer that returns a client unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Identity;
+
+impl<C> Layer<C> for Identity {
+    type Client = C;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        client
+    }
+}
+
+/// Two layers applied one after another.
+///
+/// The `inner` layer wraps a client first,
+/// then the `outer` layer wraps the result.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stack<I, O> {
+    inner: I,
+    outer: O,
+}
+
+impl<I, O> Stack<I, O> {
+    /// Creates a new stack of layers.
+    #[inline]
+    pub fn new(inner: I, outer: O) -> Self {
+        Self { inner, outer }
+    }
+}
+
+impl<C, I, O> Layer<C> for Stack<I, O>
+where
+    I: Layer<C>,
+    O: Layer<I::Client>,
+{
+    type Client = O::Client;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        self.outer.layer(self.inner.layer(client))
+    }
+}
+
+/// The builder of a layer stack.
+///
+/// Layers are applied in the order they are added,
+/// so the first added layer is the outermost one
+/// and sees a request first.
+#[derive(Clone, Debug, Default)]
+pub struct Builder<L = Identity>(L);
+
+impl Builder {
+    /// Creates an empty builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self(Identity)
+    }
+}
+
+impl<L> Builder<L> {
+    /// Adds a new layer.
+    #[inline]
+    pub fn layer<N>(self, layer: N) -> Builder<Stack<N, L>> {
+        Builder(Stack::new(layer, self.0))
+    }
+
+    /// Adds the [default headers](DefaultHeaders) layer.
+    #[inline]
+    pub fn default_headers(self, headers: HeaderMap) -> Builder<Stack<DefaultHeaders, L>> {
+        self.layer(DefaultHeaders::new(headers))
+    }
+
+    /// Adds the [request mapping](MapRequest) layer.
+    #[inline]
+    pub fn map_request<F>(self, f: F) -> Builder<Stack<MapRequest<F>, L>> {
+        self.layer(MapRequest::new(f))
+    }
+
+    /// Adds the [response mapping](MapResponse) layer.
+    #[inline]
+    pub fn map_response<F>(self, f: F) -> Builder<Stack<MapResponse<F>, L>> {
+        self.layer(MapResponse::new(f))
+    }
+
+    /// Wraps the client with all added layers.
+    #[inline]
+    pub fn client<C>(&self, client: C) -> L::Client
+    where
+        L: Layer<C>,
+    {
+        self.0.layer(client)
+    }
+
+    /// Returns the composed layer.
+    #[inline]
+    pub fn into_inner(self) -> L {
+        self.0
+    }
+}
+
+impl<C, L> Layer<C> for Builder<L>
+where
+    L: Layer<C>,
+{
+    type Client = L::Client;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        self.0.layer(client)
+    }
+}
+
+/// The layer that adds headers missing in a request.
+#[derive(Clone, Debug, Default)]
+pub struct DefaultHeaders(Arc<HeaderMap>);
+
+impl DefaultHeaders {
+    /// Creates the layer from a header map.
+    #[inline]
+    pub fn new(headers: HeaderMap) -> Self {
+        Self(Arc::new(headers))
+    }
+}
+
+impl<C> Layer<C> for DefaultHeaders {
+    type Client = DefaultHeadersClient<C>;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        DefaultHeadersClient {
+            client,
+            headers: self.0.clone(),
+        }
+    }
+}
+
+impl FromIterator<(HeaderName, HeaderValue)> for DefaultHeaders {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (HeaderName, HeaderValue)>,
+    {
+        Self::new(HeaderMap::from_iter(iter))
+    }
+}
+
+/// The client created by the [`DefaultHeaders`] layer.
+#[derive(Debug)]
+pub struct DefaultHeadersClient<C> {
+    client: C,
+    headers: Arc<HeaderMap>,
+}
+
+impl<B, C> Client<B> for DefaultHeadersClient<C>
+where
+    C: Client<B>,
+{
+    type Body = C::Body;
+
+    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let headers = req.headers_mut();
+        for name in self.headers.keys() {
+            if let Entry::Vacant(entry) = headers.entry(name) {
+                let mut values = self.headers.get_all(name).iter().cloned();
+                if let Some(value) = values.next() {
+                    let mut entry = entry.insert_entry(value);
+                    for value in values {
+                        entry.append(value);
+                    }
+                }
+            }
+        }
+
+        self.client.send(req).await
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            client: self.client.try_clone()?,
+            headers: self.headers.clone(),
+        })
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+/// The layer that maps a request before sending.
+///
+/// The mapping function may change the request body type.
+#[derive(Clone, Copy)]
+pub struct MapRequest<F>(F);
+
+impl<F> MapRequest<F> {
+    /// Creates the layer from a mapping function.
+    #[inline]
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> fmt::Debug for MapRequest<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapRequest").finish_non_exhaustive()
+    }
+}
+
+impl<C, F> Layer<C> for MapRequest<F>
+where
+    F: Clone,
+{
+    type Client = MapRequestClient<C, F>;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        MapRequestClient {
+            client,
+            f: Arc::new(self.0.clone()),
+        }
+    }
+}
+
+/// The client created by the [`MapRequest`] layer.
+pub struct MapRequestClient<C, F> {
+    client: C,
+    f: Arc<F>,
+}
+
+impl<B, D, C, F> Client<B> for MapRequestClient<C, F>
+where
+    C: Client<D>,
+    F: Fn(Request<B>) -> Request<D>,
+{
+    type Body = C::Body;
+
+    #[inline]
+    async fn send(&mut self, req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        self.client.send((self.f)(req)).await
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            client: self.client.try_clone()?,
+            f: self.f.clone(),
+        })
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+impl<C, F> fmt::Debug for MapRequestClient<C, F>
+where
+    C: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapRequestClient")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The layer that maps a received response.
+///
+/// The mapping function may change the response body type.
+#[derive(Clone, Copy)]
+pub struct MapResponse<F>(F);
+
+impl<F> MapResponse<F> {
+    /// Creates the layer from a mapping function.
+    #[inline]
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> fmt::Debug for MapResponse<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponse").finish_non_exhaustive()
+    }
+}
+
+impl<C, F> Layer<C> for MapResponse<F>
+where
+    F: Clone,
+{
+    type Client = MapResponseClient<C, F>;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        MapResponseClient {
+            client,
+            f: Arc::new(self.0.clone()),
+        }
+    }
+}
+
+/// The client created by the [`MapResponse`] layer.
+pub struct MapResponseClient<C, F> {
+    client: C,
+    f: Arc<F>,
+}
+
+impl<B, D, C, F> Client<B> for MapResponseClient<C, F>
+where
+    C: Client<B>,
+    F: Fn(Response<C::Body>) -> Response<D>,
+    D: Body<Chunk = Bytes>,
+{
+    type Body = D;
+
+    #[inline]
+    async fn send(&mut self, req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        self.client.send(req).await.map(&*self.f)
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            client: self.client.try_clone()?,
+            f: self.f.clone(),
+        })
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+impl<C, F> fmt::Debug for MapResponseClient<C, F>
+where
+    C: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponseClient")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::ClientExt,
+        futures_lite::future,
+        http::{Method, Uri, header},
+    };
+
+    /// Responds with the request headers and body.
+    #[derive(Clone, Debug)]
+    struct Echo {
+        cloneable: bool,
+    }
+
+    impl Client<String> for Echo {
+        type Body = Bytes;
+
+        async fn send(&mut self, req: Request<String>) -> Result<Response<Self::Body>, Error> {
+            let (parts, body) = http::Request::from(req).into_parts();
+            let mut res = http::Response::new(Bytes::from(body));
+            *res.headers_mut() = parts.headers;
+            Ok(Response::new(res))
+        }
+
+        fn try_clone(&self) -> Option<Self> {
+            self.cloneable.then(|| self.clone())
+        }
+    }
+
+    fn send<C>(client: &mut C, req: Request<String>) -> Result<Response<C::Body>, Error>
+    where
+        C: Client<String>,
+    {
+        future::block_on(client.send(req))
+    }
+
+    fn get() -> Request<String> {
+        Request::new(Method::GET, Uri::from_static("/"), String::from("body"))
+    }
+
+    #[test]
+    fn default_headers() -> Result<(), Error> {
+        let headers = DefaultHeaders::from_iter([
+            (header::USER_AGENT, HeaderValue::from_static("areq")),
+            (header::ACCEPT, HeaderValue::from_static("text/plain")),
+        ]);
+
+        let mut client = Echo { cloneable: false }.with(headers);
+        let mut req = get();
+        req.headers_mut()
+            .insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+
+        let res = send(&mut client, req)?;
+        assert_eq!(res.headers()[header::USER_AGENT], "areq");
+        assert_eq!(res.headers()[header::ACCEPT], "*/*");
+        Ok(())
+    }
+
+    #[test]
+    fn stack_order() -> Result<(), Error> {
+        let push = |s: &'static str| move |req: Request<String>| req.map_body(|body| body + s);
+
+        let builder = Builder::new()
+            .map_request(push(" a"))
+            .map_request(push(" b"))
+            .map_response(|res: Response<Bytes>| {
+                res.map(|body| Bytes::from([&body[..], b" c"].concat()))
+            });
+
+        let mut client = builder.client(Echo { cloneable: false });
+        let res = send(&mut client, get())?;
+        let body = http::Response::from(res).into_body();
+        assert_eq!(body, "body a b c");
+        Ok(())
+    }
+
+    #[test]
+    fn map_request_body() -> Result<(), Error> {
+        let layer = MapRequest::new(|req: Request<&str>| req.map_body(str::to_owned));
+        let mut client = Echo { cloneable: false }.with(layer);
+        let req = Request::new(Method::GET, Uri::from_static("/"), "body");
+        let res = future::block_on(client.send(req))?;
+        assert_eq!(http::Response::from(res).into_body(), "body");
+        Ok(())
+    }
+
+    #[test]
+    fn try_clone() {
+        let builder = Builder::new()
+            .default_headers(HeaderMap::new())
+            .map_request(|req: Request<String>| req)
+            .map_response(|res: Response<Bytes>| res);
+
+        let client = builder.client(Echo { cloneable: true });
+        assert!(Client::<String>::try_clone(&client).is_some(), "cloneable");
+
+        let client = builder.client(Echo { cloneable: false });
+        assert!(
+            Client::<String>::try_clone(&client).is_none(),
+            "not cloneable"
+        );
+    }
+}
@@ -5,8 +5,46 @@
 
 mod addr;
 mod alt;
+mod auth;
 pub mod body;
+mod builder;
 mod client;
+#[cfg(any(
+    feature = "gzip",
+    feature = "deflate",
+    feature = "brotli",
+    feature = "zstd",
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "gzip",
+        feature = "deflate",
+        feature = "brotli",
+        feature = "zstd",
+    )))
+)]
+pub mod compress;
+#[cfg(feature = "cookie")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+pub mod cookie;
+#[cfg(any(
+    feature = "gzip",
+    feature = "deflate",
+    feature = "brotli",
+    feature = "zstd",
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "gzip",
+        feature = "deflate",
+        feature = "brotli",
+        feature = "zstd",
+    )))
+)]
+pub mod decompress;
+mod error;
 #[cfg(feature = "http1")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http1")))]
 pub mod http1;
@@ -16,11 +54,28 @@ pub mod http2;
 #[cfg(feature = "http2")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
 mod io;
+pub mod layer;
+pub mod multipart;
 pub mod negotiate;
+pub mod pool;
 mod proto;
 #[cfg(feature = "rtn")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rtn")))]
 mod proto_rtn;
+pub mod proxy;
+pub mod redirect;
+pub mod resolve;
+pub mod retry;
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+pub mod service;
+#[cfg(all(feature = "tower", feature = "rtn"))]
+mod service_rtn;
+pub mod socks;
+pub mod sse;
+#[cfg(test)]
+mod test;
+pub mod time;
 #[cfg(feature = "tls")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
 pub mod tls;
@@ -31,14 +86,20 @@ pub mod prelude {
         crate::{Client, ClientExt as _, Handshake},
         areq_body::prelude::*,
     };
+
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub use crate::body::FormBodyExt as _;
 }
 
 pub use {
     crate::{
-        addr::{Address, IntoHost, InvalidUri},
+        addr::{Address, IntoHost, InvalidUri, UnixAddress},
         alt::Alt,
+        builder::RequestBuilder,
         client::{Client, ClientExt},
-        proto::{Error, Handshake, Request, Response, Session},
+        error::{Error, ErrorKind},
+        proto::{Handshake, Request, Response, Session},
     },
     bytes, http,
 };
@@ -0,0 +1,823 @@
+//! The multipart bodies.
+
+use {
+    crate::{
+        body::{Body, Boxed, Hint, IntoRequestBody, PollBody},
+        proto::Request,
+    },
+    bytes::{Buf, BufMut, Bytes, BytesMut},
+    futures_lite::prelude::*,
+    http::{HeaderMap, HeaderName, HeaderValue, header},
+    std::{
+        collections::VecDeque,
+        error,
+        fmt::{self, Write as _},
+        hash::{BuildHasher, RandomState},
+        io::{Error, ErrorKind},
+        mem,
+        pin::Pin,
+        task::{Context, Poll, ready},
+    },
+};
+
+/// The `multipart/form-data` request body.
+///
+/// Parts are streamed one by one, so large files aren't loaded into memory.
+/// When sizes of all parts are known, the `Content-Length` header is set,
+/// otherwise the body is sent with the chunked encoding.
+///
+/// # Example
+///
+/// ```
+/// use areq::{
+///     Error,
+///     multipart::{Multipart, Part},
+///     prelude::*,
+/// };
+///
+/// async fn upload<C>(client: &mut C, file: &'static [u8]) -> Result<(), Error>
+/// where
+///     C: Client<areq::multipart::MultipartBody>,
+/// {
+///     let form = Multipart::new()
+///         .text("title", "report")
+///         .part("file", Part::bytes(file).file_name("report.pdf"));
+///
+///     let uri = "/upload".parse().expect("valid uri");
+///     client.post(uri, form).await?;
+///     Ok(())
+/// }
+/// ```
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<(String, Part)>,
+}
+
+impl Multipart {
+    /// Creates the empty multipart body with a random boundary.
+    pub fn new() -> Self {
+        let state = RandomState::new();
+        let a = state.hash_one(0);
+        let b = state.hash_one(1);
+        Self {
+            boundary: format!("areq-{a:016x}{b:016x}"),
+            parts: vec![],
+        }
+    }
+
+    /// Creates the empty multipart body with the given boundary.
+    ///
+    /// # Errors
+    /// Returns an error if the boundary isn't valid. As defined in
+    /// [RFC 2046](https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1),
+    /// it consists of 1 to 70 digits, letters or characters `'()+_,-./:=?`
+    /// and spaces, but can't end with a space.
+    pub fn with_boundary<S>(boundary: S) -> Result<Self, InvalidBoundary>
+    where
+        S: Into<String>,
+    {
+        let boundary = boundary.into();
+        if !is_valid_boundary(&boundary) {
+            return Err(InvalidBoundary);
+        }
+
+        Ok(Self {
+            boundary,
+            parts: vec![],
+        })
+    }
+
+    /// Returns the boundary.
+    #[inline]
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Adds the text field.
+    #[inline]
+    pub fn text<N, V>(self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.part(name, Part::text(value))
+    }
+
+    /// Adds the part with the field name.
+    #[inline]
+    pub fn part<N>(mut self, name: N, part: Part) -> Self
+    where
+        N: Into<String>,
+    {
+        self.parts.push((name.into(), part));
+        self
+    }
+
+    /// Returns the `Content-Type` header value with the boundary.
+    ///
+    /// The boundary is quoted if it contains special characters.
+    pub fn content_type(&self) -> HeaderValue {
+        let token = self
+            .boundary
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"'+_-.".contains(&b));
+
+        let value = if token {
+            format!("multipart/form-data; boundary={}", self.boundary)
+        } else {
+            format!("multipart/form-data; boundary=\"{}\"", self.boundary)
+        };
+
+        HeaderValue::try_from(value).expect("the boundary is validated")
+    }
+
+    fn into_multipart_body(self) -> MultipartBody {
+        let content_type = self.content_type();
+        let mut segments = VecDeque::with_capacity(self.parts.len() * 2 + 1);
+        let mut len = Some(0);
+        for (n, (name, part)) in self.parts.into_iter().enumerate() {
+            let mut head = String::new();
+            if n != 0 {
+                head.push_str("\r\n");
+            }
+
+            _ = write!(
+                head,
+                "--{}\r\ncontent-disposition: form-data; name=\"{}\"",
+                self.boundary,
+                escape(&name),
+            );
+
+            if let Some(file_name) = &part.file_name {
+                _ = write!(head, "; filename=\"{}\"", escape(file_name));
+            }
+
+            head.push_str("\r\n");
+            let mut head = head.into_bytes();
+            for (key, value) in &part.headers {
+                head.extend_from_slice(key.as_str().as_bytes());
+                head.extend_from_slice(b": ");
+                head.extend_from_slice(value.as_bytes());
+                head.extend_from_slice(b"\r\n");
+            }
+
+            head.extend_from_slice(b"\r\n");
+            len = len
+                .zip(part.len)
+                .map(|(len, part)| len + head.len() as u64 + part);
+
+            segments.push_back(Segment::Bytes(Bytes::from(head)));
+            segments.push_back(part.source);
+        }
+
+        let tail = if segments.is_empty() {
+            format!("--{}--\r\n", self.boundary)
+        } else {
+            format!("\r\n--{}--\r\n", self.boundary)
+        };
+
+        len = len.map(|len| len + tail.len() as u64);
+        segments.push_back(Segment::Bytes(Bytes::from(tail)));
+
+        MultipartBody {
+            segments,
+            content_type,
+            len,
+        }
+    }
+}
+
+impl Default for Multipart {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntoRequestBody for Multipart {
+    type Chunk = Bytes;
+    type Body = MultipartBody;
+
+    #[inline]
+    fn into_req_body(self) -> Self::Body {
+        self.into_multipart_body()
+    }
+
+    fn upd_req(req: &mut Request<Self::Body>) {
+        let content_type = req.body().content_type.clone();
+        let len = req.body().len;
+        let headers = req.headers_mut();
+        headers.insert(header::CONTENT_TYPE, content_type);
+        if let Some(len) = len {
+            headers.insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+        }
+    }
+}
+
+/// Checks the boundary consists of 1 to 70 allowed characters.
+fn is_valid_boundary(boundary: &str) -> bool {
+    let bchar = |b: u8| b.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&b);
+    (1..=70).contains(&boundary.len()) && !boundary.ends_with(' ') && boundary.bytes().all(bchar)
+}
+
+/// The error of an invalid multipart boundary.
+#[derive(Debug)]
+pub struct InvalidBoundary;
+
+impl From<InvalidBoundary> for Error {
+    fn from(e: InvalidBoundary) -> Self {
+        Self::new(ErrorKind::InvalidInput, e)
+    }
+}
+
+impl From<InvalidBoundary> for crate::Error {
+    fn from(e: InvalidBoundary) -> Self {
+        Self::new(crate::ErrorKind::Request, e)
+    }
+}
+
+impl fmt::Display for InvalidBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid multipart boundary")
+    }
+}
+
+impl error::Error for InvalidBoundary {}
+
+/// Escapes quotes and line breaks in a quoted parameter.
+fn escape(s: &str) -> String {
+    s.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// The part of the [multipart](Multipart) body.
+pub struct Part {
+    headers: HeaderMap,
+    file_name: Option<String>,
+    source: Segment,
+    len: Option<u64>,
+}
+
+impl Part {
+    fn new(source: Segment, len: Option<u64>) -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            file_name: None,
+            source,
+            len,
+        }
+    }
+
+    /// Creates the text part.
+    #[inline]
+    pub fn text<S>(s: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::bytes(s.into())
+    }
+
+    /// Creates the part from in-memory bytes.
+    #[inline]
+    pub fn bytes<B>(bytes: B) -> Self
+    where
+        B: Into<Bytes>,
+    {
+        let bytes = bytes.into();
+        let len = bytes.len() as u64;
+        Self::new(Segment::Bytes(bytes), Some(len))
+    }
+
+    /// Creates the part streamed from the body.
+    ///
+    /// The part size is known if the body has a [full](Hint::Full) size hint.
+    #[inline]
+    pub fn body(body: Boxed<'static>) -> Self {
+        let len = body.size_hint().size();
+        Self::new(Segment::Body(body), len)
+    }
+
+    /// Creates the part streamed from the reader, for example from a file.
+    ///
+    /// If the length is `None`, the whole body is sent chunked.
+    #[inline]
+    pub fn reader<R>(reader: R, len: Option<u64>) -> Self
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        let reader = ReadBody {
+            reader: Box::pin(reader),
+            buf: BytesMut::new(),
+            end: false,
+        };
+
+        Self::new(Segment::Body(Box::pin(reader)), len)
+    }
+
+    /// Sets the file name.
+    #[inline]
+    pub fn file_name<S>(mut self, file_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Sets the `Content-Type` header of the part.
+    #[inline]
+    pub fn content_type(self, value: HeaderValue) -> Self {
+        self.header(header::CONTENT_TYPE, value)
+    }
+
+    /// Inserts the header of the part.
+    #[inline]
+    pub fn header(mut self, key: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(key, value);
+        self
+    }
+}
+
+enum Segment {
+    Bytes(Bytes),
+    Body(Boxed<'static>),
+}
+
+/// The streaming [multipart](Multipart) body.
+pub struct MultipartBody {
+    segments: VecDeque<Segment>,
+    content_type: HeaderValue,
+    len: Option<u64>,
+}
+
+impl Body for MultipartBody {
+    type Chunk = Bytes;
+
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, Error>> {
+        loop {
+            match self.segments.front_mut()? {
+                Segment::Bytes(bytes) => {
+                    let bytes = mem::take(bytes);
+                    self.segments.pop_front();
+                    if !bytes.is_empty() {
+                        return Some(Ok(bytes));
+                    }
+                }
+                Segment::Body(body) => match body.chunk().await {
+                    Some(Ok(chunk)) if chunk.is_empty() => {}
+                    Some(res) => return Some(res),
+                    None => _ = self.segments.pop_front(),
+                },
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Hint {
+        Hint::Chunked {
+            end: self.segments.is_empty(),
+        }
+    }
+}
+
+struct ReadBody {
+    reader: Pin<Box<dyn AsyncRead + Send>>,
+    buf: BytesMut,
+    end: bool,
+}
+
+impl PollBody for ReadBody {
+    type Chunk = Bytes;
+
+    fn poll_chunk(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Chunk, Error>>> {
+        const CHUNK_SIZE: usize = 8 * 1024;
+
+        let me = self.get_mut();
+        if me.end {
+            return Poll::Ready(None);
+        }
+
+        me.buf.resize(CHUNK_SIZE, 0);
+        match ready!(me.reader.as_mut().poll_read(cx, &mut me.buf)) {
+            Ok(0) => {
+                me.end = true;
+                Poll::Ready(None)
+            }
+            Ok(n) => Poll::Ready(Some(Ok(me.buf.split_to(n).freeze()))),
+            Err(e) => Poll::Ready(Some(Err(e))),
+        }
+    }
+
+    fn size_hint(&self) -> Hint {
+        Hint::Chunked { end: self.end }
+    }
+}
+
+/// Parses the boundary parameter of a multipart content type.
+pub(crate) fn boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';');
+    let mime = params.next()?.trim();
+    let multipart = mime
+        .get(..10)
+        .is_some_and(|ty| ty.eq_ignore_ascii_case("multipart/"));
+
+    if !multipart {
+        return None;
+    }
+
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+/// The reader of a multipart body.
+///
+/// Parts are read sequentially as the body is received,
+/// so the whole body is never buffered.
+pub struct MultipartReader<B> {
+    body: B,
+    buf: BytesMut,
+    delimiter: Bytes,
+    state: State,
+}
+
+const MAX_HEADER_SIZE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Preamble,
+    Delimiter,
+    Body,
+    End,
+}
+
+impl<B> MultipartReader<B>
+where
+    B: Body,
+{
+    /// Creates the reader of the body with the boundary.
+    pub fn new(body: B, boundary: &str) -> Self {
+        let delimiter = format!("\r\n--{boundary}");
+
+        // the first delimiter may be at the start of the body,
+        // so prepend the line break to find it like any other
+        let mut buf = BytesMut::with_capacity(1024);
+        buf.put_slice(b"\r\n");
+
+        Self {
+            body,
+            buf,
+            delimiter: Bytes::from(delimiter),
+            state: State::Preamble,
+        }
+    }
+
+    /// Returns the next part.
+    ///
+    /// The unread rest of the previous part is skipped.
+    pub async fn next_part(&mut self) -> Option<Result<PartBody<'_, B>, Error>> {
+        match self.advance().await {
+            Ok(Some(headers)) => Some(Ok(PartBody {
+                reader: self,
+                headers,
+            })),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    async fn advance(&mut self) -> Result<Option<HeaderMap>, Error> {
+        loop {
+            match self.state {
+                State::Preamble => match find(&self.buf, &self.delimiter) {
+                    Some(n) => {
+                        self.buf.advance(n + self.delimiter.len());
+                        self.state = State::Delimiter;
+                    }
+                    None => {
+                        let keep = self.delimiter.len() - 1;
+                        self.buf.advance(self.buf.len().saturating_sub(keep));
+                        self.fill().await?;
+                    }
+                },
+                State::Delimiter => return self.read_headers().await,
+                State::Body => {
+                    while let Some(chunk) = self.read_body().await {
+                        chunk?;
+                    }
+                }
+                State::End => return Ok(None),
+            }
+        }
+    }
+
+    async fn read_headers(&mut self) -> Result<Option<HeaderMap>, Error> {
+        while self.buf.len() < 2 {
+            self.fill().await?;
+        }
+
+        // the closing delimiter
+        if self.buf.starts_with(b"--") {
+            self.state = State::End;
+            return Ok(None);
+        }
+
+        // skip the transport padding after the delimiter
+        let line = loop {
+            if let Some(n) = find(&self.buf, b"\r\n") {
+                break n;
+            }
+
+            if self.buf.len() > MAX_HEADER_SIZE {
+                return Err(invalid_part());
+            }
+
+            self.fill().await?;
+        };
+
+        self.buf.advance(line + 2);
+        loop {
+            let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+            match httparse::parse_headers(&self.buf, &mut headers) {
+                Ok(httparse::Status::Complete((n, parsed))) => {
+                    let mut map = HeaderMap::with_capacity(parsed.len());
+                    for header in parsed {
+                        let key = HeaderName::from_bytes(header.name.as_bytes())
+                            .map_err(|_| invalid_part())?;
+
+                        let value =
+                            HeaderValue::from_bytes(header.value).map_err(|_| invalid_part())?;
+
+                        map.append(key, value);
+                    }
+
+                    self.buf.advance(n);
+                    self.state = State::Body;
+                    return Ok(Some(map));
+                }
+                Ok(httparse::Status::Partial) if self.buf.len() <= MAX_HEADER_SIZE => {
+                    self.fill().await?;
+                }
+                _ => return Err(invalid_part()),
+            }
+        }
+    }
+
+    async fn read_body(&mut self) -> Option<Result<Bytes, Error>> {
+        loop {
+            if self.state != State::Body {
+                return None;
+            }
+
+            if let Some(n) = find(&self.buf, &self.delimiter) {
+                let chunk = self.buf.split_to(n).freeze();
+                self.buf.advance(self.delimiter.len());
+                self.state = State::Delimiter;
+                if chunk.is_empty() {
+                    return None;
+                }
+
+                return Some(Ok(chunk));
+            }
+
+            // the end of the buffer may be the start of the delimiter
+            let keep = self.delimiter.len() - 1;
+            if self.buf.len() > keep {
+                let n = self.buf.len() - keep;
+                return Some(Ok(self.buf.split_to(n).freeze()));
+            }
+
+            if let Err(e) = self.fill().await {
+                return Some(Err(e));
+            }
+        }
+    }
+
+    /// Reads the next chunk of the body into the buffer.
+    async fn fill(&mut self) -> Result<(), Error> {
+        match self.body.chunk().await {
+            Some(Ok(chunk)) => {
+                self.buf.put(chunk);
+                Ok(())
+            }
+            Some(Err(e)) => Err(e),
+            None => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "the multipart body ended before the closing delimiter",
+            )),
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn invalid_part() -> Error {
+    Error::new(ErrorKind::InvalidData, "invalid multipart headers")
+}
+
+/// The part of a [multipart reader](MultipartReader).
+///
+/// The part is a [body](Body) with its own headers.
+pub struct PartBody<'reader, B> {
+    reader: &'reader mut MultipartReader<B>,
+    headers: HeaderMap,
+}
+
+impl<B> PartBody<'_, B> {
+    /// Returns headers of the part.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+impl<B> Body for PartBody<'_, B>
+where
+    B: Body,
+{
+    type Chunk = Bytes;
+
+    #[inline]
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, Error>> {
+        self.reader.read_body().await
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Hint {
+        Hint::Chunked {
+            end: self.reader.state != State::Body,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{Response, body::BodyExt},
+        futures_lite::future,
+    };
+
+    fn encode(form: Multipart) -> Result<(Request<MultipartBody>, String), Error> {
+        let req = Request::new(http::Method::POST, http::Uri::from_static("/"), ());
+        let mut req = req.map_body(|()| form.into_req_body());
+        Multipart::upd_req(&mut req);
+        let body = future::block_on(req.body_mut().text())?;
+        Ok((req, body))
+    }
+
+    #[test]
+    fn encode_parts() -> Result<(), Error> {
+        let form = Multipart::with_boundary("b")?.text("title", "hi").part(
+            "file",
+            Part::bytes(&b"data"[..])
+                .file_name("a \"b\".txt")
+                .content_type(HeaderValue::from_static("text/plain")),
+        );
+
+        let (req, body) = encode(form)?;
+        let expected = "--b\r\n\
+            content-disposition: form-data; name=\"title\"\r\n\
+            \r\n\
+            hi\r\n\
+            --b\r\n\
+            content-disposition: form-data; name=\"file\"; filename=\"a %22b%22.txt\"\r\n\
+            content-type: text/plain\r\n\
+            \r\n\
+            data\r\n\
+            --b--\r\n";
+
+        assert_eq!(body, expected);
+        assert_eq!(
+            req.headers()[header::CONTENT_TYPE],
+            "multipart/form-data; boundary=b",
+        );
+
+        assert_eq!(
+            req.headers()[header::CONTENT_LENGTH],
+            expected.len().to_string(),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_length() -> Result<(), Error> {
+        let file: &[u8] = b"streamed";
+        let form = Multipart::with_boundary("b")?.part("file", Part::reader(file, None));
+        let (req, body) = encode(form)?;
+        assert!(!req.headers().contains_key(header::CONTENT_LENGTH));
+        assert_eq!(
+            body,
+            "--b\r\ncontent-disposition: form-data; name=\"file\"\r\n\r\nstreamed\r\n--b--\r\n",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_boundary() {
+        let long = "b".repeat(71);
+        for boundary in ["", "b ", "b\"", "b;", "b\r\n", "ь", &long] {
+            assert!(
+                Multipart::with_boundary(boundary).is_err(),
+                "the boundary {boundary:?} is invalid",
+            );
+        }
+
+        assert!(
+            Multipart::with_boundary("b".repeat(70)).is_ok(),
+            "the longest boundary"
+        );
+    }
+
+    #[test]
+    fn quoted_boundary() -> Result<(), Error> {
+        let form = Multipart::with_boundary("a b:c")?;
+        let content_type = form.content_type();
+        assert_eq!(content_type, "multipart/form-data; boundary=\"a b:c\"");
+
+        let value = content_type.to_str().expect("ascii");
+        assert_eq!(boundary(value), Some("a b:c"));
+        Ok(())
+    }
+
+    #[test]
+    fn read_parts() -> Result<(), Error> {
+        use {crate::body::Chunked, futures_lite::stream};
+
+        const BODY: &str = "preamble\r\n\
+            --b\r\n\
+            content-type: text/plain\r\n\
+            content-range: bytes 0-4/10\r\n\
+            \r\n\
+            hello\r\n\
+            --b  \r\n\
+            \r\n\
+            line --b\r\n-b\r\n\
+            --b--\r\n\
+            epilogue";
+
+        // split the body into small chunks to cross delimiters
+        let chunks = BODY
+            .as_bytes()
+            .chunks(3)
+            .map(|chunk| Ok(Bytes::from(chunk)));
+        let mut res = http::Response::new(Chunked(stream::iter(chunks)));
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("multipart/byteranges; boundary=\"b\""),
+        );
+
+        let mut reader = Response::new(res).multipart()?;
+        future::block_on(async {
+            let part = reader.next_part().await.expect("first part")?;
+            assert_eq!(part.headers()[header::CONTENT_TYPE], "text/plain");
+            assert_eq!(part.headers()[header::CONTENT_RANGE], "bytes 0-4/10");
+            assert_eq!(part.text().await?, "hello");
+
+            let part = reader.next_part().await.expect("second part")?;
+            assert!(part.headers().is_empty());
+            assert_eq!(part.text().await?, "line --b\r\n-b");
+
+            assert!(reader.next_part().await.is_none(), "no more parts");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn skip_unread() -> Result<(), Error> {
+        let body = "--b\r\n\r\nfirst\r\n--b\r\n\r\nsecond\r\n--b--";
+        let mut reader = MultipartReader::new(body, "b");
+        future::block_on(async {
+            let part = reader.next_part().await.expect("first part")?;
+            drop(part);
+
+            let part = reader.next_part().await.expect("second part")?;
+            assert_eq!(part.text().await?, "second");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn content_type_boundary() {
+        assert_eq!(boundary("multipart/mixed; boundary=abc"), Some("abc"));
+        assert_eq!(
+            boundary("Multipart/Form-Data;charset=utf-8;BOUNDARY=\"a b\""),
+            Some("a b")
+        );
+        assert_eq!(boundary("text/plain; boundary=abc"), None);
+        assert_eq!(boundary("multipart/mixed"), None);
+    }
+}
@@ -0,0 +1,787 @@
+//! The connection pool.
+
+use {
+    crate::{
+        addr::Address,
+        body::prelude::*,
+        client::Client,
+        error::Error,
+        proto::{Request, Response},
+    },
+    bytes::Bytes,
+    futures_lite::prelude::*,
+    http::HeaderMap,
+    std::{
+        collections::{HashMap, VecDeque},
+        future, io, mem,
+        sync::{
+            Arc, Mutex, MutexGuard,
+            atomic::{AtomicBool, Ordering},
+        },
+        task::{Context, Poll, Waker},
+        time::{Duration, Instant},
+    },
+};
+
+/// The trait to establish new connections for a [pool](Pool).
+///
+/// It's implemented for any closure that takes an [address](Address)
+/// and returns a client with its connection task, so any runtime
+/// connection method can be used as a connector.
+pub trait Connector {
+    /// The client type returned by the connector.
+    type Client;
+
+    /// Connects to the given address.
+    async fn connect(
+        &self,
+        addr: Address,
+    ) -> Result<(Self::Client, impl Future<Output = ()> + Send + 'static), Error>;
+}
+
+impl<F, U, C, T> Connector for F
+where
+    F: Fn(Address) -> U,
+    U: Future<Output = Result<(C, T), Error>>,
+    T: Future<Output = ()> + Send + 'static,
+{
+    type Client = C;
+
+    #[inline]
+    async fn connect(
+        &self,
+        addr: Address,
+    ) -> Result<(Self::Client, impl Future<Output = ()> + Send + 'static), Error> {
+        self(addr).await
+    }
+}
+
+/// The trait to spawn connection tasks in background.
+pub trait Spawn {
+    /// Spawns the task.
+    fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
+}
+
+/// The [pool](Pool) configuration.
+#[derive(Clone, Debug)]
+pub struct Config {
+    max_idle_per_host: usize,
+    idle_timeout: Option<Duration>,
+    max_connections: usize,
+}
+
+impl Config {
+    /// Sets the maximum number of idle connections kept per host.
+    #[inline]
+    pub fn max_idle_per_host(mut self, n: usize) -> Self {
+        self.max_idle_per_host = n;
+        self
+    }
+
+    /// Sets the time after which an idle connection is closed.
+    ///
+    /// If `None` is passed, idle connections never expire.
+    #[inline]
+    pub fn idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum number of open connections for all hosts.
+    ///
+    /// Once the limit is reached, a new request waits until one of
+    /// the connections becomes idle or closes.
+    #[inline]
+    pub fn max_connections(mut self, n: usize) -> Self {
+        self.max_connections = n;
+        self
+    }
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: usize::MAX,
+            idle_timeout: Some(Duration::from_secs(90)),
+            max_connections: usize::MAX,
+        }
+    }
+}
+
+/// The connection pool.
+///
+/// The pool is a [client](Client) that accepts requests with absolute
+/// URIs and dispatches them over connections keyed by the request
+/// [address](Address). Connections whose clients can't be cloned, like
+/// http/1.1, are reused once the previous response body is read to the
+/// end. Cloneable clients, like http/2, share a single connection
+/// between all requests to the same host.
+///
+/// To create a pool, you need a [connector](Connector) to establish new
+/// connections and a [spawner](Spawn) to run connection tasks in background.
+/// Runtime crates provide spawners, for example `areq_tokio::tokio::Spawner`
+/// or `areq_smol::smol::Spawner`.
+pub struct Pool<N, S>
+where
+    N: Connector,
+{
+    shared: Arc<Shared<N, S>>,
+    hosts: Arc<Hosts<N::Client>>,
+}
+
+struct Shared<N, S> {
+    connector: N,
+    spawn: S,
+}
+
+impl<N, S> Pool<N, S>
+where
+    N: Connector,
+{
+    /// Creates a new pool with the default [configuration](Config).
+    #[inline]
+    pub fn new(connector: N, spawn: S) -> Self {
+        Self::with_config(connector, spawn, Config::default())
+    }
+
+    /// Creates a new pool with the given [configuration](Config).
+    #[inline]
+    pub fn with_config(connector: N, spawn: S, conf: Config) -> Self {
+        Self {
+            shared: Arc::new(Shared { connector, spawn }),
+            hosts: Arc::new(Hosts::new(conf)),
+        }
+    }
+}
+
+impl<N, S> Clone for Pool<N, S>
+where
+    N: Connector,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            hosts: self.hosts.clone(),
+        }
+    }
+}
+
+impl<B, N, S> Client<B> for Pool<N, S>
+where
+    N: Connector<Client: Client<B>>,
+    S: Spawn,
+{
+    type Body = PoolBody<N::Client, <N::Client as Client<B>>::Body>;
+
+    async fn send(&mut self, req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let addr = Address::from_uri(req.uri())?;
+        let lease = match future::poll_fn(|cx| self.hosts.poll_checkout(&addr, cx)).await {
+            Some(lease) => lease,
+            None => {
+                let reserve = Reserve(&self.hosts.limit);
+                let (client, task) = self.shared.connector.connect(addr.clone()).await?;
+                reserve.forget();
+
+                let closed = Arc::new(AtomicBool::new(false));
+                let reusable = <N::Client as Client<B>>::is_reusable;
+                let release = Release {
+                    closed: closed.clone(),
+                    limit: self.hosts.limit.clone(),
+                };
+
+                self.shared.spawn.spawn(async move {
+                    let _release = release;
+                    task.await;
+                });
+
+                match client.try_clone() {
+                    Some(shared) => {
+                        let conn = Conn {
+                            client,
+                            closed,
+                            reusable,
+                        };
+
+                        self.hosts.share(&addr, conn);
+                        Lease::Shared(shared)
+                    }
+                    None => Lease::Owned(Conn {
+                        client,
+                        closed,
+                        reusable,
+                    }),
+                }
+            }
+        };
+
+        match lease {
+            Lease::Shared(mut client) => {
+                let res = client.send(req).await?;
+                Ok(res.map(|body| PoolBody { body, back: None }))
+            }
+            Lease::Owned(mut conn) => {
+                let res = conn.client.send(req).await?;
+                let back = Back {
+                    conn,
+                    addr,
+                    hosts: self.hosts.clone(),
+                };
+
+                Ok(res.map(|body| PoolBody::new(body, back)))
+            }
+        }
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+enum Lease<C> {
+    Shared(C),
+    Owned(Conn<C>),
+}
+
+struct Conn<C> {
+    client: C,
+    closed: Arc<AtomicBool>,
+    reusable: fn(&C) -> bool,
+}
+
+impl<C> Conn<C> {
+    /// Checks if the connection is closed or can't send more requests.
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire) || !(self.reusable)(&self.client)
+    }
+}
+
+struct Idle<C> {
+    conn: Conn<C>,
+    since: Instant,
+}
+
+struct Host<C> {
+    idle: VecDeque<Idle<C>>,
+    shared: Option<Conn<C>>,
+}
+
+impl<C> Host<C> {
+    fn new() -> Self {
+        Self {
+            idle: VecDeque::new(),
+            shared: None,
+        }
+    }
+
+    fn purge(&mut self, timeout: Option<Duration>) {
+        let now = Instant::now();
+        self.idle.retain(|idle| {
+            let expired = timeout.is_some_and(|timeout| now - idle.since >= timeout);
+            !expired && !idle.conn.is_closed()
+        });
+
+        if self.shared.as_ref().is_some_and(Conn::is_closed) {
+            self.shared = None;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.idle.is_empty() && self.shared.is_none()
+    }
+}
+
+struct Hosts<C> {
+    conf: Config,
+    map: Mutex<HashMap<Address, Host<C>>>,
+    limit: Arc<Limit>,
+}
+
+impl<C> Hosts<C> {
+    fn new(conf: Config) -> Self {
+        let limit = Limit {
+            max: conf.max_connections,
+            slots: Mutex::new(Slots {
+                open: 0,
+                waiters: vec![],
+            }),
+        };
+
+        Self {
+            conf,
+            map: Mutex::new(HashMap::new()),
+            limit: Arc::new(limit),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Address, Host<C>>> {
+        self.map.lock().expect("lock pool hosts")
+    }
+
+    /// Returns an available connection for the address or `None`
+    /// if a slot for a new connection is reserved.
+    fn poll_checkout<B>(&self, addr: &Address, cx: &mut Context<'_>) -> Poll<Option<Lease<C>>>
+    where
+        C: Client<B>,
+    {
+        let mut map = self.lock();
+        if let Some(host) = map.get_mut(addr) {
+            host.purge(self.conf.idle_timeout);
+
+            if let Some(client) = host
+                .shared
+                .as_ref()
+                .and_then(|conn| conn.client.try_clone())
+            {
+                return Poll::Ready(Some(Lease::Shared(client)));
+            }
+
+            if let Some(idle) = host.idle.pop_back() {
+                return Poll::Ready(Some(Lease::Owned(idle.conn)));
+            }
+        }
+
+        if self.limit.acquire(cx) {
+            return Poll::Ready(None);
+        }
+
+        // the limit is reached, so close the oldest idle connection
+        // of any host to free up space for a new one
+        let oldest = map
+            .values_mut()
+            .filter_map(|host| Some((host.idle.front()?.since, host)))
+            .min_by_key(|(since, _)| *since);
+
+        if let Some((_, host)) = oldest {
+            host.idle.pop_front();
+        }
+
+        map.retain(|_, host| !host.is_empty());
+        Poll::Pending
+    }
+
+    fn share(&self, addr: &Address, conn: Conn<C>) {
+        let mut map = self.lock();
+        let host = map.entry(addr.clone()).or_insert_with(Host::new);
+        if host.shared.as_ref().is_none_or(Conn::is_closed) {
+            host.shared = Some(conn);
+        }
+    }
+
+    fn put_back(&self, addr: Address, conn: Conn<C>) {
+        if conn.is_closed() {
+            return;
+        }
+
+        let mut map = self.lock();
+        let host = map.entry(addr).or_insert_with(Host::new);
+        host.purge(self.conf.idle_timeout);
+        if host.idle.len() < self.conf.max_idle_per_host {
+            let since = Instant::now();
+            host.idle.push_back(Idle { conn, since });
+            self.limit.wake();
+        }
+    }
+}
+
+/// Limits the number of open connections.
+struct Limit {
+    max: usize,
+    slots: Mutex<Slots>,
+}
+
+struct Slots {
+    open: usize,
+    waiters: Vec<Waker>,
+}
+
+impl Limit {
+    fn lock(&self) -> MutexGuard<'_, Slots> {
+        self.slots.lock().expect("lock pool slots")
+    }
+
+    /// Reserves a slot for a new connection or registers the waker
+    /// to be notified when a connection becomes idle or closes.
+    fn acquire(&self, cx: &mut Context<'_>) -> bool {
+        let mut slots = self.lock();
+        if slots.open < self.max {
+            slots.open += 1;
+            true
+        } else {
+            slots.waiters.push(cx.waker().clone());
+            false
+        }
+    }
+
+    fn release(&self) {
+        let mut slots = self.lock();
+        slots.open -= 1;
+        slots.waiters.drain(..).for_each(Waker::wake);
+    }
+
+    fn wake(&self) {
+        self.lock().waiters.drain(..).for_each(Waker::wake);
+    }
+}
+
+/// Releases the reserved connection slot if establishing a connection fails.
+struct Reserve<'pool>(&'pool Limit);
+
+impl Reserve<'_> {
+    fn forget(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for Reserve<'_> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+/// Marks the connection as closed and releases its slot once the task completes.
+struct Release {
+    closed: Arc<AtomicBool>,
+    limit: Arc<Limit>,
+}
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        self.limit.release();
+    }
+}
+
+struct Back<C> {
+    conn: Conn<C>,
+    addr: Address,
+    hosts: Arc<Hosts<C>>,
+}
+
+impl<C> Back<C> {
+    fn put(self) {
+        self.hosts.put_back(self.addr, self.conn);
+    }
+}
+
+/// The response body of a [pool](Pool).
+///
+/// Once the body is read to the end, its connection returns to the pool.
+/// If the body is dropped before that, the connection is closed.
+pub struct PoolBody<C, B> {
+    body: B,
+    back: Option<Back<C>>,
+}
+
+impl<C, B> PoolBody<C, B>
+where
+    B: Body,
+{
+    fn new(body: B, back: Back<C>) -> Self {
+        let mut body = Self {
+            body,
+            back: Some(back),
+        };
+
+        body.check_end();
+        body
+    }
+
+    fn check_end(&mut self) {
+        if self.body.size_hint().end() {
+            if let Some(back) = self.back.take() {
+                back.put();
+            }
+        }
+    }
+}
+
+impl<C, B> Body for PoolBody<C, B>
+where
+    B: Body<Chunk = Bytes>,
+{
+    type Chunk = Bytes;
+
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, io::Error>> {
+        let res = self.body.chunk().await;
+        match &res {
+            Some(Ok(_)) => self.check_end(),
+            Some(Err(_)) => self.back = None,
+            None => {
+                if let Some(back) = self.back.take() {
+                    back.put();
+                }
+            }
+        }
+
+        res
+    }
+
+    #[inline]
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        self.body.trailers().await
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Hint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        futures_lite::future,
+        http::Uri,
+        std::{pin::Pin, sync::atomic::AtomicUsize},
+    };
+
+    struct Mock {
+        id: usize,
+        shared: bool,
+        reusable: bool,
+    }
+
+    impl Client<()> for Mock {
+        type Body = Bytes;
+
+        async fn send(&mut self, _: Request<()>) -> Result<Response<Self::Body>, Error> {
+            let body = Bytes::from(self.id.to_string());
+            Ok(Response::new(http::Response::new(body)))
+        }
+
+        fn try_clone(&self) -> Option<Self> {
+            self.shared.then_some(Self {
+                id: self.id,
+                shared: true,
+                reusable: self.reusable,
+            })
+        }
+
+        fn is_reusable(&self) -> bool {
+            self.reusable
+        }
+    }
+
+    type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    #[derive(Clone, Default)]
+    struct Tasks(Arc<Mutex<Vec<Task>>>);
+
+    impl Tasks {
+        fn finish(&self) {
+            self.0.lock().expect("lock tasks").clear();
+        }
+    }
+
+    impl Spawn for Tasks {
+        fn spawn<F>(&self, task: F)
+        where
+            F: Future<Output = ()> + Send + 'static,
+        {
+            self.0.lock().expect("lock tasks").push(Box::pin(task));
+        }
+    }
+
+    fn pool(
+        shared: bool,
+        conf: Config,
+    ) -> (
+        Pool<impl Connector<Client = Mock>, Tasks>,
+        Arc<AtomicUsize>,
+        Tasks,
+    ) {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let connector = {
+            let connects = connects.clone();
+            move |_| {
+                let id = connects.fetch_add(1, Ordering::Relaxed);
+                let mock = Mock {
+                    id,
+                    shared,
+                    reusable: true,
+                };
+
+                future::ready(Ok((mock, future::pending())))
+            }
+        };
+
+        let tasks = Tasks::default();
+        let pool = Pool::with_config(connector, tasks.clone(), conf);
+        (pool, connects, tasks)
+    }
+
+    fn get<C>(client: &mut C, uri: &'static str) -> Result<C::Body, Error>
+    where
+        C: Client<()>,
+    {
+        let req = Request::get(Uri::from_static(uri), ());
+        let res = future::block_on(client.send(req))?;
+        Ok(http::Response::from(res).into_body())
+    }
+
+    #[test]
+    fn reuse_idle() -> Result<(), Error> {
+        let (mut pool, connects, _) = pool(false, Config::default());
+        for _ in 0..3 {
+            let body = get(&mut pool, "http://a")?;
+            assert_eq!(future::block_on(body.text())?, "0");
+        }
+
+        assert_eq!(connects.load(Ordering::Relaxed), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn busy_connection() -> Result<(), Error> {
+        let (mut pool, connects, _) = pool(false, Config::default());
+        let mut a = get(&mut pool, "http://a")?;
+        let b = get(&mut pool, "http://a")?;
+        assert_eq!(connects.load(Ordering::Relaxed), 2);
+
+        assert_eq!(future::block_on(b.text())?, "1");
+        let c = get(&mut pool, "http://a")?;
+        assert_eq!(future::block_on(c.text())?, "1");
+        assert_eq!(
+            future::block_on(a.chunk()).transpose()?,
+            Some(Bytes::from("0"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dropped_body() -> Result<(), Error> {
+        let (mut pool, connects, _) = pool(false, Config::default());
+        for _ in 0..2 {
+            drop(get(&mut pool, "http://a")?);
+        }
+
+        assert_eq!(connects.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn share() -> Result<(), Error> {
+        let (mut pool, connects, _) = pool(true, Config::default());
+        let a = get(&mut pool, "http://a")?;
+        let b = get(&mut pool, "http://a")?;
+        assert_eq!(future::block_on(a.text())?, "0");
+        assert_eq!(future::block_on(b.text())?, "0");
+        assert_eq!(connects.load(Ordering::Relaxed), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn hosts() -> Result<(), Error> {
+        let (mut pool, connects, _) = pool(false, Config::default());
+        for uri in [
+            "http://a",
+            "http://b",
+            "https://a",
+            "http://a:81",
+            "http://a/path",
+        ] {
+            future::block_on(get(&mut pool, uri)?.text())?;
+        }
+
+        assert_eq!(connects.load(Ordering::Relaxed), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn max_idle() -> Result<(), Error> {
+        let conf = Config::default().max_idle_per_host(0);
+        let (mut pool, connects, _) = pool(false, conf);
+        for _ in 0..2 {
+            future::block_on(get(&mut pool, "http://a")?.text())?;
+        }
+
+        assert_eq!(connects.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn idle_timeout() -> Result<(), Error> {
+        let conf = Config::default().idle_timeout(Some(Duration::ZERO));
+        let (mut pool, connects, _) = pool(false, conf);
+        for _ in 0..2 {
+            future::block_on(get(&mut pool, "http://a")?.text())?;
+        }
+
+        assert_eq!(connects.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn closed_connection() -> Result<(), Error> {
+        let (mut pool, connects, tasks) = pool(true, Config::default());
+        future::block_on(get(&mut pool, "http://a")?.text())?;
+        tasks.finish();
+        future::block_on(get(&mut pool, "http://a")?.text())?;
+        assert_eq!(connects.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn not_reusable() -> Result<(), Error> {
+        for shared in [false, true] {
+            let connects = Arc::new(AtomicUsize::new(0));
+            let connector = {
+                let connects = connects.clone();
+                move |_| {
+                    let id = connects.fetch_add(1, Ordering::Relaxed);
+                    let mock = Mock {
+                        id,
+                        shared,
+                        reusable: false,
+                    };
+
+                    future::ready(Ok((mock, future::pending())))
+                }
+            };
+
+            let mut pool = Pool::new(connector, Tasks::default());
+            for _ in 0..2 {
+                future::block_on(get(&mut pool, "http://a")?.text())?;
+            }
+
+            assert_eq!(connects.load(Ordering::Relaxed), 2, "shared: {shared}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_connections() -> Result<(), Error> {
+        let conf = Config::default().max_connections(1);
+        let (mut pool, connects, tasks) = pool(false, conf);
+
+        // the idle connection is evicted to connect to another host
+        future::block_on(get(&mut pool, "http://a")?.text())?;
+        let mut checkout = Box::pin(pool.send(Request::get(Uri::from_static("http://b"), ())));
+        assert!(future::block_on(future::poll_once(&mut checkout)).is_none());
+
+        tasks.finish();
+        let body = http::Response::from(future::block_on(checkout)?).into_body();
+        assert_eq!(future::block_on(body.text())?, "1");
+        assert_eq!(connects.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn pool_is_send() {
+        fn assert_send<S>(_: S)
+        where
+            S: Send,
+        {
+        }
+
+        let (pool, ..) = pool(false, Config::default());
+        assert_send(pool);
+    }
+}
@@ -3,10 +3,12 @@ use {
         addr::Address,
         body::{Boxed, IntoRequestBody, prelude::*},
         client::Client,
+        error::Error,
+        multipart::{self, MultipartReader},
     },
     futures_lite::prelude::*,
-    http::{HeaderMap, Method, StatusCode, Uri, Version, request, response},
-    std::{convert::Infallible, error, fmt, io},
+    http::{Extensions, HeaderMap, Method, StatusCode, Uri, Version, header, request, response},
+    std::io,
 };
 
 #[cfg(feature = "rtn")]
@@ -31,72 +33,7 @@ pub trait Handshake<I, B> {
     ) -> Result<(Self::Client, impl Future<Output = ()>), Error>;
 }
 
-/// The [handshake](Handshake) error type.
-#[derive(Debug)]
-pub enum Error {
-    Io(io::Error),
-    InvalidHost,
-    UnsupportedProtocol(Box<[u8]>),
-}
-
-impl Error {
-    pub fn try_into_io(self) -> Result<io::Error, Self> {
-        match self {
-            Self::Io(e) => Ok(e),
-            e => Err(e),
-        }
-    }
-}
-
-impl From<io::Error> for Error {
-    fn from(e: io::Error) -> Self {
-        Self::Io(e)
-    }
-}
-
-impl From<Error> for io::Error {
-    fn from(e: Error) -> Self {
-        e.try_into_io().unwrap_or_else(Self::other)
-    }
-}
-
-impl From<Infallible> for Error {
-    fn from(e: Infallible) -> Self {
-        match e {}
-    }
-}
-
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Io(e) => write!(f, "io error: {e}"),
-            Self::InvalidHost => write!(f, "invalid host"),
-            Self::UnsupportedProtocol(proto) => {
-                write!(f, "unsupported protocol: ")?;
-                for chunk in proto.utf8_chunks() {
-                    write!(f, "{}", chunk.valid())?;
-                    if !chunk.invalid().is_empty() {
-                        write!(f, "{}", char::REPLACEMENT_CHARACTER)?;
-                    }
-                }
-
-                Ok(())
-            }
-        }
-    }
-}
-
-impl error::Error for Error {
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        match self {
-            Self::Io(e) => Some(e),
-            Self::InvalidHost => None,
-            Self::UnsupportedProtocol(_) => None,
-        }
-    }
-}
-
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Request<B = Boxed<'static>> {
     head: request::Parts,
     body: B,
@@ -190,6 +127,10 @@ impl<B> Request<B> {
         &self.head.uri
     }
 
+    pub fn uri_mut(&mut self) -> &mut Uri {
+        &mut self.head.uri
+    }
+
     pub fn headers(&self) -> &HeaderMap {
         &self.head.headers
     }
@@ -198,6 +139,22 @@ impl<B> Request<B> {
         &mut self.head.headers
     }
 
+    pub fn extensions(&self) -> &Extensions {
+        &self.head.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.head.extensions
+    }
+
+    pub fn body(&self) -> &B {
+        &self.body
+    }
+
+    pub fn body_mut(&mut self) -> &mut B {
+        &mut self.body
+    }
+
     pub fn map_body<F, C>(self, f: F) -> Request<C>
     where
         F: FnOnce(B) -> C,
@@ -250,6 +207,20 @@ impl<B> Response<B> {
         self.head.status
     }
 
+    /// Turns the client or server error status into an [error](Error).
+    ///
+    /// # Errors
+    /// Returns the [status](crate::ErrorKind::Status) error
+    /// if the status is `4xx` or `5xx`.
+    pub fn error_for_status(self) -> Result<Self, Error> {
+        let status = self.status();
+        if status.is_client_error() || status.is_server_error() {
+            Err(Error::from_status(status))
+        } else {
+            Ok(self)
+        }
+    }
+
     pub fn version(&self) -> Version {
         self.head.version
     }
@@ -262,6 +233,14 @@ impl<B> Response<B> {
         &mut self.head.headers
     }
 
+    pub fn extensions(&self) -> &Extensions {
+        &self.head.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.head.extensions
+    }
+
     pub fn map<F, C>(self, f: F) -> Response<C>
     where
         F: FnOnce(B) -> C,
@@ -273,6 +252,54 @@ impl<B> Response<B> {
     }
 }
 
+impl<B> Response<B>
+where
+    B: IntoBody,
+{
+    /// Reads the multipart response body part by part.
+    ///
+    /// The boundary is taken from the `Content-Type` header,
+    /// so it can be any `multipart/*` type, like `multipart/mixed`
+    /// or `multipart/byteranges`.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidData`](io::ErrorKind::InvalidData) error
+    /// if the response isn't multipart.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use areq::{Error, Response, prelude::*};
+    ///
+    /// async fn parts<B>(res: Response<B>) -> Result<Vec<String>, Error>
+    /// where
+    ///     B: IntoBody,
+    /// {
+    ///     let mut reader = res.multipart()?;
+    ///     let mut texts = vec![];
+    ///     while let Some(part) = reader.next_part().await {
+    ///         texts.push(part?.text().await?);
+    ///     }
+    ///
+    ///     Ok(texts)
+    /// }
+    /// ```
+    pub fn multipart(self) -> Result<MultipartReader<B::Body>, io::Error> {
+        let boundary = self
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(multipart::boundary)
+            .map(str::to_owned)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "the response isn't multipart")
+            })?;
+
+        let reader = MultipartReader::new(self.into_body(), &boundary);
+        Ok(reader)
+    }
+}
+
 impl<B> From<Response<B>> for http::Response<B> {
     fn from(Response { head, body }: Response<B>) -> Self {
         Self::from_parts(head, body)
@@ -1,4 +1,4 @@
-use crate::proto::{Error, Handshake};
+use crate::{error::Error, proto::Handshake};
 
 pub trait HandshakeWith<I, B>:
     Handshake<I, B, handshake(..): Future<Output = Result<(Self::Client, Self::Task), Error>>>
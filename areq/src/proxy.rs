@@ -0,0 +1,411 @@
+//! The http proxy.
+
+use {
+    crate::{
+        addr::Address,
+        auth::basic_auth,
+        client::Client,
+        error::Error,
+        proto::{Handshake, Request, Response, Session},
+    },
+    futures_lite::prelude::*,
+    http::{
+        HeaderValue, StatusCode, Uri, header,
+        uri::{Authority, PathAndQuery, Scheme},
+    },
+    std::{error, fmt, io},
+};
+
+/// The [handshake](Handshake) through an http proxy.
+///
+/// The handshake takes a [session](Session) to the proxy server and
+/// establishes a session to the target address over it. Then the inner
+/// handshake is performed.
+///
+/// For a secure target the proxy is asked to open a tunnel with the
+/// `CONNECT` method, so the inner handshake gets the raw connection to
+/// the target and can start TLS over it. For a plain http target the
+/// requests are forwarded through the proxy with absolute-form targets,
+/// unless the tunnel is [forced](Proxy::tunnel).
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "http1")] {
+/// use areq::{Address, http1::Http1, proxy::Proxy};
+///
+/// let target = Address::http("example.com");
+/// let handshake = Proxy::new(target, Http1::default()).basic_auth("user", "pass");
+/// # }
+/// ```
+pub struct Proxy<H> {
+    inner: H,
+    target: Address,
+    auth: Option<HeaderValue>,
+    tunnel: bool,
+}
+
+impl<H> Proxy<H> {
+    /// Creates the proxy handshake to the target address.
+    #[inline]
+    pub fn new(target: Address, inner: H) -> Self {
+        let tunnel = target.secure;
+        Self {
+            inner,
+            target,
+            auth: None,
+            tunnel,
+        }
+    }
+
+    /// Always opens a tunnel, even for a plain http target.
+    #[inline]
+    pub fn tunnel(mut self) -> Self {
+        self.tunnel = true;
+        self
+    }
+
+    /// Sets the `Proxy-Authorization` header value.
+    #[inline]
+    pub fn auth(mut self, value: HeaderValue) -> Self {
+        self.auth = Some(value);
+        self
+    }
+
+    /// Sets the `Proxy-Authorization` header with the basic scheme.
+    #[inline]
+    pub fn basic_auth(self, user: &str, pass: &str) -> Self {
+        self.auth(basic_auth(user, pass))
+    }
+}
+
+impl<I, B, H> Handshake<I, B> for Proxy<H>
+where
+    I: AsyncRead + AsyncWrite + Unpin,
+    H: Handshake<I, B>,
+{
+    type Client = ProxyClient<H::Client>;
+
+    async fn handshake(
+        self,
+        se: Session<I>,
+    ) -> Result<(Self::Client, impl Future<Output = ()>), Error> {
+        let Session { mut io, .. } = se;
+        let forward = if self.tunnel {
+            connect(&mut io, &self.target, self.auth.as_ref())
+                .await
+                .map_err(Error::connect)?;
+            None
+        } else {
+            let scheme = if self.target.secure {
+                Scheme::HTTPS
+            } else {
+                Scheme::HTTP
+            };
+
+            let authority = Authority::from_maybe_shared(self.target.host_value())
+                .map_err(|_| Error::invalid_host())?;
+
+            Some(Forward {
+                scheme,
+                authority,
+                auth: self.auth,
+            })
+        };
+
+        let se = Session {
+            addr: self.target,
+            io,
+        };
+
+        let (client, conn) = self.inner.handshake(se).await?;
+        let client = ProxyClient { client, forward };
+        Ok((client, conn))
+    }
+}
+
+/// Sends the `CONNECT` request and reads the proxy response.
+///
+/// The response is read byte by byte, so no data
+/// from the target is consumed from the connection.
+async fn connect<I>(
+    io: &mut I,
+    target: &Address,
+    auth: Option<&HeaderValue>,
+) -> Result<(), io::Error>
+where
+    I: AsyncRead + AsyncWrite + Unpin,
+{
+    const MAX_HEADER_SIZE: usize = 8 * 1024;
+    const MAX_HEADERS: usize = 64;
+
+    // the request target of `CONNECT` always has a port,
+    // an ipv6 host is displayed in brackets
+    let host = format!("{}:{}", target.host, target.port);
+    let host = host.as_bytes();
+    let mut req = Vec::with_capacity(128);
+    req.extend_from_slice(b"CONNECT ");
+    req.extend_from_slice(host);
+    req.extend_from_slice(b" HTTP/1.1\r\nhost: ");
+    req.extend_from_slice(host);
+    if let Some(auth) = auth {
+        req.extend_from_slice(b"\r\nproxy-authorization: ");
+        req.extend_from_slice(auth.as_bytes());
+    }
+
+    req.extend_from_slice(b"\r\n\r\n");
+    io.write_all(&req).await?;
+    io.flush().await?;
+
+    let mut head = Vec::with_capacity(128);
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() == MAX_HEADER_SIZE {
+            return Err(ProxyError::InvalidResponse.into());
+        }
+
+        let mut byte = 0;
+        let n = io.read(std::slice::from_mut(&mut byte)).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        head.push(byte);
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut res = httparse::Response::new(&mut headers);
+    let code = match res.parse(&head) {
+        Ok(httparse::Status::Complete(_)) => res.code,
+        _ => None,
+    };
+
+    let status = code
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .ok_or(ProxyError::InvalidResponse)?;
+
+    if !status.is_success() {
+        return Err(ProxyError::Status(status).into());
+    }
+
+    Ok(())
+}
+
+/// The proxy error.
+#[derive(Debug)]
+pub enum ProxyError {
+    /// The proxy refused to open a tunnel.
+    Status(StatusCode),
+    /// The proxy sent an invalid response.
+    InvalidResponse,
+}
+
+impl From<ProxyError> for io::Error {
+    fn from(e: ProxyError) -> Self {
+        let kind = match e {
+            ProxyError::Status(_) => io::ErrorKind::ConnectionRefused,
+            ProxyError::InvalidResponse => io::ErrorKind::InvalidData,
+        };
+
+        Self::new(kind, e)
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "proxy responded with status {status}"),
+            Self::InvalidResponse => write!(f, "invalid proxy response"),
+        }
+    }
+}
+
+impl error::Error for ProxyError {}
+
+#[derive(Clone)]
+struct Forward {
+    scheme: Scheme,
+    authority: Authority,
+    auth: Option<HeaderValue>,
+}
+
+/// The client created by the [`Proxy`] handshake.
+///
+/// When requests are forwarded, their uris are turned into
+/// the absolute form and the `Proxy-Authorization` header is added.
+pub struct ProxyClient<C> {
+    client: C,
+    forward: Option<Forward>,
+}
+
+impl<C> ProxyClient<C> {
+    /// Returns the inner client.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+}
+
+impl<B, C> Client<B> for ProxyClient<C>
+where
+    C: Client<B>,
+{
+    type Body = C::Body;
+
+    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        if let Some(forward) = &self.forward {
+            if req.uri().authority().is_none() {
+                let path = req
+                    .uri()
+                    .path_and_query()
+                    .cloned()
+                    .unwrap_or_else(|| PathAndQuery::from_static("/"));
+
+                *req.uri_mut() = Uri::builder()
+                    .scheme(forward.scheme.clone())
+                    .authority(forward.authority.clone())
+                    .path_and_query(path)
+                    .build()
+                    .map_err(io::Error::other)?;
+            }
+
+            if let Some(auth) = &forward.auth {
+                req.headers_mut()
+                    .insert(header::PROXY_AUTHORIZATION, auth.clone());
+            }
+        }
+
+        self.client.send(req).await
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            client: self.client.try_clone()?,
+            forward: self.forward.clone(),
+        })
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test::{Capture, Captured, Mock},
+        futures_lite::future,
+        std::net::Ipv6Addr,
+    };
+
+    fn session(input: &'static str) -> Session<Mock> {
+        Session {
+            addr: Address::http("proxy.local"),
+            io: Mock::new(input.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn tunnel() -> Result<(), Error> {
+        let se = session("HTTP/1.1 200 Connection established\r\n\r\ntarget data");
+        let target = Address::https("example.com");
+        let proxy = Proxy::new(target.clone(), Capture).auth(HeaderValue::from_static("token"));
+        let (client, _) = future::block_on(proxy.handshake(se))?;
+        let Captured { se, .. } = client.into_inner();
+
+        assert_eq!(se.addr, target);
+        assert_eq!(
+            se.io.output,
+            b"CONNECT example.com:443 HTTP/1.1\r\n\
+            host: example.com:443\r\n\
+            proxy-authorization: token\r\n\r\n",
+        );
+
+        assert_eq!(se.io.rest(), b"target data");
+        Ok(())
+    }
+
+    #[test]
+    fn tunnel_ipv6() -> Result<(), Error> {
+        let se = session("HTTP/1.1 200 Connection established\r\n\r\n");
+        let target = Address::https(Ipv6Addr::LOCALHOST);
+        let (client, _) = future::block_on(Proxy::new(target, Capture).handshake(se))?;
+        let Captured { se, .. } = client.into_inner();
+        assert_eq!(
+            se.io.output,
+            b"CONNECT [::1]:443 HTTP/1.1\r\nhost: [::1]:443\r\n\r\n",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn refused() {
+        let se = session("HTTP/1.1 407 Proxy Authentication Required\r\n\r\n");
+        let proxy = Proxy::new(Address::https("example.com"), Capture);
+        let Err(e) = future::block_on(proxy.handshake(se)) else {
+            panic!("the tunnel should be refused");
+        };
+
+        assert!(e.is_connect());
+        let e = e.try_into_io().expect("io error");
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+
+        let se = session("HTTP/1.1 200 OK\r\n");
+        let proxy = Proxy::new(Address::https("example.com"), Capture);
+        let Err(e) = future::block_on(proxy.handshake(se)) else {
+            panic!("the response is incomplete");
+        };
+
+        assert!(e.is_connect());
+        let e = e.try_into_io().expect("io error");
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn forward() -> Result<(), Error> {
+        let se = session("");
+        let target = Address::http("example.com");
+        let proxy = Proxy::new(target.clone(), Capture).basic_auth("user", "pass");
+        let (mut client, _) = future::block_on(proxy.handshake(se))?;
+
+        let uri = Uri::from_static("/path?query");
+        future::block_on(client.send(Request::get(uri, ())))?;
+
+        let Captured { se, reqs } = client.into_inner();
+        assert!(se.io.output.is_empty(), "no tunnel");
+        assert_eq!(se.addr, target);
+
+        let [req] = &reqs[..] else {
+            panic!("one request should be sent");
+        };
+
+        assert_eq!(req.uri(), "http://example.com/path?query");
+        assert_eq!(
+            req.headers()[header::PROXY_AUTHORIZATION],
+            "Basic dXNlcjpwYXNz",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn forward_ipv6() -> Result<(), Error> {
+        let se = session("");
+        let target = Address::http(Ipv6Addr::LOCALHOST);
+        let (mut client, _) = future::block_on(Proxy::new(target, Capture).handshake(se))?;
+
+        let uri = Uri::from_static("/path");
+        future::block_on(client.send(Request::get(uri, ())))?;
+
+        let Captured { reqs, .. } = client.into_inner();
+        let [req] = &reqs[..] else {
+            panic!("one request should be sent");
+        };
+
+        assert_eq!(req.uri(), "http://[::1]/path");
+        Ok(())
+    }
+}
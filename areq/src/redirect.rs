@@ -0,0 +1,645 @@
+//! The redirect-following client.
+
+use {
+    crate::{
+        addr::Address,
+        body,
+        client::Client,
+        error::{Error, ErrorKind},
+        layer::Layer,
+        proto::{Request, Response},
+    },
+    http::{HeaderMap, Method, StatusCode, Uri, header},
+    std::{fmt, sync::Arc},
+    url::Url,
+};
+
+/// The redirect policy.
+///
+/// By default, up to 10 redirects are followed to any origin.
+#[derive(Clone)]
+pub struct Policy {
+    max_hops: usize,
+    same_origin: bool,
+    filter: Option<Arc<Filter>>,
+}
+
+type Filter = dyn Fn(&Attempt<'_>) -> bool + Send + Sync;
+
+impl Policy {
+    /// Creates a policy that doesn't follow redirects.
+    #[inline]
+    pub fn none() -> Self {
+        Self::default().max_hops(0)
+    }
+
+    /// Sets the maximum number of redirects to follow.
+    #[inline]
+    pub fn max_hops(mut self, n: usize) -> Self {
+        self.max_hops = n;
+        self
+    }
+
+    /// Allows to follow only redirects to the same origin.
+    #[inline]
+    pub fn same_origin(mut self, same_origin: bool) -> Self {
+        self.same_origin = same_origin;
+        self
+    }
+
+    /// Sets the custom predicate to decide whether to follow a redirect.
+    #[inline]
+    pub fn filter<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Attempt<'_>) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(f));
+        self
+    }
+
+    fn allows(&self, attempt: &Attempt<'_>) -> bool {
+        attempt.previous.len() < self.max_hops
+            && (!self.same_origin || !attempt.cross_origin)
+            && self.filter.as_ref().is_none_or(|f| f(attempt))
+    }
+}
+
+impl Default for Policy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_hops: 10,
+            same_origin: false,
+            filter: None,
+        }
+    }
+}
+
+impl fmt::Debug for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Policy")
+            .field("max_hops", &self.max_hops)
+            .field("same_origin", &self.same_origin)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> Layer<C> for Policy {
+    type Client = Redirect<C>;
+
+    #[inline]
+    fn layer(&self, client: C) -> Self::Client {
+        Redirect::new(client).policy(self.clone())
+    }
+}
+
+/// The redirect attempt passed to a [policy](Policy) filter.
+pub struct Attempt<'req> {
+    status: StatusCode,
+    from: &'req Uri,
+    to: &'req Uri,
+    previous: &'req [Uri],
+    cross_origin: bool,
+}
+
+impl Attempt<'_> {
+    /// Returns the redirect response status.
+    #[inline]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the uri of the redirected request.
+    #[inline]
+    pub fn from(&self) -> &Uri {
+        self.from
+    }
+
+    /// Returns the uri of the redirect location.
+    #[inline]
+    pub fn to(&self) -> &Uri {
+        self.to
+    }
+
+    /// Returns the uris of previously redirected requests.
+    #[inline]
+    pub fn previous(&self) -> &[Uri] {
+        self.previous
+    }
+
+    /// Checks if the redirect leads to another origin.
+    #[inline]
+    pub fn is_cross_origin(&self) -> bool {
+        self.cross_origin
+    }
+}
+
+/// The redirect chain stored in the final [response](Response) extensions.
+#[derive(Clone, Debug, Default)]
+pub struct Chain(Vec<Uri>);
+
+impl Chain {
+    /// Returns the uris of all redirected requests in order.
+    ///
+    /// The final request uri isn't included.
+    #[inline]
+    pub fn uris(&self) -> &[Uri] {
+        &self.0
+    }
+}
+
+/// The trait to get a client for a cross-origin redirect.
+///
+/// Clients like [`H1`](crate::http1::H1) are bound to a single
+/// address, so to follow a redirect to another origin a new
+/// connection is required.
+///
+/// The trait is implemented for:
+/// * `()`, cross-origin redirects are not followed.
+/// * [`Reuse`], the current client is used for any origin.
+/// * a closure that takes an [address](Address) and returns
+///   a future with a new client.
+pub trait Reconnect<C> {
+    /// Replaces the client with one connected to the given address.
+    ///
+    /// Returns `false` if the redirect can't be followed.
+    async fn reconnect(&mut self, client: &mut C, addr: Address) -> Result<bool, Error>;
+}
+
+impl<C> Reconnect<C> for () {
+    #[inline]
+    async fn reconnect(&mut self, _: &mut C, _: Address) -> Result<bool, Error> {
+        Ok(false)
+    }
+}
+
+/// Uses the same client for any origin.
+///
+/// It's suitable for clients that dispatch requests by their uri,
+/// like a [pool](crate::pool::Pool).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Reuse;
+
+impl<C> Reconnect<C> for Reuse {
+    #[inline]
+    async fn reconnect(&mut self, _: &mut C, _: Address) -> Result<bool, Error> {
+        Ok(true)
+    }
+}
+
+impl<F, U, C> Reconnect<C> for F
+where
+    F: FnMut(Address) -> U,
+    U: Future<Output = Result<C, Error>>,
+{
+    #[inline]
+    async fn reconnect(&mut self, client: &mut C, addr: Address) -> Result<bool, Error> {
+        *client = self(addr).await?;
+        Ok(true)
+    }
+}
+
+/// The client that follows redirects.
+///
+/// Since a request may be sent several times, its body must be
+/// cloneable. When a redirect requires changing the method to
+/// `GET`, the body is replaced with the default one.
+///
+/// The chain of redirected uris is available from the final
+/// response [extensions](Response::extensions) as [`Chain`].
+pub struct Redirect<C, R = ()> {
+    client: C,
+    reconnect: R,
+    policy: Policy,
+}
+
+impl<C> Redirect<C> {
+    /// Wraps the client with the default [policy](Policy).
+    #[inline]
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            reconnect: (),
+            policy: Policy::default(),
+        }
+    }
+}
+
+impl<C, R> Redirect<C, R> {
+    /// Sets the redirect [policy](Policy).
+    #[inline]
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the way to [reconnect](Reconnect) on cross-origin redirects.
+    #[inline]
+    pub fn reconnect<S>(self, reconnect: S) -> Redirect<C, S> {
+        Redirect {
+            client: self.client,
+            reconnect,
+            policy: self.policy,
+        }
+    }
+
+    /// Returns the inner client.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+}
+
+impl<B, C, R> Client<B> for Redirect<C, R>
+where
+    B: Clone + Default,
+    C: Client<B>,
+    R: Reconnect<C>,
+{
+    type Body = C::Body;
+
+    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let mut chain = vec![];
+        loop {
+            let next = (chain.len() < self.policy.max_hops).then(|| req.clone());
+            let res = self.client.send(req).await?;
+            let Some(mut next) = next else {
+                return Ok(finish(res, chain));
+            };
+
+            let Some(to) = location(&res, next.uri()) else {
+                return Ok(finish(res, chain));
+            };
+
+            let from = next.uri();
+            let to_addr = Address::from_uri(&to).ok();
+            let cross_origin = to_addr.is_some() && Address::from_uri(from).ok() != to_addr;
+            let status = res.status();
+            let attempt = Attempt {
+                status,
+                from,
+                to: &to,
+                previous: &chain,
+                cross_origin,
+            };
+
+            if !self.policy.allows(&attempt) {
+                return Ok(finish(res, chain));
+            }
+
+            if let Some(addr) = to_addr.filter(|_| cross_origin) {
+                let reconnected = self
+                    .reconnect
+                    .reconnect(&mut self.client, addr)
+                    .await
+                    .map_err(|e| Error::new(ErrorKind::Redirect, e))?;
+
+                if !reconnected {
+                    return Ok(finish(res, chain));
+                }
+
+                // the body belongs to the replaced connection,
+                // so there is no point in reading it
+                drop(res);
+                remove_sensitive_headers(next.headers_mut());
+            } else {
+                // read the redirect body, so the connection can be reused,
+                // the body is discarded anyway, so a failed one doesn't stop redirecting
+                _ = body::drain(http::Response::from(res).into_body()).await;
+            }
+
+            let method = next.method();
+            let get = match status {
+                StatusCode::SEE_OTHER => method != Method::HEAD,
+                StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => method == Method::POST,
+                _ => false,
+            };
+
+            if get {
+                *next.method_mut() = Method::GET;
+                *next.body_mut() = B::default();
+                remove_content_headers(next.headers_mut());
+            }
+
+            chain.push(next.uri().clone());
+            *next.uri_mut() = to;
+            req = next;
+        }
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+fn finish<B>(mut res: Response<B>, chain: Vec<Uri>) -> Response<B> {
+    res.extensions_mut().insert(Chain(chain));
+    res
+}
+
+fn location<B>(res: &Response<B>, base: &Uri) -> Option<Uri> {
+    if !matches!(
+        res.status(),
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return None;
+    }
+
+    let location = res.headers().get(header::LOCATION)?.to_str().ok()?;
+    resolve(base, location)
+}
+
+/// Resolves the redirect location relative to the base uri.
+fn resolve(base: &Uri, location: &str) -> Option<Uri> {
+    // a relative base has no scheme and authority,
+    // so resolve against a placeholder origin and keep only the path
+    let relative = base.scheme().is_none() || base.authority().is_none();
+    let base = if relative {
+        let path = base.path_and_query().map_or("/", |p| p.as_str());
+        Url::parse("http://localhost").ok()?.join(path).ok()?
+    } else {
+        Url::parse(&base.to_string()).ok()?
+    };
+
+    let mut url = base.join(location).ok()?;
+    url.set_fragment(None);
+
+    let absolute = location.contains("://") || location.starts_with("//");
+    if relative && !absolute {
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_owned(),
+        };
+
+        path.parse().ok()
+    } else {
+        url.as_str().parse().ok()
+    }
+}
+
+fn remove_sensitive_headers(headers: &mut HeaderMap) {
+    headers.remove(header::AUTHORIZATION);
+    headers.remove(header::COOKIE);
+    headers.remove(header::PROXY_AUTHORIZATION);
+    headers.remove(header::HOST);
+}
+
+fn remove_content_headers(headers: &mut HeaderMap) {
+    headers.remove(header::CONTENT_TYPE);
+    headers.remove(header::CONTENT_LENGTH);
+    headers.remove(header::CONTENT_ENCODING);
+    headers.remove(header::TRANSFER_ENCODING);
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, bytes::Bytes, futures_lite::future, http::HeaderValue};
+
+    /// Records requests and responds with redirects by the request path.
+    #[derive(Default)]
+    struct Mock {
+        host: &'static str,
+        sent: Vec<(Method, Uri, HeaderMap, String)>,
+    }
+
+    impl Client<String> for Mock {
+        type Body = Bytes;
+
+        async fn send(&mut self, req: Request<String>) -> Result<Response<Self::Body>, Error> {
+            let method = req.method().clone();
+            let uri = req.uri().clone();
+            let headers = req.headers().clone();
+            let body = req.body().clone();
+            self.sent.push((method, uri.clone(), headers, body));
+
+            let redirect = |status: u16, location: &str| {
+                let mut res = http::Response::new(Bytes::from("redirect"));
+                *res.status_mut() = StatusCode::from_u16(status).expect("valid status");
+                res.headers_mut().insert(
+                    header::LOCATION,
+                    HeaderValue::from_str(location).expect("valid location"),
+                );
+
+                Response::new(res)
+            };
+
+            let res = match uri.path() {
+                "/301" => redirect(301, "/ok"),
+                "/302" => redirect(302, "/ok"),
+                "/303" => redirect(303, "/ok"),
+                "/307" => redirect(307, "/ok"),
+                "/308" => redirect(308, "ok"),
+                "/loop" => redirect(302, "/loop"),
+                "/cross" => redirect(302, "http://b/ok"),
+                _ => Response::new(http::Response::new(Bytes::from(self.host))),
+            };
+
+            Ok(res)
+        }
+    }
+
+    fn send<C>(
+        client: &mut C,
+        method: Method,
+        uri: &'static str,
+    ) -> Result<Response<C::Body>, Error>
+    where
+        C: Client<String>,
+    {
+        let mut req = Request::new(method, Uri::from_static(uri), String::from("body"));
+        req.headers_mut()
+            .insert(header::AUTHORIZATION, HeaderValue::from_static("secret"));
+
+        req.headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+
+        future::block_on(client.send(req))
+    }
+
+    fn chain<B>(res: &Response<B>) -> Vec<&str> {
+        let chain = res.extensions().get::<Chain>().expect("redirect chain");
+        chain.uris().iter().map(Uri::path).collect()
+    }
+
+    #[test]
+    fn follow() -> Result<(), Error> {
+        let mut client = Redirect::new(Mock::default());
+        for (status, method) in [
+            ("/301", Method::POST),
+            ("/302", Method::POST),
+            ("/303", Method::PUT),
+            ("/307", Method::POST),
+            ("/308", Method::POST),
+        ] {
+            let res = send(&mut client, method.clone(), status)?;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(chain(&res), [status]);
+
+            let (expected_method, expected_body) = match status {
+                "/307" | "/308" => (method, "body"),
+                _ => (Method::GET, ""),
+            };
+
+            let (method, uri, headers, body) =
+                client.client.sent.last().expect("redirected request");
+
+            assert_eq!(method, expected_method);
+            assert_eq!(uri, "/ok");
+            assert_eq!(body, expected_body);
+            assert_eq!(headers.contains_key(header::CONTENT_TYPE), body == "body");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn see_other_head() -> Result<(), Error> {
+        let mut client = Redirect::new(Mock::default());
+        send(&mut client, Method::HEAD, "/303")?;
+        let (method, ..) = client.client.sent.last().expect("redirected request");
+        assert_eq!(method, Method::HEAD);
+        Ok(())
+    }
+
+    #[test]
+    fn max_hops() -> Result<(), Error> {
+        let mut client = Redirect::new(Mock::default()).policy(Policy::default().max_hops(3));
+        let res = send(&mut client, Method::GET, "/loop")?;
+        assert_eq!(res.status(), StatusCode::FOUND);
+        assert_eq!(chain(&res), ["/loop"; 3]);
+        assert_eq!(client.client.sent.len(), 4);
+
+        let mut client = Redirect::new(Mock::default()).policy(Policy::none());
+        let res = send(&mut client, Method::GET, "/302")?;
+        assert_eq!(res.status(), StatusCode::FOUND);
+        assert!(chain(&res).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn filter() -> Result<(), Error> {
+        let policy = Policy::default().filter(|attempt| attempt.status() != StatusCode::FOUND);
+        let mut client = Redirect::new(Mock::default()).policy(policy);
+        let res = send(&mut client, Method::GET, "/302")?;
+        assert_eq!(res.status(), StatusCode::FOUND);
+
+        let res = send(&mut client, Method::GET, "/301")?;
+        assert_eq!(res.status(), StatusCode::OK);
+        Ok(())
+    }
+
+    #[test]
+    fn cross_origin() -> Result<(), Error> {
+        // without reconnect the redirect isn't followed
+        let mut client = Redirect::new(Mock::default());
+        let res = send(&mut client, Method::GET, "http://a/cross")?;
+        assert_eq!(res.status(), StatusCode::FOUND);
+
+        let mut connects = vec![];
+        let reconnect = |addr: Address| {
+            connects.push(addr);
+            future::ready(Ok(Mock {
+                host: "b",
+                ..Mock::default()
+            }))
+        };
+
+        let mut client = Redirect::new(Mock::default()).reconnect(reconnect);
+        let res = send(&mut client, Method::GET, "http://a/cross")?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(chain(&res), ["/cross"]);
+
+        let (_, uri, headers, _) = client.client.sent.last().expect("redirected request");
+        assert_eq!(uri, "http://b/ok");
+        assert!(!headers.contains_key(header::AUTHORIZATION));
+
+        drop(client);
+        assert_eq!(connects, [Address::http("b")]);
+        Ok(())
+    }
+
+    #[test]
+    fn same_origin() -> Result<(), Error> {
+        let policy = Policy::default().same_origin(true);
+        let mut client = Redirect::new(Mock::default())
+            .reconnect(Reuse)
+            .policy(policy);
+
+        let res = send(&mut client, Method::GET, "http://a/cross")?;
+        assert_eq!(res.status(), StatusCode::FOUND);
+
+        let res = send(&mut client, Method::GET, "http://a/302")?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let (_, uri, headers, _) = client.client.sent.last().expect("redirected request");
+        assert_eq!(uri, "http://a/ok");
+        assert!(headers.contains_key(header::AUTHORIZATION));
+        Ok(())
+    }
+
+    #[test]
+    fn failed_body() {
+        use crate::body::{Body, Hint};
+
+        /// The body which fails to be read.
+        struct Broken;
+
+        impl Body for Broken {
+            type Chunk = Bytes;
+
+            async fn chunk(&mut self) -> Option<Result<Self::Chunk, std::io::Error>> {
+                Some(Err(std::io::ErrorKind::ConnectionReset.into()))
+            }
+
+            fn size_hint(&self) -> Hint {
+                Hint::Chunked { end: false }
+            }
+        }
+
+        /// Redirects to the other path with the broken body.
+        struct Unstable;
+
+        impl Client<()> for Unstable {
+            type Body = Broken;
+
+            async fn send(&mut self, req: Request<()>) -> Result<Response<Self::Body>, Error> {
+                let mut res = http::Response::new(Broken);
+                if req.uri().path() == "/302" {
+                    *res.status_mut() = StatusCode::FOUND;
+                    res.headers_mut()
+                        .insert(header::LOCATION, HeaderValue::from_static("/ok"));
+                }
+
+                Ok(Response::new(res))
+            }
+        }
+
+        let mut client = Redirect::new(Unstable);
+        let req = Request::new(Method::GET, Uri::from_static("/302"), ());
+        let res = future::block_on(client.send(req)).map(|res| res.status());
+        assert_eq!(res.ok(), Some(StatusCode::OK));
+    }
+
+    #[test]
+    fn resolve_location() {
+        let cases = [
+            ("/a/b", "c", "/a/c"),
+            ("/a/b", "/c?d", "/c?d"),
+            ("/a/b", "http://x/y", "http://x/y"),
+            ("http://x/a/b", "c#f", "http://x/a/c"),
+            ("http://x/a/b", "//y/z", "http://y/z"),
+            ("https://x:8443/a", "/b", "https://x:8443/b"),
+        ];
+
+        for (base, location, expected) in cases {
+            let base = Uri::from_static(base);
+            let uri = resolve(&base, location).expect("resolve location");
+            assert_eq!(uri, expected);
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! The name resolution.
+
+use {
+    crate::{
+        addr::Address,
+        error::Error,
+        time::{self, Elapsed, Timer},
+    },
+    futures_lite::prelude::*,
+    std::{
+        collections::HashMap,
+        future, io,
+        marker::PhantomData,
+        net::SocketAddr,
+        pin::Pin,
+        task::{Context, Poll},
+        time::Duration,
+    },
+    url::Host,
+};
+
+/// The trait to resolve a domain name into socket addresses.
+pub trait Resolve {
+    /// Resolves the host and port into socket addresses.
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, io::Error>;
+}
+
+impl<R> Resolve for &R
+where
+    R: Resolve,
+{
+    #[inline]
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, io::Error> {
+        (**self).resolve(host, port).await
+    }
+}
+
+/// The resolver with static overrides.
+///
+/// Works like curl's `--resolve` option. Listed hosts and ports
+/// are resolved to the given addresses, others are passed to the
+/// inner resolver.
+#[derive(Clone, Debug)]
+pub struct Static<R> {
+    inner: R,
+    map: HashMap<(String, u16), Vec<SocketAddr>>,
+}
+
+impl<R> Static<R> {
+    /// Creates the resolver without overrides.
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            map: HashMap::new(),
+        }
+    }
+
+    /// Overrides addresses of the host and port.
+    pub fn add<H, I>(mut self, host: H, port: u16, addrs: I) -> Self
+    where
+        H: Into<String>,
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        let mut host = host.into();
+        host.make_ascii_lowercase();
+        self.map.insert((host, port), addrs.into_iter().collect());
+        self
+    }
+}
+
+impl<R> Resolve for Static<R>
+where
+    R: Resolve,
+{
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, io::Error> {
+        let key = (host.to_ascii_lowercase(), port);
+        match self.map.get(&key) {
+            Some(addrs) => Ok(addrs.clone()),
+            None => self.inner.resolve(host, port).await,
+        }
+    }
+}
+
+/// The recommended delay between connection attempts.
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Connects to one of the addresses using the Happy Eyeballs
+/// algorithm from [RFC 8305].
+///
+/// Addresses are sorted to alternate between IPv6 and IPv4 families,
+/// starting with the family of the first address. Connection attempts
+/// are started one by one with the delay between them, or immediately
+/// when a previous attempt fails, and they run concurrently. The first
+/// established connection is returned and other attempts are dropped.
+///
+/// [RFC 8305]: https://www.rfc-editor.org/rfc/rfc8305
+///
+/// # Errors
+/// Returns the last error if all attempts fail.
+pub async fn happy_eyeballs<T, F, U, C>(
+    addrs: Vec<SocketAddr>,
+    delay: Duration,
+    timer: &T,
+    connect: F,
+) -> Result<C, io::Error>
+where
+    T: Timer,
+    F: Fn(SocketAddr) -> U,
+    U: Future<Output = Result<C, io::Error>>,
+{
+    let mut addrs = interleave(addrs).into_iter();
+    let mut attempts: Vec<Pin<Box<U>>> = vec![];
+    let mut last_error = None;
+    let mut sleep = addrs.next().map(|addr| {
+        attempts.push(Box::pin(connect(addr)));
+        Box::pin(timer.sleep(delay))
+    });
+
+    future::poll_fn(|cx: &mut Context<'_>| {
+        loop {
+            let elapsed = sleep
+                .as_mut()
+                .is_some_and(|sleep| sleep.as_mut().poll(cx).is_ready());
+
+            if elapsed || attempts.is_empty() {
+                sleep = None;
+                match addrs.next() {
+                    Some(addr) => {
+                        attempts.push(Box::pin(connect(addr)));
+                        sleep = Some(Box::pin(timer.sleep(delay)));
+                    }
+                    None if attempts.is_empty() => {
+                        let e = last_error.take().unwrap_or_else(|| {
+                            io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect")
+                        });
+
+                        return Poll::Ready(Err(e));
+                    }
+                    None => {}
+                }
+            }
+
+            let mut failed = false;
+            let mut i = 0;
+            while let Some(attempt) = attempts.get_mut(i) {
+                match attempt.as_mut().poll(cx) {
+                    Poll::Ready(Ok(conn)) => return Poll::Ready(Ok(conn)),
+                    Poll::Ready(Err(e)) => {
+                        last_error = Some(e);
+                        drop(attempts.remove(i));
+                        failed = true;
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+
+            // start the next attempt immediately after a failure
+            if failed {
+                sleep = None;
+                if let Some(addr) = addrs.next() {
+                    attempts.push(Box::pin(connect(addr)));
+                    sleep = Some(Box::pin(timer.sleep(delay)));
+                }
+
+                continue;
+            }
+
+            if !elapsed {
+                return Poll::Pending;
+            }
+        }
+    })
+    .await
+}
+
+/// The network of an async runtime used by the [connector](Connector).
+pub trait Net {
+    /// The connected stream.
+    type Stream;
+
+    /// The system resolver of the runtime.
+    type Resolver: Resolve + Default;
+
+    /// The timer of the runtime.
+    type Timer: Timer + Default;
+
+    /// Connects to the socket address.
+    async fn connect(addr: SocketAddr) -> Result<Self::Stream, io::Error>;
+}
+
+/// The TCP connector.
+///
+/// Resolves the address host and connects to one of the resolved
+/// addresses using the [Happy Eyeballs](happy_eyeballs) algorithm.
+/// Sockets and timers are provided by the runtime [network](Net).
+#[derive(Clone, Debug)]
+pub struct Connector<N, R> {
+    resolver: R,
+    delay: Duration,
+    connect_timeout: Option<Duration>,
+    handshake_timeout: Option<Duration>,
+    net: PhantomData<N>,
+}
+
+impl<N> Connector<N, N::Resolver>
+where
+    N: Net,
+{
+    /// Creates the connector with the system resolver.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            resolver: N::Resolver::default(),
+            delay: CONNECTION_ATTEMPT_DELAY,
+            connect_timeout: None,
+            handshake_timeout: None,
+            net: PhantomData,
+        }
+    }
+}
+
+impl<N> Default for Connector<N, N::Resolver>
+where
+    N: Net,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, R> Connector<N, R> {
+    /// Sets the resolver.
+    #[inline]
+    pub fn resolver<S>(self, resolver: S) -> Connector<N, S> {
+        Connector {
+            resolver,
+            delay: self.delay,
+            connect_timeout: self.connect_timeout,
+            handshake_timeout: self.handshake_timeout,
+            net: PhantomData,
+        }
+    }
+
+    /// Sets the delay between connection attempts.
+    #[inline]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the timeout to resolve the host and establish a connection.
+    #[inline]
+    pub fn connect_timeout(mut self, dur: Duration) -> Self {
+        self.connect_timeout = Some(dur);
+        self
+    }
+
+    /// Sets the timeout of the handshake, including the TLS handshake.
+    #[inline]
+    pub fn handshake_timeout(mut self, dur: Duration) -> Self {
+        self.handshake_timeout = Some(dur);
+        self
+    }
+
+    /// Connects to the address.
+    ///
+    /// # Errors
+    /// Returns the [`Connect`](Elapsed::Connect) timeout error
+    /// if the connection isn't established in time.
+    pub async fn connect(&self, addr: &Address) -> Result<N::Stream, io::Error>
+    where
+        N: Net,
+        R: Resolve,
+    {
+        match self.connect_timeout {
+            Some(dur) => time::timeout(&N::Timer::default(), dur, self.resolve_and_connect(addr))
+                .await
+                .ok_or(Elapsed::Connect)?,
+            None => self.resolve_and_connect(addr).await,
+        }
+    }
+
+    /// Runs the handshake with the handshake timeout.
+    ///
+    /// # Errors
+    /// Returns the [`Handshake`](Elapsed::Handshake) timeout error
+    /// if the handshake isn't completed in time.
+    pub async fn handshake<F, T>(&self, fut: F) -> Result<T, Error>
+    where
+        N: Net,
+        F: Future<Output = Result<T, Error>>,
+    {
+        match self.handshake_timeout {
+            Some(dur) => time::timeout(&N::Timer::default(), dur, fut)
+                .await
+                .ok_or(Elapsed::Handshake)?,
+            None => fut.await,
+        }
+    }
+
+    async fn resolve_and_connect(&self, addr: &Address) -> Result<N::Stream, io::Error>
+    where
+        N: Net,
+        R: Resolve,
+    {
+        let addrs = match &addr.host {
+            Host::Domain(d) => self.resolver.resolve(d, addr.port).await?,
+            Host::Ipv4(ip4) => vec![SocketAddr::from((*ip4, addr.port))],
+            Host::Ipv6(ip6) => vec![SocketAddr::from((*ip6, addr.port))],
+        };
+
+        happy_eyeballs(addrs, self.delay, &N::Timer::default(), N::connect).await
+    }
+}
+
+/// Sorts addresses to alternate between families.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(first) = addrs.first() else {
+        return addrs;
+    };
+
+    let first_v6 = first.is_ipv6();
+    let (mut primary, mut secondary): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_v6);
+
+    let mut out = Vec::with_capacity(primary.len() + secondary.len());
+    primary.reverse();
+    secondary.reverse();
+    loop {
+        match (primary.pop(), secondary.pop()) {
+            (None, None) => break out,
+            (a, b) => out.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        futures_lite::future,
+        std::{
+            cell::RefCell,
+            error,
+            net::{Ipv4Addr, Ipv6Addr},
+        },
+    };
+
+    fn v4(n: u8) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::new(10, 0, 0, n), 80))
+    }
+
+    fn v6(n: u16) -> SocketAddr {
+        SocketAddr::from((Ipv6Addr::new(0xfd, 0, 0, 0, 0, 0, 0, n), 80))
+    }
+
+    #[test]
+    fn interleave_families() {
+        let addrs = vec![v6(1), v6(2), v6(3), v4(1), v4(2)];
+        assert_eq!(interleave(addrs), [v6(1), v4(1), v6(2), v4(2), v6(3)]);
+
+        let addrs = vec![v4(1), v4(2), v6(1)];
+        assert_eq!(interleave(addrs), [v4(1), v6(1), v4(2)]);
+    }
+
+    #[derive(Default)]
+    struct Fake;
+
+    impl Resolve for Fake {
+        async fn resolve(&self, _: &str, _: u16) -> Result<Vec<SocketAddr>, io::Error> {
+            Ok(vec![v4(1)])
+        }
+    }
+
+    #[test]
+    fn static_override() -> Result<(), io::Error> {
+        let resolver = Static::new(Fake).add("Example.com", 443, [v6(1), v4(2)]);
+        future::block_on(async {
+            assert_eq!(resolver.resolve("example.com", 443).await?, [v6(1), v4(2)]);
+            assert_eq!(resolver.resolve("example.com", 80).await?, [v4(1)]);
+            assert_eq!(resolver.resolve("other.com", 443).await?, [v4(1)]);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn race() -> Result<(), io::Error> {
+        let started = RefCell::new(vec![]);
+        let connect = |addr: SocketAddr| {
+            started.borrow_mut().push(addr);
+            async move {
+                match addr {
+                    // the broken ipv6 hangs forever
+                    addr if addr == v6(1) => future::pending().await,
+                    addr if addr == v6(2) => Err(io::Error::from(io::ErrorKind::ConnectionRefused)),
+                    addr => Ok(addr),
+                }
+            }
+        };
+
+        // the timer elapses immediately
+        let timer = |_| future::ready(());
+        let addrs = vec![v6(1), v6(2), v4(1)];
+        let conn = future::block_on(happy_eyeballs(addrs, Duration::ZERO, &timer, connect))?;
+        assert_eq!(conn, v4(1));
+        assert_eq!(*started.borrow(), [v6(1), v4(1)]);
+
+        // without the timer the next attempt starts only after a failure
+        started.borrow_mut().clear();
+        let timer = |_| future::pending::<()>();
+        let addrs = vec![v6(2), v4(1)];
+        let conn = future::block_on(happy_eyeballs(addrs, Duration::ZERO, &timer, connect))?;
+        assert_eq!(conn, v4(1));
+        assert_eq!(*started.borrow(), [v6(2), v4(1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn all_failed() {
+        let connect = |_| async { Err::<(), _>(io::Error::from(io::ErrorKind::ConnectionRefused)) };
+        let timer = |_| future::pending::<()>();
+
+        let res = future::block_on(happy_eyeballs(
+            vec![v4(1), v6(1)],
+            Duration::ZERO,
+            &timer,
+            connect,
+        ));
+        let e = res.expect_err("all attempts should fail");
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+
+        let res = future::block_on(happy_eyeballs(vec![], Duration::ZERO, &timer, connect));
+        let e = res.expect_err("no addresses");
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    /// The network which connects to any address
+    /// and whose timer elapses immediately.
+    #[derive(Clone, Copy, Debug)]
+    struct Loopback;
+
+    #[derive(Default)]
+    struct Elapse;
+
+    impl Timer for Elapse {
+        fn sleep(&self, _: Duration) -> impl Future<Output = ()> + Send {
+            future::ready(())
+        }
+    }
+
+    impl Net for Loopback {
+        type Stream = SocketAddr;
+        type Resolver = Fake;
+        type Timer = Elapse;
+
+        async fn connect(addr: SocketAddr) -> Result<Self::Stream, io::Error> {
+            Ok(addr)
+        }
+    }
+
+    #[test]
+    fn connector() -> Result<(), io::Error> {
+        let connector = Connector::<Loopback, _>::new();
+        let addr = Address::http("example.com");
+        assert_eq!(future::block_on(connector.connect(&addr))?, v4(1));
+
+        let addr = Address::http(Ipv6Addr::LOCALHOST);
+        let local = SocketAddr::from((Ipv6Addr::LOCALHOST, 80));
+        assert_eq!(future::block_on(connector.connect(&addr))?, local);
+        Ok(())
+    }
+
+    #[test]
+    fn handshake_timeout() -> Result<(), Error> {
+        let connector = Connector::<Loopback, _>::new();
+        let stalled = future::pending::<Result<(), Error>>();
+        let ready = future::block_on(future::or(
+            async { connector.handshake(stalled).await.map(|()| false) },
+            async { Ok(true) },
+        ))?;
+
+        assert!(ready, "no timeout by default");
+
+        let connector = connector.handshake_timeout(Duration::from_secs(1));
+        assert_eq!(
+            future::block_on(connector.handshake(future::ready(Ok(1))))?,
+            1
+        );
+
+        let stalled = future::pending::<Result<(), Error>>();
+        let e =
+            future::block_on(connector.handshake(stalled)).expect_err("the handshake times out");
+        assert!(e.is_timeout(), "the timeout error");
+
+        let elapsed = error::Error::source(&e).and_then(|e| e.downcast_ref());
+        assert_eq!(elapsed, Some(&Elapsed::Handshake));
+        Ok(())
+    }
+}
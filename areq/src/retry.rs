@@ -0,0 +1,419 @@
+//! The retrying client.
+
+use {
+    crate::{
+        body,
+        client::Client,
+        error::Error,
+        proto::{Request, Response},
+        time::Timer,
+    },
+    http::{HeaderMap, Method, StatusCode, header},
+    std::{
+        hash::{BuildHasher, RandomState},
+        io,
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+/// The retry policy.
+///
+/// By default, up to 3 retries are made for idempotent
+/// requests failed with a transient I/O error or responded
+/// with `429 Too Many Requests` or `503 Service Unavailable`.
+#[derive(Clone, Debug)]
+pub struct Policy {
+    retries: usize,
+    base: Duration,
+    max_delay: Duration,
+    any_method: bool,
+    statuses: Vec<StatusCode>,
+}
+
+impl Policy {
+    /// Sets the maximum number of retries.
+    #[inline]
+    pub fn retries(mut self, n: usize) -> Self {
+        self.retries = n;
+        self
+    }
+
+    /// Sets the base delay of exponential backoff.
+    ///
+    /// The delay is doubled after each retry.
+    #[inline]
+    pub fn base_delay(mut self, base: Duration) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Sets the maximum delay before a retry.
+    ///
+    /// If a response has the `Retry-After` header with a longer
+    /// delay, it's returned without retrying.
+    #[inline]
+    pub fn max_delay(mut self, max: Duration) -> Self {
+        self.max_delay = max;
+        self
+    }
+
+    /// Allows to retry requests with non-idempotent methods like `POST`.
+    #[inline]
+    pub fn any_method(mut self, any_method: bool) -> Self {
+        self.any_method = any_method;
+        self
+    }
+
+    /// Sets response statuses to retry.
+    #[inline]
+    pub fn statuses<I>(mut self, statuses: I) -> Self
+    where
+        I: IntoIterator<Item = StatusCode>,
+    {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    fn allows(&self, method: &Method) -> bool {
+        self.any_method
+            || matches!(
+                *method,
+                Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+            )
+    }
+
+    /// Returns the backoff delay for the zero-based retry number.
+    fn backoff(&self, retry: usize) -> Duration {
+        let exp = u32::try_from(retry).unwrap_or(u32::MAX);
+        let delay = 2_u32
+            .checked_pow(exp)
+            .and_then(|factor| self.base.checked_mul(factor))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+
+        // take a random delay between a half and the full one
+        let half = delay / 2;
+        half + half.mul_f64(random())
+    }
+}
+
+impl Default for Policy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            base: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            any_method: false,
+            statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::SERVICE_UNAVAILABLE,
+            ],
+        }
+    }
+}
+
+/// Returns a random number in the range `0.0..=1.0`.
+fn random() -> f64 {
+    let n = RandomState::new().hash_one(Instant::now());
+    n as f64 / u64::MAX as f64
+}
+
+/// The client that retries failed requests.
+///
+/// Since a request may be sent several times, its body must be
+/// cloneable. A retried response body is read to the end or
+/// dropped before the next attempt.
+///
+/// Note that a single connection client can't recover after a
+/// connection error, so wrap a [pool](crate::pool::Pool) to retry
+/// such failures with a new connection.
+pub struct Retry<C, T> {
+    client: C,
+    timer: T,
+    policy: Policy,
+}
+
+impl<C, T> Retry<C, T> {
+    /// Wraps the client with the default [policy](Policy).
+    ///
+    /// The [timer](Timer) is used to wait between attempts.
+    #[inline]
+    pub fn new(client: C, timer: T) -> Self {
+        Self {
+            client,
+            timer,
+            policy: Policy::default(),
+        }
+    }
+
+    /// Sets the retry [policy](Policy).
+    #[inline]
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the inner client.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+}
+
+impl<B, C, T> Client<B> for Retry<C, T>
+where
+    B: Clone,
+    C: Client<B>,
+    T: Timer,
+{
+    type Body = C::Body;
+
+    async fn send(&mut self, mut req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let allows = self.policy.allows(req.method());
+        let mut retry = 0;
+        loop {
+            let next = (allows && retry < self.policy.retries).then(|| req.clone());
+            let res = self.client.send(req).await;
+            let Some(next) = next else {
+                return res;
+            };
+
+            let delay = match res {
+                Ok(res) if self.policy.statuses.contains(&res.status()) => {
+                    let delay = match retry_after(res.headers()) {
+                        Some(delay) if delay > self.policy.max_delay => return Ok(res),
+                        Some(delay) => delay,
+                        None => self.policy.backoff(retry),
+                    };
+
+                    // the response is discarded anyway,
+                    // so a failed body doesn't stop retrying
+                    _ = body::drain(http::Response::from(res).into_body()).await;
+                    delay
+                }
+                Err(e) if is_transient(&e) => self.policy.backoff(retry),
+                res => return res,
+            };
+
+            self.timer.sleep(delay).await;
+            retry += 1;
+            req = next;
+        }
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+/// Parses the `Retry-After` header as either seconds or a date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Checks if the error is likely caused by a temporary
+/// connection problem, so the request may be sent again.
+fn is_transient(e: &Error) -> bool {
+    if e.is_connect() || e.is_closed() || e.is_timeout() {
+        return true;
+    }
+
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<io::Error>())
+        .is_some_and(|e| e.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test::{Scripted, status},
+        bytes::Bytes,
+        futures_lite::future,
+        http::{HeaderValue, Uri},
+        std::{cell::RefCell, io},
+    };
+
+    fn reset() -> Result<Response<Bytes>, Error> {
+        Err(Error::from(io::Error::from(io::ErrorKind::ConnectionReset)))
+    }
+
+    struct Sleeps(RefCell<Vec<Duration>>);
+
+    impl Timer for &Sleeps {
+        fn sleep(&self, dur: Duration) -> impl Future<Output = ()> + Send {
+            self.0.borrow_mut().push(dur);
+            future::ready(())
+        }
+    }
+
+    fn send(
+        mock: Scripted,
+        method: Method,
+        policy: Policy,
+    ) -> (Result<StatusCode, Error>, usize, Vec<Duration>) {
+        let sleeps = Sleeps(RefCell::new(vec![]));
+        let mut client = Retry::new(mock, &sleeps).policy(policy);
+        let req = Request::new(method, Uri::from_static("/"), ());
+        let res = future::block_on(client.send(req)).map(|res| res.status());
+        let sent = client.into_inner().sent.len();
+        (res, sent, sleeps.0.into_inner())
+    }
+
+    #[test]
+    fn retry_status() {
+        let mock = Scripted::new([status(503), status(429), status(200)]);
+        let (res, sent, sleeps) = send(mock, Method::GET, Policy::default());
+        assert_eq!(res.ok(), Some(StatusCode::OK));
+        assert_eq!(sent, 3);
+        assert_eq!(sleeps.len(), 2);
+    }
+
+    #[test]
+    fn retry_error() {
+        let mock = Scripted::new([reset(), status(200)]);
+        let (res, sent, _) = send(mock, Method::PUT, Policy::default());
+        assert_eq!(res.ok(), Some(StatusCode::OK));
+        assert_eq!(sent, 2);
+
+        let not_found = Err(Error::from(io::Error::from(io::ErrorKind::NotFound)));
+        let mock = Scripted::new([not_found]);
+        let (res, sent, _) = send(mock, Method::GET, Policy::default());
+        assert!(res.is_err(), "the error isn't transient");
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn retries() {
+        let mock = Scripted::new([status(503), status(503), status(503)]);
+        let (res, sent, _) = send(mock, Method::GET, Policy::default().retries(2));
+        assert_eq!(res.ok(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(sent, 3);
+    }
+
+    #[test]
+    fn idempotent() {
+        let mock = Scripted::new([status(503)]);
+        let (res, sent, _) = send(mock, Method::POST, Policy::default());
+        assert_eq!(res.ok(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(sent, 1);
+
+        let mock = Scripted::new([reset(), status(200)]);
+        let (res, sent, _) = send(mock, Method::POST, Policy::default().any_method(true));
+        assert_eq!(res.ok(), Some(StatusCode::OK));
+        assert_eq!(sent, 2);
+    }
+
+    #[test]
+    fn backoff() {
+        let policy = Policy::default()
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5));
+
+        let mock = Scripted::new([reset(), reset(), reset(), reset(), status(200)]);
+        let (_, _, sleeps) = send(mock, Method::GET, policy.retries(4));
+        for (sleep, max) in sleeps.into_iter().zip([1, 2, 4, 5]) {
+            let max = Duration::from_secs(max);
+            assert!(max / 2 <= sleep && sleep <= max, "{sleep:?} within {max:?}");
+        }
+    }
+
+    #[test]
+    fn retry_after() {
+        let mut res = status(503);
+        if let Ok(res) = &mut res {
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("3"));
+        }
+
+        let mock = Scripted::new([res, status(200)]);
+        let (_, _, sleeps) = send(mock, Method::GET, Policy::default());
+        assert_eq!(sleeps, [Duration::from_secs(3)]);
+
+        let mut res = status(503);
+        if let Ok(res) = &mut res {
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("60"));
+        }
+
+        let mock = Scripted::new([res]);
+        let (res, sent, _) = send(mock, Method::GET, Policy::default());
+        assert_eq!(res.ok(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn failed_body() {
+        use crate::body::{Body, Hint};
+
+        /// The body which fails to be read.
+        struct Broken;
+
+        impl Body for Broken {
+            type Chunk = Bytes;
+
+            async fn chunk(&mut self) -> Option<Result<Self::Chunk, io::Error>> {
+                Some(Err(io::Error::from(io::ErrorKind::ConnectionReset)))
+            }
+
+            fn size_hint(&self) -> Hint {
+                Hint::Chunked { end: false }
+            }
+        }
+
+        /// Responds with the broken body until the last attempt.
+        struct Unstable(usize);
+
+        impl Client<()> for Unstable {
+            type Body = Broken;
+
+            async fn send(&mut self, _: Request<()>) -> Result<Response<Self::Body>, Error> {
+                self.0 += 1;
+                let mut res = http::Response::new(Broken);
+                if self.0 == 1 {
+                    *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                }
+
+                Ok(Response::new(res))
+            }
+        }
+
+        let sleeps = Sleeps(RefCell::new(vec![]));
+        let mut client = Retry::new(Unstable(0), &sleeps);
+        let req = Request::new(Method::GET, Uri::from_static("/"), ());
+        let res = future::block_on(client.send(req)).map(|res| res.status());
+        assert_eq!(res.ok(), Some(StatusCode::OK));
+        assert_eq!(client.into_inner().0, 2);
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(super::retry_after(&headers), Some(Duration::from_secs(120)));
+
+        let past = HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT");
+        headers.insert(header::RETRY_AFTER, past);
+        assert_eq!(super::retry_after(&headers), Some(Duration::ZERO));
+
+        let future = SystemTime::now() + Duration::from_secs(100);
+        let date = HeaderValue::try_from(httpdate::fmt_http_date(future)).expect("valid date");
+        headers.insert(header::RETRY_AFTER, date);
+        let delay = super::retry_after(&headers).expect("parsed date");
+        assert!(delay <= Duration::from_secs(100), "{delay:?}");
+        assert!(delay >= Duration::from_secs(98), "{delay:?}");
+
+        headers.insert(header::RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(super::retry_after(&headers), None);
+    }
+}
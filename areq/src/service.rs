@@ -0,0 +1,314 @@
+//! The [`tower`] integration.
+
+use {
+    crate::{
+        body::Body,
+        client::Client,
+        error::Error,
+        proto::{Request, Response},
+    },
+    bytes::Bytes,
+    std::{error, future, io, pin::Pin},
+    tower::Service,
+};
+
+#[cfg(not(feature = "rtn"))]
+use std::task::{Context, Poll};
+
+#[cfg(feature = "rtn")]
+pub(crate) type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+#[cfg(not(feature = "rtn"))]
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// The adapter to use a [client](Client) as a [`Service`].
+///
+/// Each call sends a request with a clone of the client,
+/// so the client must support [cloning](Client::try_clone),
+/// like [`H2`](crate::http2::H2) does. Otherwise the call fails
+/// with an [unsupported](io::ErrorKind::Unsupported) error.
+///
+/// With the `rtn` feature enabled the returned future is [`Send`].
+#[derive(Debug)]
+pub struct ServiceClient<C>(pub(crate) C);
+
+impl<C> ServiceClient<C> {
+    /// Wraps the client.
+    #[inline]
+    pub fn new(client: C) -> Self {
+        Self(client)
+    }
+
+    /// Returns the inner client.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.0
+    }
+}
+
+impl<C> Clone for ServiceClient<C>
+where
+    C: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+pub(crate) fn unsupported() -> Error {
+    Error::from(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the client doesn't support cloning",
+    ))
+}
+
+#[cfg(not(feature = "rtn"))]
+impl<B, C> Service<http::Request<B>> for ServiceClient<C>
+where
+    B: 'static,
+    C: Client<B> + 'static,
+{
+    type Response = http::Response<C::Body>;
+    type Error = Error;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let client = self.0.try_clone();
+        Box::pin(async move {
+            let mut client = client.ok_or_else(unsupported)?;
+            let res = client.send(Request::from(req)).await?;
+            Ok(res.into())
+        })
+    }
+}
+
+/// The adapter to use a [`Service`] as a [client](Client).
+///
+/// The service must respond with a body implementing [`Body`].
+/// Its errors are converted into [I/O errors](crate::ErrorKind::Io),
+/// unless the service returns the client [error](Error) itself.
+/// Since services are usually cheap to clone, the client is
+/// [cloneable](Client::try_clone) when the service is.
+#[derive(Clone, Debug)]
+pub struct TowerClient<S>(S);
+
+impl<S> TowerClient<S> {
+    /// Wraps the service.
+    #[inline]
+    pub fn new(service: S) -> Self {
+        Self(service)
+    }
+
+    /// Returns the inner service.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+impl<B, D, S> Client<B> for TowerClient<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<D>> + Clone,
+    S::Error: Into<Box<dyn error::Error + Send + Sync>>,
+    D: Body<Chunk = Bytes>,
+{
+    type Body = D;
+
+    async fn send(&mut self, req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        future::poll_fn(|cx| self.0.poll_ready(cx))
+            .await
+            .map_err(io::Error::other)?;
+
+        let res = self.0.call(req.into()).await.map_err(io::Error::other)?;
+
+        Ok(res.into())
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        axum::{Router, routing},
+        futures_lite::future,
+        http::Uri,
+        http_body_util::BodyExt as _,
+        tower::ServiceExt,
+    };
+
+    /// The axum router with its responses collected into [`Bytes`].
+    fn router() -> TowerClient<
+        impl Service<
+            http::Request<String>,
+            Response = http::Response<Bytes>,
+            Error = axum::Error,
+            Future: Send,
+        > + Clone
+        + Send,
+    > {
+        let router = Router::new()
+            .route("/", routing::get(async || "Hello, World!"))
+            .route("/echo", routing::post(async |body: String| body));
+
+        let service = tower::service_fn(move |req: http::Request<String>| {
+            let router = router.clone();
+            async move {
+                let Ok(res) = router.oneshot(req).await;
+                let (parts, body) = res.into_parts();
+                let body = body.collect().await?.to_bytes();
+                Ok(http::Response::from_parts(parts, body))
+            }
+        });
+
+        TowerClient::new(service)
+    }
+
+    #[test]
+    fn tower_client() -> Result<(), Error> {
+        future::block_on(async {
+            let mut client = router();
+            let res = client
+                .send(Request::get(Uri::from_static("/"), String::new()))
+                .await?;
+
+            assert_eq!(res.status(), http::StatusCode::OK);
+            assert_eq!(http::Response::from(res).into_body(), "Hello, World!");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn service_client() -> Result<(), Error> {
+        future::block_on(async {
+            let service = ServiceClient::new(router());
+            let req = http::Request::post("/echo")
+                .body(String::from("echo"))
+                .expect("valid request");
+
+            let res = service.oneshot(req).await?;
+            assert_eq!(res.into_body(), "echo");
+            Ok(())
+        })
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn service_client_h2() -> Result<(), Error> {
+        use crate::{Address, Handshake, Session, body::BodyExt as _, http2::Http2, test::Duplex};
+
+        let router = Router::new()
+            .route("/", routing::get(async || "Hello, World!"))
+            .route("/echo", routing::post(async |body: String| body));
+
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let server = async move {
+            let mut conn = h2::server::handshake(server_io).await?;
+
+            // serve requests one by one until the client is gone
+            while let Some(Ok((req, mut respond))) = conn.accept().await {
+                let router = router.clone();
+                let handle = async move {
+                    let (parts, mut body) = req.into_parts();
+                    let mut data = vec![];
+                    while let Some(chunk) = body.data().await {
+                        let chunk = chunk?;
+                        body.flow_control().release_capacity(chunk.len())?;
+                        data.extend_from_slice(&chunk);
+                    }
+
+                    let req = http::Request::from_parts(
+                        parts,
+                        http_body_util::Full::new(Bytes::from(data)),
+                    );
+
+                    let Ok(res) = router.oneshot(req).await;
+                    let (parts, body) = res.into_parts();
+                    let body = body.collect().await.expect("axum body").to_bytes();
+                    let mut send =
+                        respond.send_response(http::Response::from_parts(parts, ()), false)?;
+                    send.send_data(body, true)
+                };
+
+                // drive the connection while the request is handled
+                let drive = future::poll_fn(|cx| conn.poll_closed(cx));
+                future::or(handle, drive).await?;
+            }
+
+            Ok::<_, h2::Error>(())
+        };
+
+        let client = async move {
+            let se = Session {
+                addr: Address::http("localhost"),
+                io: Duplex(client_io),
+            };
+
+            let (client, conn) = Http2::default().handshake(se).await?;
+            let mut service = ServiceClient::new(client);
+            let calls = async move {
+                let req = http::Request::get("http://localhost/")
+                    .body(String::new())
+                    .expect("valid request");
+
+                let res = service.ready().await?.call(req).await?;
+                assert_eq!(res.status(), http::StatusCode::OK);
+                assert_eq!(res.into_body().text().await?, "Hello, World!");
+
+                let req = http::Request::post("http://localhost/echo")
+                    .body(String::from("echo"))
+                    .expect("valid request");
+
+                let res = service.oneshot(req).await?;
+                assert_eq!(res.into_body().text().await?, "echo");
+                Ok(())
+            };
+
+            // the connection is dropped with the client,
+            // so the server sees the client is gone
+            let conn = async {
+                conn.await;
+                Err(Error::from(io::Error::from(
+                    io::ErrorKind::ConnectionAborted,
+                )))
+            };
+
+            future::or(calls, conn).await
+        };
+
+        let (server, res) = future::block_on(future::zip(server, client));
+        server.map_err(Error::from)?;
+        res
+    }
+
+    #[test]
+    fn not_cloneable() {
+        struct Single;
+
+        impl Client<String> for Single {
+            type Body = Bytes;
+
+            async fn send(&mut self, _: Request<String>) -> Result<Response<Self::Body>, Error> {
+                Ok(Response::new(http::Response::default()))
+            }
+        }
+
+        let service = ServiceClient::new(Single);
+        let res = future::block_on(service.oneshot(http::Request::default()));
+        let err = res.expect_err("the client isn't cloneable");
+        assert_eq!(
+            err.try_into_io().expect("io error").kind(),
+            io::ErrorKind::Unsupported,
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use {
+    crate::{
+        client::Client,
+        error::Error,
+        proto::Request,
+        service::{BoxFuture, ServiceClient, unsupported},
+    },
+    std::task::{Context, Poll},
+    tower::Service,
+};
+
+impl<B, C> Service<http::Request<B>> for ServiceClient<C>
+where
+    B: Send + 'static,
+    C: Client<B, send(..): Send> + Send + 'static,
+{
+    type Response = http::Response<C::Body>;
+    type Error = Error;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let client = self.0.try_clone();
+        Box::pin(async move {
+            let mut client = client.ok_or_else(unsupported)?;
+            let res = client.send(Request::from(req)).await?;
+            Ok(res.into())
+        })
+    }
+}
@@ -0,0 +1,417 @@
+//! The socks proxy.
+
+use {
+    crate::{
+        addr::Address,
+        error::Error,
+        proto::{Handshake, Session},
+    },
+    futures_lite::prelude::*,
+    std::{error, fmt, io, net::Ipv4Addr},
+    url::Host,
+};
+
+/// The [handshake](Handshake) through a SOCKS5 proxy.
+///
+/// The handshake takes a [session](Session) to the proxy server, asks
+/// it to connect to the target address and then performs the inner
+/// handshake over the connection. Domain names are resolved by the
+/// proxy.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "http1")] {
+/// use areq::{Address, http1::Http1, socks::Socks5};
+///
+/// let target = Address::http("example.com");
+/// let handshake = Socks5::new(target, Http1::default()).auth("user", "pass");
+/// # }
+/// ```
+pub struct Socks5<H> {
+    inner: H,
+    target: Address,
+    auth: Option<(String, String)>,
+}
+
+impl<H> Socks5<H> {
+    const VERSION: u8 = 5;
+    const NO_AUTH: u8 = 0;
+    const PASSWORD: u8 = 2;
+    const NO_METHODS: u8 = 0xff;
+    const CONNECT: u8 = 1;
+
+    /// Creates the handshake to the target address.
+    #[inline]
+    pub fn new(target: Address, inner: H) -> Self {
+        Self {
+            inner,
+            target,
+            auth: None,
+        }
+    }
+
+    /// Sets the username and password authentication.
+    #[inline]
+    pub fn auth<U, P>(mut self, user: U, pass: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        self.auth = Some((user.into(), pass.into()));
+        self
+    }
+
+    async fn connect<I>(&self, io: &mut I) -> Result<(), io::Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        let methods: &[u8] = match self.auth {
+            Some(_) => &[Self::NO_AUTH, Self::PASSWORD],
+            None => &[Self::NO_AUTH],
+        };
+
+        let mut req = vec![Self::VERSION, methods.len() as u8];
+        req.extend_from_slice(methods);
+        io.write_all(&req).await?;
+        io.flush().await?;
+
+        let mut res = [0; 2];
+        io.read_exact(&mut res).await?;
+        match res {
+            [Self::VERSION, Self::NO_AUTH] => {}
+            [Self::VERSION, Self::PASSWORD] => {
+                let (user, pass) = self.auth.as_ref().ok_or(SocksError::InvalidResponse)?;
+                password(io, user, pass).await?;
+            }
+            [Self::VERSION, Self::NO_METHODS] => return Err(SocksError::Auth.into()),
+            _ => return Err(SocksError::InvalidResponse.into()),
+        }
+
+        req.clear();
+        req.extend_from_slice(&[Self::VERSION, Self::CONNECT, 0]);
+        match &self.target.host {
+            Host::Domain(domain) => {
+                let len = u8::try_from(domain.len()).map_err(|_| SocksError::Unsupported)?;
+                req.extend_from_slice(&[3, len]);
+                req.extend_from_slice(domain.as_bytes());
+            }
+            Host::Ipv4(ip) => {
+                req.push(1);
+                req.extend_from_slice(&ip.octets());
+            }
+            Host::Ipv6(ip) => {
+                req.push(4);
+                req.extend_from_slice(&ip.octets());
+            }
+        }
+
+        req.extend_from_slice(&self.target.port.to_be_bytes());
+        io.write_all(&req).await?;
+        io.flush().await?;
+
+        let mut res = [0; 4];
+        io.read_exact(&mut res).await?;
+        let [version, reply, _, kind] = res;
+        if version != Self::VERSION {
+            return Err(SocksError::InvalidResponse.into());
+        }
+
+        if reply != 0 {
+            return Err(SocksError::Reply(reply).into());
+        }
+
+        // skip the bound address and port
+        let len = match kind {
+            1 => 4,
+            3 => {
+                let mut len = 0;
+                io.read_exact(std::slice::from_mut(&mut len)).await?;
+                usize::from(len)
+            }
+            4 => 16,
+            _ => return Err(SocksError::InvalidResponse.into()),
+        };
+
+        let mut bound = [0; u8::MAX as usize + 2];
+        io.read_exact(&mut bound[..len + 2]).await?;
+        Ok(())
+    }
+}
+
+/// Performs the username and password authentication from RFC 1929.
+async fn password<I>(io: &mut I, user: &str, pass: &str) -> Result<(), io::Error>
+where
+    I: AsyncRead + AsyncWrite + Unpin,
+{
+    const VERSION: u8 = 1;
+
+    let user_len = u8::try_from(user.len()).map_err(|_| SocksError::Unsupported)?;
+    let pass_len = u8::try_from(pass.len()).map_err(|_| SocksError::Unsupported)?;
+
+    let mut req = vec![VERSION, user_len];
+    req.extend_from_slice(user.as_bytes());
+    req.push(pass_len);
+    req.extend_from_slice(pass.as_bytes());
+    io.write_all(&req).await?;
+    io.flush().await?;
+
+    let mut res = [0; 2];
+    io.read_exact(&mut res).await?;
+    match res {
+        [VERSION, 0] => Ok(()),
+        [VERSION, _] => Err(SocksError::Auth.into()),
+        _ => Err(SocksError::InvalidResponse.into()),
+    }
+}
+
+impl<I, B, H> Handshake<I, B> for Socks5<H>
+where
+    I: AsyncRead + AsyncWrite + Unpin,
+    H: Handshake<I, B>,
+{
+    type Client = H::Client;
+
+    async fn handshake(
+        self,
+        se: Session<I>,
+    ) -> Result<(Self::Client, impl Future<Output = ()>), Error> {
+        let Session { mut io, .. } = se;
+        self.connect(&mut io).await.map_err(Error::connect)?;
+
+        let se = Session {
+            addr: self.target,
+            io,
+        };
+
+        self.inner.handshake(se).await
+    }
+}
+
+/// The [handshake](Handshake) through a SOCKS4a proxy.
+///
+/// Works like [`Socks5`], but for legacy proxies. The protocol doesn't
+/// support passwords and IPv6 addresses, only an optional user id.
+pub struct Socks4<H> {
+    inner: H,
+    target: Address,
+    user: String,
+}
+
+impl<H> Socks4<H> {
+    const VERSION: u8 = 4;
+    const CONNECT: u8 = 1;
+    const GRANTED: u8 = 0x5a;
+
+    /// Creates the handshake to the target address.
+    #[inline]
+    pub fn new(target: Address, inner: H) -> Self {
+        Self {
+            inner,
+            target,
+            user: String::new(),
+        }
+    }
+
+    /// Sets the user id.
+    #[inline]
+    pub fn user<U>(mut self, user: U) -> Self
+    where
+        U: Into<String>,
+    {
+        self.user = user.into();
+        self
+    }
+
+    async fn connect<I>(&self, io: &mut I) -> Result<(), io::Error>
+    where
+        I: AsyncRead + AsyncWrite + Unpin,
+    {
+        // an invalid ip address 0.0.0.x tells the proxy to resolve the domain
+        let (ip, domain) = match &self.target.host {
+            Host::Domain(domain) => (Ipv4Addr::new(0, 0, 0, 1), Some(domain)),
+            Host::Ipv4(ip) => (*ip, None),
+            Host::Ipv6(_) => return Err(SocksError::Unsupported.into()),
+        };
+
+        let mut req = vec![Self::VERSION, Self::CONNECT];
+        req.extend_from_slice(&self.target.port.to_be_bytes());
+        req.extend_from_slice(&ip.octets());
+        req.extend_from_slice(self.user.as_bytes());
+        req.push(0);
+        if let Some(domain) = domain {
+            req.extend_from_slice(domain.as_bytes());
+            req.push(0);
+        }
+
+        io.write_all(&req).await?;
+        io.flush().await?;
+
+        let mut res = [0; 8];
+        io.read_exact(&mut res).await?;
+        match res[..2] {
+            [0, Self::GRANTED] => Ok(()),
+            [0, reply] => Err(SocksError::Reply(reply).into()),
+            _ => Err(SocksError::InvalidResponse.into()),
+        }
+    }
+}
+
+impl<I, B, H> Handshake<I, B> for Socks4<H>
+where
+    I: AsyncRead + AsyncWrite + Unpin,
+    H: Handshake<I, B>,
+{
+    type Client = H::Client;
+
+    async fn handshake(
+        self,
+        se: Session<I>,
+    ) -> Result<(Self::Client, impl Future<Output = ()>), Error> {
+        let Session { mut io, .. } = se;
+        self.connect(&mut io).await.map_err(Error::connect)?;
+
+        let se = Session {
+            addr: self.target,
+            io,
+        };
+
+        self.inner.handshake(se).await
+    }
+}
+
+/// The socks proxy error.
+#[derive(Debug)]
+pub enum SocksError {
+    /// The authentication failed or no method is accepted.
+    Auth,
+    /// The proxy failed to connect with the given reply code.
+    Reply(u8),
+    /// The address or credentials can't be sent with the protocol.
+    Unsupported,
+    /// The proxy sent an invalid response.
+    InvalidResponse,
+}
+
+impl From<SocksError> for io::Error {
+    fn from(e: SocksError) -> Self {
+        let kind = match e {
+            SocksError::Auth => io::ErrorKind::PermissionDenied,
+            SocksError::Reply(_) => io::ErrorKind::ConnectionRefused,
+            SocksError::Unsupported => io::ErrorKind::InvalidInput,
+            SocksError::InvalidResponse => io::ErrorKind::InvalidData,
+        };
+
+        Self::new(kind, e)
+    }
+}
+
+impl fmt::Display for SocksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auth => write!(f, "socks authentication failed"),
+            Self::Reply(code) => write!(f, "socks proxy replied with code {code}"),
+            Self::Unsupported => write!(f, "unsupported socks address or credentials"),
+            Self::InvalidResponse => write!(f, "invalid socks response"),
+        }
+    }
+}
+
+impl error::Error for SocksError {}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test::{Capture, Mock},
+        futures_lite::future,
+        std::net::Ipv6Addr,
+    };
+
+    fn session(input: &'static [u8]) -> Session<Mock> {
+        Session {
+            addr: Address::http("proxy.local"),
+            io: Mock::new(input),
+        }
+    }
+
+    #[test]
+    fn socks5() -> Result<(), Error> {
+        let input = b"\x05\x00\x05\x00\x00\x01\x7f\x00\x00\x01\x1f\x90target";
+        let target = Address::https("example.com");
+        let socks = Socks5::new(target.clone(), Capture);
+        let (client, _) = future::block_on(socks.handshake(session(input)))?;
+
+        assert_eq!(client.se.addr, target);
+        assert_eq!(
+            client.se.io.output,
+            b"\x05\x01\x00\x05\x01\x00\x03\x0bexample.com\x01\xbb",
+        );
+
+        assert_eq!(client.se.io.rest(), b"target");
+        Ok(())
+    }
+
+    #[test]
+    fn socks5_auth() -> Result<(), Error> {
+        let input = b"\x05\x02\x01\x00\x05\x00\x00\x03\x04host\x00\x50";
+        let target = Address::http(Ipv6Addr::LOCALHOST);
+        let socks = Socks5::new(target, Capture).auth("user", "pw");
+        let (client, _) = future::block_on(socks.handshake(session(input)))?;
+
+        let mut expected = b"\x05\x02\x00\x02\x01\x04user\x02pw\x05\x01\x00\x04".to_vec();
+        expected.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        expected.extend_from_slice(b"\x00\x50");
+        assert_eq!(client.se.io.output, expected);
+        assert!(client.se.io.rest().is_empty(), "the response is read");
+        Ok(())
+    }
+
+    #[test]
+    fn socks5_errors() {
+        let cases: [(&[u8], _); 4] = [
+            (b"\x05\xff", io::ErrorKind::PermissionDenied),
+            (b"\x05\x02\x01\x01", io::ErrorKind::PermissionDenied),
+            (
+                b"\x05\x00\x05\x05\x00\x01",
+                io::ErrorKind::ConnectionRefused,
+            ),
+            (b"\x04\x00", io::ErrorKind::InvalidData),
+        ];
+
+        for (input, kind) in cases {
+            let socks = Socks5::new(Address::http("example.com"), Capture).auth("u", "p");
+            let Err(e) = future::block_on(socks.handshake(session(input))) else {
+                panic!("the handshake should fail");
+            };
+
+            assert!(e.is_connect(), "{e}");
+            let e = e.try_into_io().expect("io error");
+            assert_eq!(e.kind(), kind, "{e}");
+        }
+    }
+
+    #[test]
+    fn socks4() -> Result<(), Error> {
+        let input = b"\x00\x5a\x00\x00\x00\x00\x00\x00";
+        let target = Address::http("example.com");
+        let socks = Socks4::new(target, Capture).user("id");
+        let (client, _) = future::block_on(socks.handshake(session(input)))?;
+        assert_eq!(
+            client.se.io.output,
+            b"\x04\x01\x00\x50\x00\x00\x00\x01id\x00example.com\x00",
+        );
+
+        let input = b"\x00\x5b\x00\x00\x00\x00\x00\x00";
+        let target = Address::http(Ipv4Addr::LOCALHOST);
+        let socks = Socks4::new(target, Capture);
+        let Err(e) = future::block_on(socks.handshake(session(input))) else {
+            panic!("the request should be rejected");
+        };
+
+        assert!(e.is_connect());
+        let e = e.try_into_io().expect("io error");
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+        Ok(())
+    }
+}
@@ -0,0 +1,653 @@
+//! The server-sent events.
+
+use {
+    crate::{
+        body::{BodyExt, BoxedLocal, Hint, IntoBody, PollBody},
+        client::Client,
+        error::Error,
+        proto::Request,
+        time::Timer,
+    },
+    bytes::{Buf, BufMut, Bytes, BytesMut},
+    futures_lite::prelude::*,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, Uri, header},
+    std::{
+        io,
+        marker::PhantomData,
+        mem,
+        pin::Pin,
+        task::{Context, Poll, ready},
+        time::Duration,
+    },
+};
+
+/// The server-sent event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    event: Option<String>,
+    data: String,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl Event {
+    /// Returns the event type.
+    ///
+    /// If the type isn't specified, it's `message`.
+    pub fn event(&self) -> &str {
+        self.event.as_deref().unwrap_or("message")
+    }
+
+    /// Returns the event data.
+    ///
+    /// Lines of multi-line data are joined with `\n`.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Returns the last event ID.
+    ///
+    /// The ID is inherited from previous events
+    /// if the event doesn't set it.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the reconnection time set with the event.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Returns the event data.
+    pub fn into_data(self) -> String {
+        self.data
+    }
+}
+
+/// Parses the body as a stream of [events](Event).
+///
+/// # Example
+///
+/// ```
+/// use {
+///     areq::{Error, prelude::*, sse},
+///     futures_lite::StreamExt,
+/// };
+///
+/// async fn listen<C>(client: &mut C) -> Result<(), Error>
+/// where
+///     C: Client<()>,
+/// {
+///     let uri = "/events".parse().expect("valid uri");
+///     let res = client.get(uri, ()).await?;
+///
+///     let events = sse::events(res);
+///     let mut events = std::pin::pin!(events);
+///     while let Some(event) = events.next().await {
+///         println!("{}", event?.data());
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn events<B>(body: B) -> EventStream<impl PollBody<Chunk = B::Chunk>>
+where
+    B: IntoBody,
+{
+    EventStream::new(body.into_poll_body())
+}
+
+pin_project_lite::pin_project! {
+    /// The stream of server-sent [events](Event).
+    ///
+    /// Created by the [`events`] function.
+    pub struct EventStream<B> {
+        #[pin]
+        body: B,
+        buf: BytesMut,
+        block: Block,
+        last_id: String,
+        retry: Option<Duration>,
+        start: bool,
+        skip_lf: bool,
+    }
+}
+
+#[derive(Default)]
+struct Block {
+    event: Option<String>,
+    data: String,
+    retry: Option<Duration>,
+}
+
+impl<B> EventStream<B> {
+    /// Creates the stream from a polling body.
+    pub fn new(body: B) -> Self {
+        Self {
+            body,
+            buf: BytesMut::new(),
+            block: Block::default(),
+            last_id: String::new(),
+            retry: None,
+            start: true,
+            skip_lf: false,
+        }
+    }
+
+    /// Returns the last event ID received from the stream.
+    pub fn last_event_id(&self) -> Option<&str> {
+        non_empty(&self.last_id)
+    }
+
+    /// Returns the last reconnection time received from the stream.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+}
+
+impl<B> Stream for EventStream<B>
+where
+    B: PollBody,
+{
+    type Item = Result<Event, io::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut me = self.project();
+        loop {
+            while let Some(line) = next_line(me.buf, me.skip_lf) {
+                let mut line = &line[..];
+                if mem::take(me.start) {
+                    line = line.strip_prefix(BOM).unwrap_or(line);
+                }
+
+                let mut state = State {
+                    block: me.block,
+                    last_id: me.last_id,
+                    retry: me.retry,
+                };
+
+                if let Some(event) = state.line(line) {
+                    return Poll::Ready(Some(Ok(event)));
+                }
+            }
+
+            match ready!(me.body.as_mut().poll_chunk(cx)) {
+                Some(Ok(mut chunk)) => {
+                    while chunk.has_remaining() {
+                        let part = chunk.chunk();
+                        let n = part.len();
+                        me.buf.put_slice(part);
+                        chunk.advance(n);
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                // an incomplete event is discarded at the end
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Takes the next line terminated by `\r\n`, `\n` or `\r`.
+fn next_line(buf: &mut BytesMut, skip_lf: &mut bool) -> Option<BytesMut> {
+    if *skip_lf && !buf.is_empty() {
+        *skip_lf = false;
+        if buf[0] == b'\n' {
+            buf.advance(1);
+        }
+    }
+
+    let pos = buf.iter().position(|&b| b == b'\r' || b == b'\n')?;
+    let line = buf.split_to(pos);
+    if buf[0] == b'\r' {
+        // the following line feed may come in the next chunk
+        *skip_lf = true;
+    }
+
+    buf.advance(1);
+    Some(line)
+}
+
+struct State<'stream> {
+    block: &'stream mut Block,
+    last_id: &'stream mut String,
+    retry: &'stream mut Option<Duration>,
+}
+
+impl State<'_> {
+    fn line(&mut self, line: &[u8]) -> Option<Event> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+
+        // the line is a comment
+        if line[0] == b':' {
+            return None;
+        }
+
+        let (field, value) = match line.iter().position(|&b| b == b':') {
+            Some(pos) => {
+                let value = &line[pos + 1..];
+                (&line[..pos], value.strip_prefix(b" ").unwrap_or(value))
+            }
+            None => (line, &[][..]),
+        };
+
+        match field {
+            // an empty event type means the default one
+            b"event" => {
+                self.block.event =
+                    (!value.is_empty()).then(|| String::from_utf8_lossy(value).into_owned());
+            }
+            b"data" => {
+                self.block.data.push_str(&String::from_utf8_lossy(value));
+                self.block.data.push('\n');
+            }
+            b"id" if !value.contains(&0) => {
+                *self.last_id = String::from_utf8_lossy(value).into_owned();
+            }
+            b"retry" if !value.is_empty() && value.iter().all(u8::is_ascii_digit) => {
+                if let Some(ms) = std::str::from_utf8(value).ok().and_then(|s| s.parse().ok()) {
+                    let retry = Duration::from_millis(ms);
+                    self.block.retry = Some(retry);
+                    *self.retry = Some(retry);
+                }
+            }
+            // unknown fields are ignored
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let Block {
+            event,
+            mut data,
+            retry,
+        } = mem::take(self.block);
+        if data.is_empty() {
+            return None;
+        }
+
+        data.pop();
+        Some(Event {
+            event,
+            data,
+            id: non_empty(self.last_id).map(str::to_owned),
+            retry,
+        })
+    }
+}
+
+fn non_empty(s: &str) -> Option<&str> {
+    (!s.is_empty()).then_some(s)
+}
+
+/// The reconnecting source of server-sent [events](Event).
+///
+/// Sends `GET` requests to the uri and reads [events](Event) from
+/// response bodies. When a connection is lost, it waits for the
+/// reconnection time and sends the request again with the
+/// `Last-Event-ID` header, so the server can resume the stream.
+///
+/// # Example
+///
+/// ```
+/// use {
+///     areq::{Client, Error, sse::EventSource},
+///     std::time::Duration,
+/// };
+///
+/// async fn listen<C>(client: C, timer: impl Fn(Duration) -> std::future::Ready<()>)
+/// where
+///     C: Client<()>,
+///     C::Body: 'static,
+/// {
+///     let uri = "http://localhost:3001/events".parse().expect("valid uri");
+///     let mut source = EventSource::new(client, timer, uri);
+///     while let Some(event) = source.next().await {
+///         match event {
+///             Ok(event) => println!("{}", event.data()),
+///             Err(e) => eprintln!("error: {e}"),
+///         }
+///     }
+/// }
+/// ```
+pub struct EventSource<C, T, B = ()> {
+    client: C,
+    timer: T,
+    uri: Uri,
+    retry: Duration,
+    last_id: String,
+    state: Connection,
+    body: PhantomData<fn() -> B>,
+}
+
+enum Connection {
+    Connect { wait: bool },
+    Open(EventStream<Boxed>),
+    Closed,
+}
+
+impl<C, T, B> EventSource<C, T, B> {
+    /// Creates the event source.
+    ///
+    /// The [timer](Timer) is used to wait before reconnecting.
+    pub fn new(client: C, timer: T, uri: Uri) -> Self {
+        Self {
+            client,
+            timer,
+            uri,
+            retry: Duration::from_secs(3),
+            last_id: String::new(),
+            state: Connection::Connect { wait: false },
+            body: PhantomData,
+        }
+    }
+
+    /// Sets the initial reconnection time.
+    ///
+    /// The server can change it with the `retry` field.
+    /// By default, it's 3 seconds.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the last event ID to resume the stream from.
+    pub fn last_event_id<S>(mut self, id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.last_id = id.into();
+        self
+    }
+
+    /// Returns the last received event ID.
+    pub fn get_last_event_id(&self) -> Option<&str> {
+        non_empty(&self.last_id)
+    }
+
+    /// Closes the source, so no more events are received.
+    pub fn close(&mut self) {
+        self.state = Connection::Closed;
+    }
+
+    /// Returns the inner client.
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+
+    /// Receives the next event.
+    ///
+    /// Returns `None` if the source is closed. It happens
+    /// when the server responds with `204 No Content`.
+    ///
+    /// # Errors
+    /// Connection errors are returned, but the source reconnects
+    /// on the next call. If the server responds with an unsuccessful
+    /// status or a wrong content type, the error is returned and the
+    /// source is closed.
+    pub async fn next(&mut self) -> Option<Result<Event, Error>>
+    where
+        C: Client<B, Body: 'static>,
+        T: Timer,
+        B: Default,
+    {
+        loop {
+            match &mut self.state {
+                Connection::Connect { wait } => {
+                    if *wait {
+                        self.timer.sleep(self.retry).await;
+                    }
+
+                    match self.connect().await {
+                        Ok(Some(stream)) => self.state = Connection::Open(stream),
+                        Ok(None) => {
+                            self.state = Connection::Closed;
+                            return None;
+                        }
+                        Err(e) => {
+                            self.state = if e.is_status() || e.is_protocol() {
+                                Connection::Closed
+                            } else {
+                                Connection::Connect { wait: true }
+                            };
+
+                            return Some(Err(e));
+                        }
+                    }
+                }
+                Connection::Open(stream) => {
+                    let next = stream.next().await;
+                    if let Some(id) = stream.last_event_id() {
+                        id.clone_into(&mut self.last_id);
+                    }
+
+                    if let Some(retry) = stream.retry() {
+                        self.retry = retry;
+                    }
+
+                    match next {
+                        Some(Ok(event)) => return Some(Ok(event)),
+                        Some(Err(e)) => {
+                            self.state = Connection::Connect { wait: true };
+                            return Some(Err(Error::body(e)));
+                        }
+                        None => self.state = Connection::Connect { wait: true },
+                    }
+                }
+                Connection::Closed => return None,
+            }
+        }
+    }
+
+    async fn connect(&mut self) -> Result<Option<EventStream<Boxed>>, Error>
+    where
+        C: Client<B, Body: 'static>,
+        B: Default,
+    {
+        let mut req = Request::new(Method::GET, self.uri.clone(), B::default());
+        let headers = req.headers_mut();
+        headers.insert(
+            header::ACCEPT,
+            const { HeaderValue::from_static("text/event-stream") },
+        );
+
+        headers.insert(
+            header::CACHE_CONTROL,
+            const { HeaderValue::from_static("no-cache") },
+        );
+
+        if let Some(id) = non_empty(&self.last_id) {
+            if let Ok(id) = HeaderValue::from_str(id) {
+                headers.insert(LAST_EVENT_ID, id);
+            }
+        }
+
+        let res = self.client.send(req).await?;
+        if res.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+
+        if !res.status().is_success() {
+            return Err(Error::from_status(res.status()));
+        }
+
+        let event_stream = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("text/event-stream"));
+
+        if !event_stream {
+            let e = io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the content type isn't event stream",
+            );
+            return Err(Error::from(e));
+        }
+
+        let mut stream = EventStream::new(Boxed(res.boxed_local()));
+        stream.last_id.clone_from(&self.last_id);
+        Ok(Some(stream))
+    }
+}
+
+/// The boxed body polled by the event stream.
+struct Boxed(BoxedLocal<'static>);
+
+impl PollBody for Boxed {
+    type Chunk = Bytes;
+
+    fn poll_chunk(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Chunk, io::Error>>> {
+        self.0.as_mut().poll_chunk(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<HeaderMap, io::Error>>> {
+        self.0.as_mut().poll_trailers(cx)
+    }
+
+    fn size_hint(&self) -> Hint {
+        self.0.size_hint()
+    }
+}
+
+const LAST_EVENT_ID: header::HeaderName = header::HeaderName::from_static("last-event-id");
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            proto::Response,
+            test::{Scripted, status},
+        },
+        futures_lite::{future, stream},
+        std::cell::RefCell,
+    };
+
+    fn parse(chunks: &[&'static str]) -> Vec<Event> {
+        let body = stream::iter(chunks.iter().map(|s| Ok(Bytes::from_static(s.as_bytes()))));
+        let events = events(crate::body::Chunked(body));
+        future::block_on(events.map(|event| event.expect("read event")).collect())
+    }
+
+    fn event(event: Option<&str>, data: &str, id: Option<&str>) -> Event {
+        Event {
+            event: event.map(str::to_owned),
+            data: data.to_owned(),
+            id: id.map(str::to_owned),
+            retry: None,
+        }
+    }
+
+    #[test]
+    fn parse_events() {
+        let events = parse(&[
+            "\u{feff}: comment\n",
+            "data: first\n\n",
+            "event: update\r\ndata:multi\r\nda",
+            "ta: line\r\nid: 1\r\n\r",
+            "\ndata: {}\r\r",
+            "retry: 1500\nunknown: field\ndata\n\n",
+            "id\n\n",
+            "data: x\n\n",
+            "event:\ndata: x\n\n",
+            "data: incomplete",
+        ]);
+
+        let mut with_retry = event(None, "", Some("1"));
+        with_retry.retry = Some(Duration::from_millis(1500));
+
+        assert_eq!(
+            events,
+            [
+                event(None, "first", None),
+                event(Some("update"), "multi\nline", Some("1")),
+                event(None, "{}", Some("1")),
+                with_retry,
+                event(None, "x", None),
+                event(None, "x", None),
+            ],
+        );
+
+        assert_eq!(events[1].event(), "update");
+        assert_eq!(events[2].event(), "message");
+        assert_eq!(events[5].event(), "message");
+    }
+
+    fn stream_response(body: &'static str) -> Result<Response<Bytes>, Error> {
+        let res = http::Response::builder()
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .body(Bytes::from_static(body.as_bytes()))
+            .expect("valid response");
+
+        Ok(Response::new(res))
+    }
+
+    #[test]
+    fn reconnect() {
+        let client = Scripted::new([
+            stream_response("retry: 10\nid: 1\ndata: a\n\nid: 2\ndata: b\n\n"),
+            Err(Error::from(io::Error::from(
+                io::ErrorKind::ConnectionRefused,
+            ))),
+            stream_response("data: c\n\n"),
+            status(204),
+        ]);
+
+        let delays = RefCell::new(vec![]);
+        let timer = |dur| {
+            delays.borrow_mut().push(dur);
+            future::ready(())
+        };
+
+        let mut source = EventSource::new(client, timer, Uri::from_static("http://a/events"));
+        let mut next = || future::block_on(source.next());
+
+        assert_eq!(next().expect("event").expect("ok").data(), "a");
+        assert_eq!(next().expect("event").expect("ok").data(), "b");
+        assert!(next().expect("error").is_err());
+        let event = next().expect("event").expect("ok");
+        assert_eq!(event.data(), "c");
+        assert_eq!(event.id(), Some("2"));
+        assert!(next().is_none());
+        assert!(next().is_none());
+
+        let sent = source.into_inner().sent;
+        let ids: Vec<_> = sent
+            .iter()
+            .map(|req| req.headers().get(LAST_EVENT_ID))
+            .collect();
+
+        let id = HeaderValue::from_static("2");
+        assert_eq!(ids, [None, Some(&id), Some(&id), Some(&id)]);
+        assert_eq!(sent[0].headers()[header::ACCEPT], "text/event-stream");
+        assert_eq!(delays.into_inner(), [Duration::from_millis(10); 3]);
+    }
+
+    #[test]
+    fn wrong_status() {
+        let client = Scripted::new([status(404)]);
+
+        let timer = |_| future::ready(());
+        let mut source = EventSource::new(client, timer, Uri::from_static("http://a/events"));
+        let e = future::block_on(source.next())
+            .expect("error")
+            .expect_err("not found");
+
+        assert!(e.is_status());
+        assert!(future::block_on(source.next()).is_none());
+    }
+}
@@ -0,0 +1,165 @@
+use {
+    crate::{
+        client::Client,
+        error::Error,
+        proto::{Handshake, Request, Response, Session},
+    },
+    bytes::Bytes,
+    futures_lite::prelude::*,
+    http::StatusCode,
+    std::{
+        collections::VecDeque,
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+};
+
+/// The connection with a prepared input that records the output.
+pub(crate) struct Mock {
+    input: io::Cursor<&'static [u8]>,
+    pub output: Vec<u8>,
+}
+
+impl Mock {
+    pub fn new(input: &'static [u8]) -> Self {
+        Self {
+            input: io::Cursor::new(input),
+            output: vec![],
+        }
+    }
+
+    /// Returns the unread input.
+    pub fn rest(&self) -> &[u8] {
+        let pos = usize::try_from(self.input.position()).expect("the position should fit");
+        &self.input.get_ref()[pos..]
+    }
+}
+
+impl AsyncRead for Mock {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(io::Read::read(&mut self.input, buf))
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.output.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// The handshake that keeps the session and records requests.
+pub(crate) struct Capture;
+
+pub(crate) struct Captured {
+    pub se: Session<Mock>,
+    pub reqs: Vec<Request<()>>,
+}
+
+impl Handshake<Mock, ()> for Capture {
+    type Client = Captured;
+
+    async fn handshake(
+        self,
+        se: Session<Mock>,
+    ) -> Result<(Self::Client, impl Future<Output = ()>), Error> {
+        let client = Captured { se, reqs: vec![] };
+        Ok((client, async {}))
+    }
+}
+
+impl Client<()> for Captured {
+    type Body = Bytes;
+
+    async fn send(&mut self, req: Request<()>) -> Result<Response<Self::Body>, Error> {
+        self.reqs.push(req);
+        Ok(Response::new(http::Response::new(Bytes::new())))
+    }
+}
+
+/// The client that responds with scripted results and records requests.
+pub(crate) struct Scripted {
+    results: VecDeque<Result<Response<Bytes>, Error>>,
+    pub sent: Vec<Request<()>>,
+}
+
+impl Scripted {
+    pub fn new<I>(results: I) -> Self
+    where
+        I: IntoIterator<Item = Result<Response<Bytes>, Error>>,
+    {
+        Self {
+            results: results.into_iter().collect(),
+            sent: vec![],
+        }
+    }
+}
+
+impl Client<()> for Scripted {
+    type Body = Bytes;
+
+    async fn send(&mut self, req: Request<()>) -> Result<Response<Self::Body>, Error> {
+        self.sent.push(req);
+        self.results.pop_front().expect("scripted result")
+    }
+}
+
+/// Creates the empty response with the given status.
+pub(crate) fn status(code: u16) -> Result<Response<Bytes>, Error> {
+    let mut res = http::Response::new(Bytes::new());
+    *res.status_mut() = StatusCode::from_u16(code).expect("valid status");
+    Ok(Response::new(res))
+}
+
+/// The in-memory connection to an http/2 server in tests.
+#[cfg(feature = "http2")]
+pub(crate) struct Duplex(pub tokio::io::DuplexStream);
+
+#[cfg(feature = "http2")]
+impl AsyncRead for Duplex {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        let read = tokio::io::AsyncRead::poll_read(Pin::new(&mut self.0), cx, &mut buf);
+        read.map_ok(|()| buf.filled().len())
+    }
+}
+
+#[cfg(feature = "http2")]
+impl AsyncWrite for Duplex {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+    }
+}
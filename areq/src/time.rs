@@ -0,0 +1,418 @@
+//! Time utilities.
+
+use {
+    crate::{
+        body::{Body, Hint},
+        client::Client,
+        error::{Error, ErrorKind},
+        proto::{Request, Response},
+    },
+    futures_lite::future,
+    http::HeaderMap,
+    std::{
+        error, fmt, io,
+        time::{Duration, Instant},
+    },
+};
+
+#[cfg(any(feature = "http1", feature = "http2"))]
+use std::{pin::Pin, sync::Arc};
+
+/// The runtime-agnostic timer.
+///
+/// The trait is implemented for closures that take
+/// a [duration](Duration) and return a future that
+/// completes after it elapses. The future is `Send`,
+/// so connections waiting on it can run on any executor.
+pub trait Timer {
+    /// Waits until the duration has elapsed.
+    fn sleep(&self, dur: Duration) -> impl Future<Output = ()> + Send;
+}
+
+impl<F, U> Timer for F
+where
+    F: Fn(Duration) -> U,
+    U: Future + Send,
+{
+    #[inline]
+    fn sleep(&self, dur: Duration) -> impl Future<Output = ()> + Send {
+        let sleep = self(dur);
+        async {
+            sleep.await;
+        }
+    }
+}
+
+#[cfg(any(feature = "http1", feature = "http2"))]
+type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The type-erased [timer](Timer) stored by connection clients.
+#[cfg(any(feature = "http1", feature = "http2"))]
+#[derive(Clone)]
+pub(crate) struct SharedTimer(Arc<dyn Fn(Duration) -> Sleep + Send + Sync>);
+
+#[cfg(any(feature = "http1", feature = "http2"))]
+impl SharedTimer {
+    pub fn new<T>(timer: T) -> Self
+    where
+        T: Timer + Send + Sync + 'static,
+    {
+        let timer = Arc::new(timer);
+        Self(Arc::new(move |dur| {
+            let timer = timer.clone();
+            Box::pin(async move { timer.sleep(dur).await })
+        }))
+    }
+
+    /// Returns the sleep future which doesn't borrow the timer.
+    pub fn sleep(&self, dur: Duration) -> Sleep {
+        (self.0)(dur)
+    }
+}
+
+/// Runs the future until it completes or the duration elapses.
+///
+/// Returns `None` if the time is out.
+pub async fn timeout<T, F>(timer: &T, dur: Duration, fut: F) -> Option<F::Output>
+where
+    T: Timer,
+    F: Future,
+{
+    let fut = async { Some(fut.await) };
+    let sleep = async {
+        timer.sleep(dur).await;
+        None
+    };
+
+    future::or(fut, sleep).await
+}
+
+/// The timeout error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Elapsed {
+    /// The connection is not established in time.
+    Connect,
+    /// The TLS or protocol handshake is not completed in time.
+    Handshake,
+    /// The response head is not received in time.
+    FirstByte,
+    /// The next body chunk is not received in time.
+    BodyIdle,
+    /// The request with its response body is not completed in time.
+    Total,
+}
+
+impl From<Elapsed> for io::Error {
+    fn from(e: Elapsed) -> Self {
+        Self::new(io::ErrorKind::TimedOut, e)
+    }
+}
+
+impl From<Elapsed> for Error {
+    fn from(e: Elapsed) -> Self {
+        Self::new(ErrorKind::Timeout, e)
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect => write!(f, "connect timed out"),
+            Self::Handshake => write!(f, "handshake timed out"),
+            Self::FirstByte => write!(f, "response timed out"),
+            Self::BodyIdle => write!(f, "body read timed out"),
+            Self::Total => write!(f, "request deadline exceeded"),
+        }
+    }
+}
+
+impl error::Error for Elapsed {}
+
+/// The client with request timeouts.
+///
+/// The time-to-first-byte timeout limits waiting for the response
+/// head, the body idle timeout limits waiting for each body chunk,
+/// and the total deadline limits the entire request including reading
+/// of the response body. Each timeout produces its own [error](Elapsed)
+/// with the [`TimedOut`](io::ErrorKind::TimedOut) kind.
+pub struct Timeout<C, T> {
+    client: C,
+    timer: T,
+    first_byte: Option<Duration>,
+    body_idle: Option<Duration>,
+    total: Option<Duration>,
+}
+
+impl<C, T> Timeout<C, T> {
+    /// Wraps the client without any timeouts set.
+    #[inline]
+    pub fn new(client: C, timer: T) -> Self {
+        Self {
+            client,
+            timer,
+            first_byte: None,
+            body_idle: None,
+            total: None,
+        }
+    }
+
+    /// Sets the time-to-first-byte timeout.
+    #[inline]
+    pub fn first_byte(mut self, dur: Duration) -> Self {
+        self.first_byte = Some(dur);
+        self
+    }
+
+    /// Sets the body idle timeout.
+    #[inline]
+    pub fn body_idle(mut self, dur: Duration) -> Self {
+        self.body_idle = Some(dur);
+        self
+    }
+
+    /// Sets the total deadline.
+    #[inline]
+    pub fn total(mut self, dur: Duration) -> Self {
+        self.total = Some(dur);
+        self
+    }
+
+    /// Returns the inner client.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+}
+
+impl<B, C, T> Client<B> for Timeout<C, T>
+where
+    C: Client<B>,
+    T: Timer + Clone,
+{
+    type Body = TimeoutBody<C::Body, T>;
+
+    async fn send(&mut self, req: Request<B>) -> Result<Response<Self::Body>, Error> {
+        let deadline = self.total.map(|total| Instant::now() + total);
+        let limit = earliest(
+            self.first_byte.map(|dur| (dur, Elapsed::FirstByte)),
+            deadline.map(|deadline| (remaining(deadline), Elapsed::Total)),
+        );
+
+        let res = match limit {
+            Some((dur, e)) => timeout(&self.timer, dur, self.client.send(req))
+                .await
+                .ok_or(e)??,
+            None => self.client.send(req).await?,
+        };
+
+        Ok(res.map(|body| TimeoutBody {
+            body,
+            timer: self.timer.clone(),
+            idle: self.body_idle,
+            deadline,
+        }))
+    }
+
+    #[inline]
+    fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            client: self.client.try_clone()?,
+            timer: self.timer.clone(),
+            first_byte: self.first_byte,
+            body_idle: self.body_idle,
+            total: self.total,
+        })
+    }
+
+    #[inline]
+    fn is_reusable(&self) -> bool {
+        self.client.is_reusable()
+    }
+}
+
+/// The response body of the [`Timeout`] client.
+pub struct TimeoutBody<B, T> {
+    body: B,
+    timer: T,
+    idle: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl<B, T> TimeoutBody<B, T> {
+    /// Returns the inner body.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.body
+    }
+
+    fn limit(&self) -> Option<(Duration, Elapsed)> {
+        earliest(
+            self.idle.map(|dur| (dur, Elapsed::BodyIdle)),
+            self.deadline
+                .map(|deadline| (remaining(deadline), Elapsed::Total)),
+        )
+    }
+}
+
+impl<B, T> Body for TimeoutBody<B, T>
+where
+    B: Body,
+    T: Timer,
+{
+    type Chunk = B::Chunk;
+
+    async fn chunk(&mut self) -> Option<Result<Self::Chunk, io::Error>> {
+        match self.limit() {
+            Some((dur, e)) => match timeout(&self.timer, dur, self.body.chunk()).await {
+                Some(chunk) => chunk,
+                None => Some(Err(e.into())),
+            },
+            None => self.body.chunk().await,
+        }
+    }
+
+    async fn trailers(&mut self) -> Option<Result<HeaderMap, io::Error>> {
+        match self.limit() {
+            Some((dur, e)) => match timeout(&self.timer, dur, self.body.trailers()).await {
+                Some(trailers) => trailers,
+                None => Some(Err(e.into())),
+            },
+            None => self.body.trailers().await,
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Hint {
+        self.body.size_hint()
+    }
+}
+
+fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
+}
+
+fn earliest(
+    a: Option<(Duration, Elapsed)>,
+    b: Option<(Duration, Elapsed)>,
+) -> Option<(Duration, Elapsed)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.0 <= a.0 { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ClientExt, body::BodyExt},
+        bytes::Bytes,
+        futures_lite::{prelude::*, stream},
+        http::Uri,
+    };
+
+    /// The timer that elapses only for the given duration.
+    #[derive(Clone)]
+    struct Only(Duration);
+
+    impl Timer for Only {
+        async fn sleep(&self, dur: Duration) {
+            if dur != self.0 {
+                future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// The client that responds after a delay with a slow body.
+    struct Slow {
+        head: bool,
+        body: bool,
+    }
+
+    impl Client<()> for Slow {
+        type Body = areq_body::Chunked<stream::Boxed<Result<Bytes, io::Error>>>;
+
+        async fn send(&mut self, _: Request<()>) -> Result<Response<Self::Body>, Error> {
+            if !self.head {
+                future::pending::<()>().await;
+            }
+
+            let body = self.body;
+            let chunks =
+                stream::once(Ok(Bytes::from_static(b"a"))).chain(stream::once_future(async move {
+                    if !body {
+                        future::pending::<()>().await;
+                    }
+
+                    Ok(Bytes::from_static(b"b"))
+                }));
+
+            let body = areq_body::Chunked(chunks.boxed());
+            Ok(Response::new(http::Response::new(body)))
+        }
+    }
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    fn elapsed(e: &io::Error) -> Option<Elapsed> {
+        e.get_ref()?.downcast_ref().copied()
+    }
+
+    #[test]
+    fn first_byte() {
+        let slow = Slow {
+            head: false,
+            body: true,
+        };
+        let mut client = Timeout::new(slow, Only(SEC)).first_byte(SEC);
+        let res = future::block_on(client.get(Uri::from_static("/"), ()));
+        let Err(e) = res else {
+            panic!("the request should time out");
+        };
+
+        assert!(e.is_timeout());
+        let source = e.get_ref().and_then(|e| e.downcast_ref()).copied();
+        assert_eq!(source, Some(Elapsed::FirstByte));
+    }
+
+    #[test]
+    fn body_idle() -> Result<(), Error> {
+        let slow = Slow {
+            head: true,
+            body: false,
+        };
+        let mut client = Timeout::new(slow, Only(SEC))
+            .first_byte(SEC * 2)
+            .body_idle(SEC);
+        let e = future::block_on(async {
+            let res = client.get(Uri::from_static("/"), ()).await?;
+            let mut body = http::Response::from(res).into_body();
+            let chunk = body.chunk().await.transpose()?;
+            assert_eq!(chunk.as_deref(), Some(&b"a"[..]));
+            Ok::<_, Error>(body.text().await.expect_err("the body should time out"))
+        })?;
+
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(elapsed(&e), Some(Elapsed::BodyIdle));
+
+        let slow = Slow {
+            head: true,
+            body: true,
+        };
+        let mut client = Timeout::new(slow, Only(SEC)).body_idle(SEC);
+        let text =
+            future::block_on(async { client.get(Uri::from_static("/"), ()).await?.text().await })?;
+        assert_eq!(text, "ab");
+        Ok(())
+    }
+
+    #[test]
+    fn earliest_limit() {
+        let a = Some((SEC, Elapsed::FirstByte));
+        let b = Some((SEC * 2, Elapsed::Total));
+        assert_eq!(earliest(a, b), a);
+        assert_eq!(earliest(b, a), a);
+        assert_eq!(earliest(None, b), b);
+        assert_eq!(earliest(None, None), None);
+    }
+}
@@ -1,7 +1,10 @@
 //! The http client over TLS.
 
 use {
-    crate::proto::{Error, Handshake, Session},
+    crate::{
+        error::{Error, ErrorKind},
+        proto::{Handshake, Session},
+    },
     futures_lite::prelude::*,
     futures_rustls::{
         TlsConnector,
@@ -9,7 +12,7 @@ use {
         pki_types::ServerName,
         rustls::{ClientConfig, RootCertStore},
     },
-    std::{io, sync::Arc},
+    std::{error, fmt, io, sync::Arc},
     url::Host,
 };
 
@@ -100,7 +103,7 @@ where
         let Session { addr, io } = se;
 
         let name = as_server_name(&addr.host)?.to_owned();
-        let tls = self.connector.connect(name, io).await?;
+        let tls = self.connector.connect(name, io).await.map_err(Error::tls)?;
 
         let (_, conn) = tls.get_ref();
         let proto = conn
@@ -112,7 +115,7 @@ where
         let handshake = self
             .inner
             .negotiate(proto)
-            .ok_or_else(|| Error::UnsupportedProtocol(Box::from(proto)))?;
+            .ok_or_else(|| Error::new(ErrorKind::Tls, UnsupportedProtocol(Box::from(proto))))?;
 
         let se = Session { addr, io: tls };
         let (client, conn) = handshake.handshake(se).await?;
@@ -120,10 +123,30 @@ where
     }
 }
 
+/// The negotiated protocol isn't supported.
+#[derive(Debug)]
+struct UnsupportedProtocol(Box<[u8]>);
+
+impl fmt::Display for UnsupportedProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported protocol: ")?;
+        for chunk in self.0.utf8_chunks() {
+            write!(f, "{}", chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                write!(f, "{}", char::REPLACEMENT_CHARACTER)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl error::Error for UnsupportedProtocol {}
+
 fn as_server_name(host: &Host) -> Result<ServerName<'_>, Error> {
     match host {
         Host::Domain(domain) => {
-            ServerName::try_from(domain.as_str()).map_err(|_| Error::InvalidHost)
+            ServerName::try_from(domain.as_str()).map_err(|_| Error::invalid_host())
         }
         Host::Ipv4(ip) => Ok(ServerName::from(*ip)),
         Host::Ipv6(ip) => Ok(ServerName::from(*ip)),